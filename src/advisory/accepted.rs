@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::config::AcceptedAdvisory;
+use crate::error::{GuardianError, Result};
+use crate::models::{AcceptedIssue, SecurityIssue};
+
+/// Advisories accepted in the configuration whose acceptance has not
+/// expired, by ID.
+#[derive(Debug, Default)]
+pub struct AcceptedAdvisories {
+    active: BTreeMap<String, AcceptedAdvisory>,
+}
+
+impl AcceptedAdvisories {
+    /// Validates `entries` and drops those that have expired, warning about
+    /// each: their advisories are reported again.
+    pub fn new(entries: &[AcceptedAdvisory]) -> Result<Self> {
+        let now = SystemTime::now();
+        let mut active = BTreeMap::new();
+
+        for entry in entries {
+            if !is_advisory_id(&entry.id) {
+                return Err(GuardianError::Config {
+                    message: format!("`{}` is not a RustSec advisory ID", entry.id),
+                    source: None,
+                });
+            }
+            if let Some(expires) = &entry.expires {
+                let date = humantime::parse_rfc3339_weak(&format!("{} 00:00:00", expires))
+                    .map_err(|err| {
+                        let message =
+                            format!("{}: `{}` is not a YYYY-MM-DD date", entry.id, expires);
+                        GuardianError::config(message, err)
+                    })?;
+                if date <= now {
+                    warn!(
+                        "Acceptance of {} expired on {}, reporting it again",
+                        entry.id, expires
+                    );
+                    continue;
+                }
+            }
+            if active.insert(entry.id.clone(), entry.clone()).is_some() {
+                return Err(GuardianError::Config {
+                    message: format!("{} is accepted more than once", entry.id),
+                    source: None,
+                });
+            }
+        }
+
+        Ok(Self { active })
+    }
+
+    /// The acceptance covering `issue`, when it comes from an accepted
+    /// advisory.
+    pub fn accept(&self, issue: &SecurityIssue) -> Option<AcceptedIssue> {
+        let entry = self.active.get(issue.advisory_id.as_ref()?)?;
+        Some(AcceptedIssue {
+            issue: issue.clone(),
+            reason: entry.reason.clone(),
+            expires: entry.expires.clone(),
+        })
+    }
+
+    /// Warns about acceptances that no reported advisory matched, which
+    /// are likely stale or mistyped.
+    pub fn warn_unmatched(&self, accepted: &BTreeMap<String, Vec<AcceptedIssue>>) {
+        for id in self.active.keys() {
+            let matched = accepted
+                .values()
+                .flatten()
+                .any(|accepted| accepted.issue.advisory_id.as_ref() == Some(id));
+            if !matched {
+                warn!("Accepted advisory {} does not affect any dependency", id);
+            }
+        }
+    }
+}
+
+/// RustSec IDs look like `RUSTSEC-2021-0001`.
+fn is_advisory_id(id: &str) -> bool {
+    let Some(rest) = id.strip_prefix("RUSTSEC-") else {
+        return false;
+    };
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    match rest.split_once('-') {
+        Some((year, number)) => year.len() == 4 && digits(year) && digits(number),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, expires: Option<&str>) -> AcceptedAdvisory {
+        AcceptedAdvisory {
+            id: id.to_string(),
+            expires: expires.map(str::to_string),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn drops_expired_acceptances() {
+        let accepted = AcceptedAdvisories::new(&[
+            entry("RUSTSEC-2024-0001", None),
+            entry("RUSTSEC-2024-0002", Some("2999-12-31")),
+            entry("RUSTSEC-2024-0003", Some("2020-01-01")),
+        ])
+        .unwrap();
+
+        let ids: Vec<&str> = accepted.active.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["RUSTSEC-2024-0001", "RUSTSEC-2024-0002"]);
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        for entries in [
+            vec![entry("CVE-2024-0001", None)],
+            vec![entry("RUSTSEC-24-0001", None)],
+            vec![entry("RUSTSEC-2024-0001", Some("next year"))],
+            vec![
+                entry("RUSTSEC-2024-0001", None),
+                entry("RUSTSEC-2024-0001", Some("2999-12-31")),
+            ],
+        ] {
+            assert!(AcceptedAdvisories::new(&entries).is_err(), "{:?}", entries);
+        }
+    }
+
+    #[test]
+    fn accepts_only_issues_of_accepted_advisories() {
+        let accepted = AcceptedAdvisories::new(&[entry("RUSTSEC-2024-0001", None)]).unwrap();
+        let issue = |id: &str| SecurityIssue {
+            advisory_id: Some(id.to_string()),
+            ..Default::default()
+        };
+
+        assert!(accepted.accept(&issue("RUSTSEC-2024-0001")).is_some());
+        assert!(accepted.accept(&issue("RUSTSEC-2024-0002")).is_none());
+        assert!(accepted.accept(&SecurityIssue::default()).is_none());
+    }
+}
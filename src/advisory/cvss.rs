@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+/// Computes the CVSS v3.x base score of a vector such as
+/// `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`, following section 7 of
+/// the CVSS v3.1 specification.
+///
+/// Returns `None` for other CVSS versions or malformed vectors.
+pub fn base_score(vector: &str) -> Option<f32> {
+    let mut parts = vector.split('/');
+    let version = parts.next()?;
+    if version != "CVSS:3.0" && version != "CVSS:3.1" {
+        return None;
+    }
+
+    let metrics: HashMap<&str, &str> = parts
+        .map(|part| part.split_once(':'))
+        .collect::<Option<_>>()?;
+
+    let scope_changed = match *metrics.get("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match *metrics.get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges_required = match (*metrics.get("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match *metrics.get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_metric = |key| match *metrics.get(key)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+
+    let confidentiality: f64 = impact_metric("C")?;
+    let integrity: f64 = impact_metric("I")?;
+    let availability: f64 = impact_metric("A")?;
+
+    let iss = 1.0 - (1.0 - confidentiality) * (1.0 - integrity) * (1.0 - availability);
+    let impact = if scope_changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    let exploitability =
+        8.22 * attack_vector * attack_complexity * privileges_required * user_interaction;
+
+    let score = if impact <= 0.0 {
+        0.0
+    } else if scope_changed {
+        round_up((1.08 * (impact + exploitability)).min(10.0))
+    } else {
+        round_up((impact + exploitability).min(10.0))
+    };
+
+    Some(score as f32)
+}
+
+/// The CVSS v3.1 `Roundup` function: the smallest one-decimal number
+/// greater than or equal to `value`, robust to floating point error.
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as u64;
+    if scaled.is_multiple_of(10_000) {
+        scaled as f64 / 100_000.0
+    } else {
+        ((scaled / 10_000) + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+
+    #[test]
+    fn scores_known_vectors() {
+        let vectors = [
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H", 9.8),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H", 10.0),
+            ("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H", 7.8),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N", 6.1),
+            ("CVSS:3.0/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:N", 5.9),
+            ("CVSS:3.1/AV:P/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N", 1.6),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N", 0.0),
+        ];
+
+        for (vector, score) in vectors {
+            assert_eq!(base_score(vector), Some(score), "{}", vector);
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions_and_malformed_vectors() {
+        assert_eq!(base_score("CVSS:2.0/AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(base_score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N"), None);
+        // Missing, unknown and unparsable metrics
+        assert_eq!(base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H"), None);
+        assert_eq!(
+            base_score("CVSS:3.1/AV:X/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            None
+        );
+        assert_eq!(base_score("CVSS:3.1/AV"), None);
+    }
+
+    #[test]
+    fn rounds_up_to_one_decimal() {
+        assert_eq!(round_up(4.02), 4.1);
+        assert_eq!(round_up(4.0), 4.0);
+        // Floating point error below the precision is not rounded up
+        assert_eq!(round_up(4.000_000_1), 4.0);
+    }
+
+    #[test]
+    fn severity_bands_follow_the_qualitative_scale() {
+        let bands = [
+            (0.0, Severity::Info),
+            (0.1, Severity::Low),
+            (3.9, Severity::Low),
+            (4.0, Severity::Medium),
+            (6.9, Severity::Medium),
+            (7.0, Severity::High),
+            (8.9, Severity::High),
+            (9.0, Severity::Critical),
+            (10.0, Severity::Critical),
+        ];
+
+        for (score, severity) in bands {
+            assert_eq!(Severity::from_cvss(score), severity, "{}", score);
+        }
+    }
+}
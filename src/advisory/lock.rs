@@ -0,0 +1,47 @@
+use std::fs::{self, File, TryLockError};
+use std::path::Path;
+
+use crate::error::{GuardianError, Result};
+
+/// A lock on the shared advisory database checkout, held through a file
+/// lock on `path` until dropped. Processes updating the checkout hold it
+/// exclusively, those reading it shared, so that no process parses a
+/// half-updated checkout and no two update it at once.
+pub struct DbLock {
+    _file: File,
+}
+
+impl DbLock {
+    /// Waits until no other process is updating the checkout.
+    pub async fn shared(path: &Path) -> Result<Self> {
+        let file = open(path)?;
+        let locked = tokio::task::spawn_blocking(move || file.lock_shared().map(|()| file)).await;
+        match locked {
+            Ok(Ok(file)) => Ok(Self { _file: file }),
+            Ok(Err(err)) => Err(GuardianError::io(path, err)),
+            Err(err) => Err(GuardianError::io(path, err.into())),
+        }
+    }
+
+    /// The exclusive lock, or `None` when another process holds the lock.
+    pub fn try_exclusive(path: &Path) -> Result<Option<Self>> {
+        let file = open(path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => Err(GuardianError::io(path, err)),
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| GuardianError::io(parent, err))?;
+    }
+    File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|err| GuardianError::io(path, err))
+}
@@ -0,0 +1,672 @@
+use semver::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
+
+use crate::cache::cache_root;
+use crate::error::{BoxError, GuardianError, Result};
+use crate::models::{FixKind, SecurityIssue, Severity};
+use crate::retry::{retry, RetryPolicy, Transient};
+
+mod accepted;
+mod cvss;
+mod lock;
+
+pub use accepted::AcceptedAdvisories;
+use lock::DbLock;
+
+const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db.git";
+
+/// A single RustSec advisory, reduced to the fields the analyzer needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: String,
+    pub date: String,
+    pub url: Option<String>,
+    pub aliases: Vec<String>,
+    pub cvss: Option<String>,
+    pub informational: Option<String>,
+    pub withdrawn: Option<String>,
+    pub patched: Vec<VersionReq>,
+    pub unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// Returns true when `version` is neither patched nor unaffected.
+    pub fn affects(&self, version: &Version) -> bool {
+        if self.withdrawn.is_some() {
+            return false;
+        }
+
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| req_matches(req, version))
+    }
+
+    /// The patched release to upgrade to from `version`: the lowest one
+    /// above it that satisfies every requirement on the crate in
+    /// `requirements`, or else the lowest one above it.
+    pub fn fix_version(&self, version: &Version, requirements: &[&VersionReq]) -> Option<Version> {
+        let fixes: Vec<Version> = self
+            .patched
+            .iter()
+            .filter_map(lower_bound)
+            .filter(|fixed| fixed > version)
+            .collect();
+        let compatible = fixes
+            .iter()
+            .filter(|fixed| requirements.iter().all(|req| req.matches(fixed)))
+            .min();
+        compatible.or(fixes.iter().min()).cloned()
+    }
+
+    /// Severity from the CVSS score; informational advisories are Low when
+    /// the crate is unmaintained and Info otherwise.
+    pub fn severity(&self) -> Severity {
+        match self.informational.as_deref() {
+            Some("unmaintained") => return Severity::Low,
+            Some(_) => return Severity::Info,
+            None => {}
+        }
+
+        match self.cvss_score() {
+            Some(score) => Severity::from_cvss(score),
+            // Unscored vulnerabilities are still known vulnerabilities
+            None => Severity::High,
+        }
+    }
+
+    /// Base score computed from the advisory's CVSS vector.
+    pub fn cvss_score(&self) -> Option<f32> {
+        self.cvss.as_deref().and_then(cvss::base_score)
+    }
+
+    /// The finding for a package at `version`, which the packages depending
+    /// on it require to match `requirements`.
+    pub fn to_issue(&self, version: &Version, requirements: &[&VersionReq]) -> SecurityIssue {
+        let mut description = format!("{}: {}", self.id, self.title);
+        if !self.aliases.is_empty() {
+            description.push_str(&format!(" ({})", self.aliases.join(", ")));
+        }
+
+        let fix = self.fix_version(version, requirements);
+        SecurityIssue {
+            severity: self.severity(),
+            description,
+            affected_versions: vec![version.to_string()],
+            fix_version: fix.as_ref().map(Version::to_string),
+            fix_kind: fix.as_ref().map(|fix| FixKind::between(version, fix)),
+            advisory_id: Some(self.id.clone()),
+            informational: self.informational.clone(),
+            cvss_score: self.cvss_score(),
+            cvss_vector: self.cvss.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Parsed advisories indexed by crate name.
+pub struct AdvisoryDatabase {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Opens the advisory database.
+    ///
+    /// When `path` is given it is treated as a pre-cloned copy of
+    /// `advisory-db` and used as-is, without touching the network.
+    /// Otherwise the database is cloned into (or updated inside) the user
+    /// cache directory when it was last updated more than `ttl` ago,
+    /// retrying transient failures per `policy`; if that fails, or
+    /// `offline` is set, an existing local copy is used. Processes sharing
+    /// the cache directory update it one at a time.
+    #[cfg_attr(
+        feature = "otel",
+        tracing::instrument(
+            name = "advisory_database",
+            target = "telemetry",
+            skip_all,
+            fields(offline = offline, advisories)
+        )
+    )]
+    pub async fn open(
+        path: Option<&Path>,
+        offline: bool,
+        ttl: Duration,
+        policy: &RetryPolicy,
+    ) -> Result<Self> {
+        let db = match path {
+            Some(path) => Self::load(path).await,
+            None => Self::open_shared(offline, ttl, policy)
+                .await
+                .map_err(GuardianError::Advisory),
+        }?;
+        #[cfg(feature = "otel")]
+        tracing::Span::current().record(
+            "advisories",
+            db.advisories.values().map(Vec::len).sum::<usize>(),
+        );
+        Ok(db)
+    }
+
+    async fn open_shared(
+        offline: bool,
+        ttl: Duration,
+        policy: &RetryPolicy,
+    ) -> Result<Self, BoxError> {
+        let db_path = default_db_path()?;
+        if !offline {
+            update(&db_path, ttl, policy).await?;
+        }
+
+        // Waits for an update by another process to finish
+        let _lock = DbLock::shared(&cache_root()?.join("advisory-db.lock")).await?;
+        if !db_path.exists() {
+            return Err(format!("No local advisory database at {}", db_path.display()).into());
+        }
+        Self::load_checkout(&db_path).await
+    }
+
+    /// The git revision of the checkout [`open`](Self::open) loads, if it
+    /// is a git checkout.
+    pub async fn revision(path: Option<&Path>) -> Option<String> {
+        match path {
+            Some(path) => revision(path).await,
+            None => revision(&default_db_path().ok()?).await,
+        }
+    }
+
+    /// Loads advisories from a checkout, reusing the parsed cache when the
+    /// checkout's revision has not changed.
+    pub async fn load(db_path: &Path) -> Result<Self> {
+        Self::load_checkout(db_path)
+            .await
+            .map_err(GuardianError::Advisory)
+    }
+
+    async fn load_checkout(db_path: &Path) -> Result<Self, BoxError> {
+        let revision = revision(db_path).await;
+        let cache_path = parsed_cache_path()?;
+
+        if let Some(revision) = &revision {
+            if let Some(advisories) = read_parsed_cache(&cache_path, db_path, revision) {
+                debug!("Using cached advisories for revision {}", revision);
+                return Ok(Self::from_advisories(advisories));
+            }
+        }
+
+        let advisories = parse_db(db_path)?;
+        info!("Loaded {} advisories from {}", advisories.len(), db_path.display());
+
+        if let Some(revision) = revision {
+            if let Err(err) = write_parsed_cache(&cache_path, db_path, &revision, &advisories) {
+                warn!("Failed to cache parsed advisories: {}", err);
+            }
+        }
+
+        Ok(Self::from_advisories(advisories))
+    }
+
+    fn from_advisories(advisories: Vec<Advisory>) -> Self {
+        let mut by_package: HashMap<String, Vec<Advisory>> = HashMap::new();
+        for advisory in advisories {
+            by_package
+                .entry(advisory.package.clone())
+                .or_default()
+                .push(advisory);
+        }
+
+        Self {
+            advisories: by_package,
+        }
+    }
+
+    /// Returns every advisory affecting `package_name` at `version`.
+    pub fn lookup(&self, package_name: &str, version: &Version) -> Vec<Advisory> {
+        self.advisories
+            .get(package_name)
+            .map(|advisories| {
+                advisories
+                    .iter()
+                    .filter(|advisory| advisory.affects(version))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Clones or updates the shared checkout at `path`, unless it was updated
+/// within `ttl` or another process is updating it already.
+async fn update(path: &Path, ttl: Duration, policy: &RetryPolicy) -> Result<(), BoxError> {
+    let stamp = cache_root()?.join("advisory-db.updated");
+    if is_fresh(path, &stamp, ttl) {
+        debug!("Advisory database is up to date, not fetching");
+        return Ok(());
+    }
+    let Some(_lock) = DbLock::try_exclusive(&cache_root()?.join("advisory-db.lock"))? else {
+        info!("Advisory database in use by another process, not updating it");
+        return Ok(());
+    };
+    // The lock may have just been released by a process that updated it
+    if is_fresh(path, &stamp, ttl) {
+        return Ok(());
+    }
+
+    let fetched = retry(policy, "Fetching the advisory database", || {
+        fetch(path, policy.timeout)
+    })
+    .await;
+    match fetched {
+        Ok(()) => {
+            let now = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+            if let Err(err) = fs::write(&stamp, now) {
+                debug!("Failed to record the advisory database update: {}", err);
+            }
+        }
+        Err(err) if !path.exists() => return Err(err.into()),
+        Err(err) => warn!(
+            "Could not update advisory database, using local copy: {:#}",
+            err
+        ),
+    }
+    Ok(())
+}
+
+/// Whether the checkout at `path` was updated less than `ttl` ago, going
+/// by the modification time of `stamp`.
+fn is_fresh(path: &Path, stamp: &Path, ttl: Duration) -> bool {
+    let updated = fs::metadata(stamp).and_then(|metadata| metadata.modified());
+    path.exists()
+        && updated.is_ok_and(|updated| {
+            SystemTime::now()
+                .duration_since(updated)
+                .is_ok_and(|age| age < ttl)
+        })
+}
+
+fn default_db_path() -> Result<PathBuf> {
+    Ok(cache_root()?.join("advisory-db"))
+}
+
+fn parsed_cache_path() -> Result<PathBuf> {
+    Ok(cache_root()?.join("advisories.json"))
+}
+
+async fn fetch(path: &Path, timeout: Duration) -> Result<()> {
+    let cloning = !path.join(".git").exists();
+    let mut command = Command::new("git");
+    if !cloning {
+        info!("Updating advisory database in {}", path.display());
+        command.arg("-C").arg(path).args(["pull", "--ff-only", "--quiet"]);
+    } else {
+        info!("Cloning advisory database into {}", path.display());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| GuardianError::io(parent, err))?;
+        }
+        command
+            .args(["clone", "--depth", "1", "--quiet", ADVISORY_DB_URL])
+            .arg(path);
+    }
+
+    command.kill_on_drop(true);
+    let result = match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => Ok(()),
+        Ok(Ok(output)) => Err(GuardianError::network(
+            ADVISORY_DB_URL,
+            Transient(format!(
+                "git exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        )),
+        Ok(Err(err)) => Err(GuardianError::io("git", err)),
+        Err(elapsed) => Err(GuardianError::network(ADVISORY_DB_URL, elapsed)),
+    };
+
+    // Don't leave a half-cloned checkout behind to be "updated" next time
+    if result.is_err() && cloning && path.exists() {
+        fs::remove_dir_all(path).ok();
+    }
+    result
+}
+
+async fn revision(db_path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(db_path)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[derive(Serialize, Deserialize)]
+struct ParsedCache {
+    source: PathBuf,
+    revision: String,
+    advisories: Vec<Advisory>,
+}
+
+fn read_parsed_cache(cache_path: &Path, db_path: &Path, revision: &str) -> Option<Vec<Advisory>> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let cache: ParsedCache = serde_json::from_str(&content).ok()?;
+    (cache.source == db_path && cache.revision == revision).then_some(cache.advisories)
+}
+
+fn write_parsed_cache(
+    cache_path: &Path,
+    db_path: &Path,
+    revision: &str,
+    advisories: &[Advisory],
+) -> Result<(), BoxError> {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let cache = ParsedCache {
+        source: db_path.to_path_buf(),
+        revision: revision.to_string(),
+        advisories: advisories.to_vec(),
+    };
+    fs::write(cache_path, serde_json::to_vec(&cache)?)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryMetadata,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryMetadata {
+    id: String,
+    package: String,
+    #[serde(default)]
+    date: String,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<VersionReq>,
+    #[serde(default)]
+    unaffected: Vec<VersionReq>,
+}
+
+fn parse_db(db_path: &Path) -> Result<Vec<Advisory>, BoxError> {
+    let crates_dir = db_path.join("crates");
+    if !crates_dir.is_dir() {
+        return Err(format!("{} is not an advisory-db checkout", db_path.display()).into());
+    }
+
+    let mut advisories = Vec::new();
+    for entry in WalkDir::new(&crates_dir) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+
+        match parse_advisory(path) {
+            Ok(advisory) => advisories.push(advisory),
+            Err(err) => warn!("Skipping advisory {}: {}", path.display(), err),
+        }
+    }
+
+    Ok(advisories)
+}
+
+/// Parses an advisory in the `advisory-db` Markdown format: a fenced TOML
+/// front matter block followed by a `# Title` heading.
+fn parse_advisory(path: &Path) -> Result<Advisory, BoxError> {
+    let content = fs::read_to_string(path)?;
+    let body = content
+        .trim_start()
+        .strip_prefix("```toml")
+        .ok_or("missing TOML front matter")?;
+    let (front_matter, markdown) = body
+        .split_once("```")
+        .ok_or("unterminated TOML front matter")?;
+
+    let file: AdvisoryFile = toml::from_str(front_matter)?;
+    let title = markdown
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    Ok(Advisory {
+        id: file.advisory.id,
+        package: file.advisory.package,
+        title,
+        date: file.advisory.date,
+        url: file.advisory.url,
+        aliases: file.advisory.aliases,
+        cvss: file.advisory.cvss,
+        informational: file.advisory.informational,
+        withdrawn: file.advisory.withdrawn,
+        patched: file.versions.patched,
+        unaffected: file.versions.unaffected,
+    })
+}
+
+/// Like `VersionReq::matches`, but without semver's rule that excludes
+/// pre-releases from ranges; `1.2.0-alpha` must still count as affected by
+/// an advisory patched in `>= 1.2.0`.
+fn req_matches(req: &VersionReq, version: &Version) -> bool {
+    req.comparators.iter().all(|cmp| comparator_matches(cmp, version))
+}
+
+fn comparator_matches(cmp: &Comparator, version: &Version) -> bool {
+    let Some((lower, upper)) = comparator_range(cmp) else {
+        return cmp.matches(version);
+    };
+
+    let above_lower = match lower {
+        Some((bound, inclusive)) => version > &bound || (inclusive && version == &bound),
+        None => true,
+    };
+    let below_upper = match upper {
+        Some((bound, inclusive)) => version < &bound || (inclusive && version == &bound),
+        None => true,
+    };
+
+    above_lower && below_upper
+}
+
+type Bound = Option<(Version, bool)>;
+
+/// Converts a comparator into explicit `(lower, upper)` bounds; the flag on
+/// each bound marks it inclusive. Exclusive upper bounds use a `-0`
+/// pre-release so that pre-releases of the next version fall outside.
+fn comparator_range(cmp: &Comparator) -> Option<(Bound, Bound)> {
+    let base = Version {
+        major: cmp.major,
+        minor: cmp.minor.unwrap_or(0),
+        patch: cmp.patch.unwrap_or(0),
+        pre: cmp.pre.clone(),
+        build: BuildMetadata::EMPTY,
+    };
+    let floor = |major, minor, patch| Version {
+        major,
+        minor,
+        patch,
+        pre: Prerelease::new("0").unwrap(),
+        build: BuildMetadata::EMPTY,
+    };
+    // Upper bound of a partial version such as `1.2` or `1`
+    let partial_end = || match (cmp.minor, cmp.patch) {
+        (Some(minor), Some(patch)) => floor(cmp.major, minor, patch + 1),
+        (Some(minor), None) => floor(cmp.major, minor + 1, 0),
+        _ => floor(cmp.major + 1, 0, 0),
+    };
+
+    let range = match cmp.op {
+        Op::Exact | Op::Wildcard => {
+            if cmp.patch.is_some() {
+                (Some((base.clone(), true)), Some((base, true)))
+            } else {
+                (Some((base, true)), Some((partial_end(), false)))
+            }
+        }
+        Op::Greater => {
+            if cmp.patch.is_some() {
+                (Some((base, false)), None)
+            } else {
+                (Some((partial_end(), true)), None)
+            }
+        }
+        Op::GreaterEq => (Some((base, true)), None),
+        Op::Less => (None, Some((base, false))),
+        Op::LessEq => {
+            if cmp.patch.is_some() {
+                (None, Some((base, true)))
+            } else {
+                (None, Some((partial_end(), false)))
+            }
+        }
+        Op::Tilde => {
+            let end = match cmp.minor {
+                Some(minor) => floor(cmp.major, minor + 1, 0),
+                None => floor(cmp.major + 1, 0, 0),
+            };
+            (Some((base, true)), Some((end, false)))
+        }
+        Op::Caret => {
+            let end = match (cmp.major, cmp.minor, cmp.patch) {
+                (0, Some(0), Some(patch)) => floor(0, 0, patch + 1),
+                (0, Some(minor), _) => floor(0, minor + 1, 0),
+                (major, _, _) => floor(major + 1, 0, 0),
+            };
+            (Some((base, true)), Some((end, false)))
+        }
+        _ => return None,
+    };
+
+    Some(range)
+}
+
+/// The smallest version a requirement admits, used to suggest a fix.
+fn lower_bound(req: &VersionReq) -> Option<Version> {
+    req.comparators
+        .iter()
+        .filter_map(|cmp| match comparator_range(cmp)? {
+            (Some((bound, true)), _) => Some(bound),
+            (Some((bound, false)), _) => Some(Version::new(
+                bound.major,
+                bound.minor,
+                bound.patch + 1,
+            )),
+            (None, _) => None,
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An advisory patched on the 1.2, 1.3 and 2.x branches.
+    fn advisory() -> Advisory {
+        let reqs = |reqs: &[&str]| reqs.iter().map(|req| req.parse().unwrap()).collect();
+        Advisory {
+            id: "RUSTSEC-2024-0001".to_string(),
+            package: "dep".to_string(),
+            title: "Memory corruption".to_string(),
+            date: "2024-01-01".to_string(),
+            url: None,
+            aliases: Vec::new(),
+            cvss: None,
+            informational: None,
+            withdrawn: None,
+            patched: reqs(&[">= 1.2.5, < 1.3.0", ">= 1.3.2, < 2.0.0", ">= 2.0.1"]),
+            unaffected: reqs(&["< 1.0.0"]),
+        }
+    }
+
+    fn version(version: &str) -> Version {
+        version.parse().unwrap()
+    }
+
+    fn fix(from: &str, requirements: &[&str]) -> Option<String> {
+        let requirements: Vec<VersionReq> = requirements
+            .iter()
+            .map(|req| req.parse().unwrap())
+            .collect();
+        let requirements: Vec<&VersionReq> = requirements.iter().collect();
+        advisory()
+            .fix_version(&version(from), &requirements)
+            .map(|fix| fix.to_string())
+    }
+
+    #[test]
+    fn affects_versions_between_the_patched_branches() {
+        let advisory = advisory();
+        let affected = |v: &str| advisory.affects(&version(v));
+
+        assert!(!affected("0.9.0"));
+        assert!(affected("1.2.4"));
+        assert!(!affected("1.2.5"));
+        assert!(affected("1.3.0"));
+        assert!(affected("1.3.2-rc.1"));
+        assert!(!affected("1.9.0"));
+        assert!(affected("2.0.0"));
+        assert!(!affected("2.0.1"));
+    }
+
+    #[test]
+    fn recommends_the_lowest_fix_the_requirements_allow() {
+        assert_eq!(fix("1.2.0", &["^1.2"]).as_deref(), Some("1.2.5"));
+        assert_eq!(fix("1.2.0", &["~1.2", "^1.2.1"]).as_deref(), Some("1.2.5"));
+        assert_eq!(fix("1.3.0", &["^1.3"]).as_deref(), Some("1.3.2"));
+        assert_eq!(fix("1.2.0", &[">=1.3, <2"]).as_deref(), Some("1.3.2"));
+        assert_eq!(fix("2.0.0", &["^2"]).as_deref(), Some("2.0.1"));
+    }
+
+    #[test]
+    fn falls_back_to_the_lowest_fix_above_the_version() {
+        assert_eq!(fix("1.2.0", &["=1.2.0"]).as_deref(), Some("1.2.5"));
+        assert_eq!(
+            fix("1.3.0", &["~1.3.0", "=1.3.1"]).as_deref(),
+            Some("1.3.2")
+        );
+        assert_eq!(fix("1.3.0", &[]).as_deref(), Some("1.3.2"));
+    }
+
+    #[test]
+    fn issues_carry_the_size_of_the_upgrade() {
+        let advisory = advisory();
+        let kind = |requirement: &str| {
+            let requirement: VersionReq = requirement.parse().unwrap();
+            let issue = advisory.to_issue(&version("1.2.0"), &[&requirement]);
+            (issue.fix_version.unwrap(), issue.fix_kind.unwrap())
+        };
+
+        assert_eq!(kind("^1.2"), ("1.2.5".to_string(), FixKind::Patch));
+        assert_eq!(kind(">=1.3, <2"), ("1.3.2".to_string(), FixKind::Minor));
+        assert_eq!(kind(">=2"), ("2.0.1".to_string(), FixKind::Major));
+    }
+}
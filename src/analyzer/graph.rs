@@ -0,0 +1,149 @@
+use cargo_metadata::{DependencyKind, Metadata, Package, PackageId};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The resolved dependency graph below a set of root packages, with
+/// shortest paths from the roots precomputed by a breadth-first search.
+/// Edges of only skipped kinds (e.g. dev-dependencies) are left out, and
+/// so are packages deeper than `max_depth` (0 being the roots' direct
+/// dependencies).
+pub struct DependencyGraph<'a> {
+    packages: HashMap<&'a PackageId, &'a Package>,
+    dependents: HashMap<&'a PackageId, Vec<&'a PackageId>>,
+    roots: Vec<&'a PackageId>,
+    parents: HashMap<&'a PackageId, &'a PackageId>,
+    resolved: bool,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new(
+        metadata: &'a Metadata,
+        roots: &[&'a PackageId],
+        skip: &[DependencyKind],
+        max_depth: Option<usize>,
+    ) -> Self {
+        let packages: HashMap<_, _> = metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package))
+            .collect();
+
+        let mut dependencies: HashMap<&PackageId, Vec<&PackageId>> = HashMap::new();
+        let mut dependents: HashMap<&PackageId, Vec<&PackageId>> = HashMap::new();
+        let roots = roots.to_vec();
+
+        if let Some(resolve) = &metadata.resolve {
+            for node in &resolve.nodes {
+                for dep in node.deps.iter().filter(|dep| is_followed(dep, skip)) {
+                    dependencies.entry(&node.id).or_default().push(&dep.pkg);
+                    dependents.entry(&dep.pkg).or_default().push(&node.id);
+                }
+            }
+        }
+
+        let mut parents = HashMap::new();
+        let mut visited: HashSet<&PackageId> = roots.iter().copied().collect();
+        // Paired with the depth of their dependencies
+        let mut queue: VecDeque<(&PackageId, usize)> = roots.iter().map(|&id| (id, 0)).collect();
+        while let Some((id, depth)) = queue.pop_front() {
+            if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                continue;
+            }
+            for &dep in dependencies.get(id).into_iter().flatten() {
+                if visited.insert(dep) {
+                    parents.insert(dep, id);
+                    queue.push_back((dep, depth + 1));
+                }
+            }
+        }
+
+        Self {
+            packages,
+            dependents,
+            roots,
+            parents,
+            resolved: metadata.resolve.is_some(),
+        }
+    }
+
+    /// Whether `id` is a root or reachable from one. Without a resolved
+    /// graph every package is assumed reachable.
+    pub fn contains(&self, id: &PackageId) -> bool {
+        !self.resolved || self.roots.contains(&id) || self.parents.contains_key(id)
+    }
+
+    pub fn package(&self, id: &PackageId) -> Option<&'a Package> {
+        self.packages.get(id).copied()
+    }
+
+    /// Packages that depend on `id` directly.
+    pub fn dependents(&self, id: &PackageId) -> Vec<&'a Package> {
+        self.dependents
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter(|&&dependent| self.contains(dependent))
+            .filter_map(|&dependent| self.package(dependent))
+            .collect()
+    }
+
+    /// The shortest chain of packages from a root to `id`, inclusive.
+    pub fn shortest_path(&self, id: &PackageId) -> Option<Vec<&'a Package>> {
+        let mut path = vec![self.package(id)?];
+        let mut current = id;
+        while !self.roots.contains(&current) {
+            current = *self.parents.get(current)?;
+            path.push(self.package(current)?);
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Number of distinct paths from a root to `id`. Cycles (possible
+    /// through dev-dependencies) are cut rather than followed.
+    pub fn path_count(&self, id: &PackageId) -> u64 {
+        let Some((&id, _)) = self.packages.get_key_value(id) else {
+            return 0;
+        };
+
+        let mut memo = HashMap::new();
+        let mut in_progress = HashSet::new();
+        self.count_paths(id, &mut memo, &mut in_progress)
+    }
+
+    fn count_paths(
+        &self,
+        id: &'a PackageId,
+        memo: &mut HashMap<&'a PackageId, u64>,
+        in_progress: &mut HashSet<&'a PackageId>,
+    ) -> u64 {
+        if self.roots.contains(&id) {
+            return 1;
+        }
+        if let Some(&count) = memo.get(id) {
+            return count;
+        }
+        if !in_progress.insert(id) {
+            return 0;
+        }
+
+        let count =
+            self.dependents
+                .get(id)
+                .into_iter()
+                .flatten()
+                .fold(0u64, |total, &dependent| {
+                    total.saturating_add(self.count_paths(dependent, memo, in_progress))
+                });
+
+        in_progress.remove(id);
+        memo.insert(id, count);
+        count
+    }
+}
+
+/// Whether an edge has a kind that is not skipped. Edges without kind
+/// information (older cargo versions) are always followed.
+pub fn is_followed(dep: &cargo_metadata::NodeDep, skip: &[DependencyKind]) -> bool {
+    dep.dep_kinds.is_empty() || dep.dep_kinds.iter().any(|info| !skip.contains(&info.kind))
+}
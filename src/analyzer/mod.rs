@@ -1,91 +1,1782 @@
-use anyhow::Result;
-use cargo_metadata::{MetadataCommand, Package};
-use serde::Serialize;
-use std::collections::HashMap;
-use tracing::info;
+use cargo_metadata::{
+    CargoOpt, DependencyKind as CargoDependencyKind, Metadata, MetadataCommand, Package, PackageId,
+};
+use glob::Pattern;
+use rayon::prelude::*;
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
-use crate::models::{DependencyInfo, SecurityIssue};
-use crate::scanner::SecurityScanner;
+use crate::advisory::{AcceptedAdvisories, AdvisoryDatabase};
+use crate::baseline::BaselineDiff;
+use crate::cache::AnalysisCache;
+use crate::capabilities::{self, CapabilitySet};
+use crate::cargo_config;
+use crate::config::{Bans, Config, Escalation};
+use crate::confusion::ConfusionChecker;
+use crate::edition;
+use crate::error::{GuardianError, Result};
+use crate::features;
+use crate::fixes::FixCheck;
+use crate::ignore::{IgnoreRules, IGNORE_FILE};
+use crate::license::{self, LicenseChecker};
+use crate::macros;
+use crate::maintenance::{self, CratesIo};
+use crate::models::{
+    AcceptedIssue, DependencyEdge, DependencyInfo, DependencyKind, ResolvedPackage, SecurityIssue,
+    Severity,
+};
+use crate::overrides;
+use crate::pins;
+use crate::plugin::Plugins;
+use crate::redact::PathRedactor;
+use crate::registry::{self, OutdatedCounts, RegistryIndex};
+use crate::rules;
+use crate::scanner::{self, SecurityScanner};
+use crate::since::{self, UnchangedPackages};
+use crate::sources::SourceChecker;
+use crate::typosquat::TyposquatChecker;
 
-#[derive(Debug, Serialize)]
+mod graph;
+mod progress;
+mod timings;
+
+pub use graph::DependencyGraph;
+use progress::ScanProgress;
+pub use timings::{PackageTimings, Timings};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyAnalysis {
     pub total_dependencies: usize,
     pub direct_dependencies: Vec<DependencyInfo>,
-    pub dependency_tree: HashMap<String, Vec<String>>,
-    pub security_issues: HashMap<String, Vec<SecurityIssue>>,
+    pub dependency_tree: BTreeMap<String, Vec<DependencyEdge>>,
+    /// Every package of the resolved graph, keyed like `dependency_tree`
+    #[serde(default)]
+    pub packages: BTreeMap<String, ResolvedPackage>,
+    pub security_issues: BTreeMap<String, Vec<SecurityIssue>>,
+    pub suppressed_issues: usize,
+    /// Advisory findings accepted in the configuration, which are not part
+    /// of `security_issues`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub accepted_issues: BTreeMap<String, Vec<AcceptedIssue>>,
+    /// Number of crates resolved at more than one version
+    #[serde(default)]
+    pub duplicate_crates: usize,
+    /// Number of dependencies providing a procedural macro
+    #[serde(default)]
+    pub proc_macro_crates: usize,
+    /// Number of resolved packages overridden by `[patch]` or `[replace]`
+    #[serde(default)]
+    pub patched_crates: usize,
+    /// Dependencies behind their newest release, with `--outdated`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outdated_crates: Option<OutdatedCounts>,
+    /// What each package can do, itself or through its normal
+    /// dependencies, keyed like `dependency_tree`
+    #[serde(default)]
+    pub capabilities: BTreeMap<String, CapabilitySet>,
+    /// Sum of the severity weights of all reported issues
+    #[serde(default)]
+    pub risk_score: u32,
+    /// Risk score of each package with issues
+    #[serde(default)]
+    pub package_risk: BTreeMap<String, u32>,
+    /// Issues hidden by `--min-severity` or `--min-confidence`
+    #[serde(default)]
+    pub filtered_issues: usize,
+    /// Comparison with `--baseline`; `security_issues` then holds only the
+    /// new issues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<BaselineDiff>,
+    /// Scan times per package and stage, with `--profile`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+    /// Whether the fix versions of the findings resolve, with
+    /// `--check-fixes` or `--apply-fixes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_checks: Option<Vec<FixCheck>>,
+    /// Dependencies whose sources were not scanned with `--since`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unchanged_packages: Option<UnchangedPackages>,
+    /// Dependencies whose sources were not scanned because
+    /// `--scan-timeout` ran out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incomplete: Option<Incomplete>,
+}
+
+/// The packages left unscanned when the scan timeout was reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incomplete {
+    /// The timeout, e.g. `5m`
+    pub timeout: String,
+    /// `name@version` of each package whose sources were not scanned
+    pub unscanned_packages: Vec<String>,
+}
+
+impl DependencyAnalysis {
+    /// Drops issues below `threshold` from the report, counting them in
+    /// `filtered_issues`. Risk scores still cover every issue.
+    pub fn retain_min_severity(&mut self, threshold: &Severity) {
+        for issues in self.security_issues.values_mut() {
+            let before = issues.len();
+            issues.retain(|issue| issue.severity.meets(threshold));
+            self.filtered_issues += before - issues.len();
+        }
+        self.security_issues.retain(|_, issues| !issues.is_empty());
+    }
+}
+
+/// The resolved dependency graph alone, from
+/// [`Analyzer::dependency_tree`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyTree {
+    /// The analyzed workspace members, keyed like `edges`
+    pub roots: Vec<String>,
+    /// The dependencies of each package, keyed by `name@version`
+    pub edges: BTreeMap<String, Vec<DependencyEdge>>,
 }
 
+#[derive(Debug)]
 pub struct Analyzer {
     manifest_path: String,
+    config: Config,
     security_scanner: SecurityScanner,
+    plugins: Plugins,
+    accepted: AcceptedAdvisories,
+    /// `dependency-severity`, with the crate name globs compiled
+    escalations: Vec<(Pattern, Escalation)>,
 }
 
 impl Analyzer {
-    pub fn new(manifest_path: String) -> Result<Self> {
+    pub fn new(manifest_path: String, mut config: Config) -> Result<Self> {
+        config.apply_deny_toml()?;
+        let plugins = match &config.plugin_dir {
+            Some(dir) => Plugins::load(dir)?,
+            None => Plugins::default(),
+        };
+        for id in config.severity_overrides.keys() {
+            let custom = config.patterns.iter().any(|pattern| &pattern.id == id);
+            if rules::find(id).is_none() && !custom && !id.starts_with("RUSTSEC-") {
+                warn!(
+                    "severity-overrides: {} is not a built-in or custom rule or an advisory",
+                    id
+                );
+            }
+        }
+        let accepted = AcceptedAdvisories::new(&config.accepted_advisories)
+            .map_err(|err| GuardianError::config("Invalid accepted-advisories", err))?;
+        let escalations = config
+            .dependency_severity
+            .iter()
+            .map(|(name, escalation)| {
+                let pattern = Pattern::new(name).map_err(|err| {
+                    GuardianError::config(format!("Invalid dependency-severity key {}", name), err)
+                })?;
+                Ok((pattern, escalation.clone()))
+            })
+            .collect::<Result<_>>()?;
         Ok(Self {
             manifest_path,
-            security_scanner: SecurityScanner::new()?,
+            security_scanner: SecurityScanner::new(&config)?,
+            plugins,
+            accepted,
+            escalations,
+            config,
         })
     }
 
+    /// Raises the severity of `issue` on `package` as far as the
+    /// `dependency-severity` entries matching the crate name ask for,
+    /// remembering the severity it had.
+    fn escalate_severity(&self, package: &str, issue: &mut SecurityIssue) {
+        let escalated = self
+            .escalations
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(package))
+            .map(|(_, escalation)| escalation.apply(&issue.severity))
+            .max();
+        if let Some(severity) = escalated.filter(|severity| *severity > issue.severity) {
+            issue.escalated_from = Some(std::mem::replace(&mut issue.severity, severity));
+        }
+    }
+
+    /// The manifest the analyzer was created for.
+    pub fn manifest_path(&self) -> &Path {
+        Path::new(&self.manifest_path)
+    }
+
+    /// The configuration the analyzer was created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Resolves the dependency graph of the manifest and runs every enabled
+    /// check over it: advisories, yanked versions, license policy, duplicate
+    /// versions and source patterns.
+    ///
+    /// Shells out to `cargo metadata` unless `config.metadata_json` holds its
+    /// saved output, and may use the network to update the advisory database
+    /// and query the crates.io index unless `config.offline` is set. Results
+    /// are cached while `Cargo.lock` and the configuration are unchanged,
+    /// until the advisory and registry data they were computed from expire.
     pub async fn analyze(&self) -> Result<DependencyAnalysis> {
-        info!("Analyzing dependencies from {}", self.manifest_path);
+        self.analyze_with(|_, _| {}).await
+    }
+
+    /// Resolves the dependency graph of the manifest, as `analyze` does,
+    /// without running any check or touching the network beyond what
+    /// `cargo metadata` needs.
+    pub fn dependency_tree(&self) -> Result<DependencyTree> {
+        let metadata = match self.config.metadata_json.as_deref() {
+            Some(path) => load_metadata(path)?,
+            None if !Path::new(&self.manifest_path).is_file() => {
+                return Err(GuardianError::ManifestNotFound {
+                    path: self.manifest_path.clone().into(),
+                });
+            }
+            None => self.metadata()?,
+        };
+
+        let members = self.select_members(&metadata)?;
+        let member_ids: Vec<&PackageId> = members.iter().map(|member| &member.id).collect();
+        let skip = self.skipped_kinds();
+        let graph = DependencyGraph::new(&metadata, &member_ids, &skip, self.config.max_depth);
+        let mut edges = BTreeMap::new();
+        self.build_dependency_tree(&metadata, &graph, &skip, &mut edges)?;
+        Ok(DependencyTree {
+            roots: members.iter().map(|member| package_key(member)).collect(),
+            edges,
+        })
+    }
+
+    /// Like [`analyze`](Self::analyze), also calling `on_issue` with the
+    /// package name and each reported issue as soon as that package has
+    /// been checked, in the order the scan completes them. A cached
+    /// analysis is replayed in full.
+    #[cfg_attr(
+        feature = "otel",
+        tracing::instrument(
+            name = "analyze",
+            target = "telemetry",
+            skip_all,
+            fields(manifest = %self.manifest_path, packages, issues)
+        )
+    )]
+    pub async fn analyze_with(
+        &self,
+        mut on_issue: impl FnMut(&str, &SecurityIssue),
+    ) -> Result<DependencyAnalysis> {
+        // Counted from here, so that resolving the graph and fetching the
+        // advisory database use up the time as well
+        let deadline = self
+            .config
+            .scan_timeout
+            .map(|timeout| Instant::now() + timeout);
+        let metadata_json = self.config.metadata_json.as_deref();
+        match metadata_json {
+            Some(path) => info!("Analyzing dependencies from {}", path.display()),
+            None => info!("Analyzing dependencies from {}", self.manifest_path),
+        }
+        if metadata_json.is_none() && !Path::new(&self.manifest_path).is_file() {
+            return Err(GuardianError::ManifestNotFound {
+                path: self.manifest_path.clone().into(),
+            });
+        }
+
+        // Source edits do not change the lockfile, so a watch run must
+        // rescan, and a profile measures a fresh scan; what --since skips
+        // depends on the ref as well
+        let cache = if self.config.cache
+            && !self.config.watch
+            && !self.config.profile
+            && self.config.since.is_none()
+        {
+            self.analysis_cache().await
+        } else {
+            None
+        };
+        if let Some(analysis) = cache.as_ref().and_then(AnalysisCache::load) {
+            info!("Cargo.lock unchanged, using cached analysis");
+            if self.config.advisories {
+                self.accepted.warn_unmatched(&analysis.accepted_issues);
+            }
+            for (package, issues) in &analysis.security_issues {
+                for issue in issues {
+                    on_issue(package, issue);
+                }
+            }
+            return Ok(analysis);
+        }
 
-        let metadata = MetadataCommand::new()
-            .manifest_path(&self.manifest_path)
-            .exec()?;
+        let metadata = match metadata_json {
+            Some(path) => load_metadata(path)?,
+            None => self.metadata()?,
+        };
 
-        let root_package = metadata
-            .root_package()
-            .ok_or_else(|| anyhow::anyhow!("No root package found"))?;
+        let members = self.select_members(&metadata)?;
+        let member_ids: Vec<&PackageId> = members.iter().map(|member| &member.id).collect();
+        let skip = self.skipped_kinds();
+        let graph = DependencyGraph::new(&metadata, &member_ids, &skip, self.config.max_depth);
 
-        let direct_deps: Vec<DependencyInfo> = root_package
-            .dependencies
+        let packages: Vec<&Package> = metadata
+            .packages
             .iter()
-            .map(|dep| DependencyInfo {
-                name: dep.name.clone(),
-                version: dep.req.to_string(),
-                is_direct: true,
-                features: dep.features.clone(),
-                dependencies: Vec::new(),
+            .filter(|package| graph.contains(&package.id))
+            .collect();
+
+        // Dependencies locked at the same version as at the --since ref are
+        // still held to every policy, but their sources are not scanned
+        let locked_before = match &self.config.since {
+            Some(git_ref) => since::locked_at(metadata.workspace_root.as_std_path(), git_ref)?,
+            None => None,
+        };
+        let unchanged: HashSet<&PackageId> = packages
+            .iter()
+            .filter(|package| !member_ids.contains(&&package.id))
+            .filter(|package| {
+                locked_before.as_ref().is_some_and(|locked| {
+                    locked.contains(&(package.name.clone(), package.version.to_string()))
+                })
             })
+            .map(|package| &package.id)
             .collect();
 
-        let mut dep_tree: HashMap<String, Vec<String>> = HashMap::new();
-        self.build_dependency_tree(&metadata.packages, &mut dep_tree)?;
+        let direct_deps: Vec<DependencyInfo> = members
+            .iter()
+            .flat_map(|member| {
+                member
+                    .dependencies
+                    .iter()
+                    .filter(|dep| !skip.contains(&dep.kind))
+                    .map(|dep| {
+                        let resolved = resolved_dependency(&metadata, &graph, member, dep);
+                        DependencyInfo {
+                            member: member.name.clone(),
+                            name: dep.name.clone(),
+                            version: dep.req.to_string(),
+                            is_direct: true,
+                            features: dep.features.clone(),
+                            dependencies: Vec::new(),
+                            edition: resolved.map(|package| package.edition.to_string()),
+                            rust_version: resolved
+                                .and_then(|package| package.rust_version.as_ref())
+                                // Parsed as a requirement; report the plain version
+                                .map(|req| req.to_string().trim_start_matches('^').to_string()),
+                            default_features: resolved
+                                .filter(|_| dep.uses_default_features)
+                                .map(features::default_features)
+                                .unwrap_or_default(),
+                        }
+                    })
+            })
+            .collect();
+
+        let mut dep_tree: BTreeMap<String, Vec<DependencyEdge>> = BTreeMap::new();
+        self.build_dependency_tree(&metadata, &graph, &skip, &mut dep_tree)?;
+
+        let advisories = if self.config.advisories {
+            let db_path = self.config.advisory_db.as_deref();
+            let offline = self.config.offline;
+            let ttl = Duration::from_secs(self.config.advisory_db_ttl);
+            let policy = self.config.retry_policy();
+            match AdvisoryDatabase::open(db_path, offline, ttl, &policy).await {
+                Ok(db) => Some(db),
+                Err(err) => {
+                    warn!(
+                        "Advisory database unavailable, skipping advisory checks: {:#}",
+                        err
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let licenses = if self.config.license.is_configured() {
+            Some(LicenseChecker::new(&self.config.license)?)
+        } else {
+            None
+        };
+
+        // Dev-dependencies, build-dependencies and what only they pull in
+        // are not linked into what the project distributes
+        let linked = (self.config.license.compatibility && self.config.is_rule_enabled("GUARD044"))
+            .then(|| {
+                let skip = [CargoDependencyKind::Development, CargoDependencyKind::Build];
+                DependencyGraph::new(&metadata, &member_ids, &skip, self.config.max_depth)
+            });
+
+        let sources = SourceChecker::new(&self.config.allow_git)?;
+
+        let confusion = if self.config.internal_crates.is_empty() {
+            None
+        } else {
+            let registry = self.config.internal_registry.as_deref();
+            Some(ConfusionChecker::new(
+                &self.config.internal_crates,
+                registry,
+            )?)
+        };
+
+        let typosquats = self
+            .config
+            .typosquat
+            .then(|| TyposquatChecker::new(&self.config.allow_similar));
+
+        let redactor = self
+            .config
+            .redact_paths
+            .then(|| PathRedactor::new(metadata.workspace_root.as_std_path()));
+
+        let ignore_rules =
+            IgnoreRules::load(metadata.workspace_root.as_std_path(), &self.config.ignore)?;
 
-        let mut security_issues = HashMap::new();
-        for package in &metadata.packages {
-            if let Ok(issues) = self.security_scanner.scan_package(package) {
-                if !issues.is_empty() {
-                    security_issues.insert(package.name.clone(), issues);
+        let mut yanked = if !self.config.yanked || !self.config.is_rule_enabled("GUARD013") {
+            HashMap::new()
+        } else if self.config.offline {
+            warn!("Offline mode, skipping yanked version checks");
+            HashMap::new()
+        } else {
+            match RegistryIndex::new(self.config.retry_policy()) {
+                Ok(index) => index.yanked_issues(&packages).await,
+                Err(err) => {
+                    warn!(
+                        "Registry index unavailable, skipping yanked version checks: {:#}",
+                        err
+                    );
+                    HashMap::new()
+                }
+            }
+        };
+
+        let mut stale = if !self.config.maintenance || !self.config.is_rule_enabled("GUARD030") {
+            HashMap::new()
+        } else if self.config.offline {
+            warn!("Offline mode, skipping maintenance checks");
+            HashMap::new()
+        } else {
+            match CratesIo::new(self.config.retry_policy()) {
+                Ok(crates_io) => {
+                    crates_io
+                        .stale_issues(&packages, self.config.max_age_months)
+                        .await
+                }
+                Err(err) => {
+                    warn!(
+                        "crates.io unavailable, skipping maintenance checks: {:#}",
+                        err
+                    );
+                    HashMap::new()
+                }
+            }
+        };
+
+        let mut outdated = if !self.config.outdated || !self.config.is_rule_enabled("GUARD040") {
+            None
+        } else {
+            match RegistryIndex::new(self.config.retry_policy()) {
+                Ok(index) => {
+                    // Offline, only crates with a cached index entry are checked
+                    let index = if self.config.offline {
+                        index.cached_only()
+                    } else {
+                        index
+                    };
+                    let dependencies: Vec<&Package> = packages
+                        .iter()
+                        .copied()
+                        .filter(|package| !member_ids.contains(&&package.id))
+                        .collect();
+                    Some(index.outdated(&dependencies).await)
+                }
+                Err(err) => {
+                    warn!(
+                        "Registry index unavailable, skipping outdated checks: {:#}",
+                        err
+                    );
+                    None
+                }
+            }
+        };
+        let outdated_crates = outdated
+            .as_ref()
+            .map(|outdated| OutdatedCounts::of(outdated.values()));
+
+        // Keyed by the resolved package, reported once however many members
+        // declare it
+        let mut default_features = HashMap::new();
+        // The members pinning each resolved package with `=x.y.z`
+        let mut pinned_by: HashMap<&PackageId, Vec<String>> = HashMap::new();
+        let sensitive = &self.config.sensitive_features;
+        for member in &members {
+            let declared = member
+                .dependencies
+                .iter()
+                .filter(|dep| !skip.contains(&dep.kind));
+            for dep in declared {
+                let Some(package) = resolved_dependency(&metadata, &graph, member, dep) else {
+                    continue;
+                };
+                if let Some(issue) = features::check(dep, package, sensitive) {
+                    default_features.entry(package.id.clone()).or_insert(issue);
+                }
+                if pins::is_exact(&dep.req) {
+                    let members = pinned_by.entry(&package.id).or_default();
+                    if !members.contains(&member.name) {
+                        members.push(member.name.clone());
+                    }
                 }
             }
         }
 
-        Ok(DependencyAnalysis {
-            total_dependencies: metadata.packages.len() - 1,
+        let mut duplicates = duplicate_issues(&metadata, &graph, &packages, &self.config.bans);
+        let mut patches = overrides::check(metadata.workspace_root.as_std_path());
+        let mut registries = cargo_config::check(metadata.workspace_root.as_std_path(), &members);
+        let patched_crates = packages
+            .iter()
+            .filter(|package| patches.contains_key(&package.name))
+            .count();
+        let duplicate_crates = duplicates.len();
+        let proc_macro_crates = packages
+            .iter()
+            .filter(|package| !member_ids.contains(&&package.id) && macros::is_proc_macro(package))
+            .count();
+
+        // The features cargo resolved for each package
+        let active_features: Option<HashMap<&PackageId, BTreeSet<String>>> =
+            self.config.active_features_only.then(|| {
+                let nodes = metadata.resolve.iter().flat_map(|resolve| &resolve.nodes);
+                nodes
+                    .map(|node| (&node.id, node.features.iter().cloned().collect()))
+                    .collect()
+            });
+        let no_features = BTreeSet::new();
+
+        // The bar goes to stderr, but keep machine-readable runs quiet unless
+        // their report goes to a file
+        let machine_readable = self.config.output_file.is_none()
+            && matches!(self.config.output.as_deref(), Some("json" | "ndjson"));
+        let show_bar = std::io::stderr().is_terminal() && !machine_readable && !self.config.quiet;
+        let progress = ScanProgress::new(packages.len(), show_bar);
+        // Each package is finished, and its issues handed to `on_issue`, as
+        // soon as its scan completes; results are kept in graph order so the
+        // analysis does not depend on scheduling
+        let (sender, receiver) = mpsc::channel();
+        let mut finished: Vec<Vec<SecurityIssue>> = vec![Vec::new(); packages.len()];
+        let mut accepted_issues: Vec<Vec<AcceptedIssue>> = vec![Vec::new(); packages.len()];
+        let mut suppressed_issues = 0;
+        let mut low_confidence_issues = 0;
+        let mut timings = Timings::default();
+        let mut own_capabilities = BTreeMap::new();
+        let mut unscanned = vec![false; packages.len()];
+        // Overrides, filters and the accepted, suppressed and redacted
+        // findings, for the issues of the package at `index`
+        let mut finish = |index: usize, package: &Package, mut issues: Vec<SecurityIssue>| {
+            for issue in &mut issues {
+                if let Some(rule) = issue.rule_id.as_deref().and_then(rules::find) {
+                    issue.confidence = rule.confidence;
+                }
+                self.config.override_severity(issue);
+                self.escalate_severity(&package.name, issue);
+            }
+            issues.retain(|issue| {
+                [&issue.rule_id, &issue.advisory_id]
+                    .into_iter()
+                    .flatten()
+                    .all(|id| self.config.is_rule_enabled(id))
+            });
+            if let Some(min_confidence) = self.config.min_confidence {
+                let before = issues.len();
+                issues.retain(|issue| issue.confidence >= min_confidence);
+                low_confidence_issues += before - issues.len();
+            }
+            scanner::sort_issues(&mut issues);
+
+            if !issues.is_empty() {
+                let path = dependency_path(&graph, package);
+                let other_paths = graph.path_count(&package.id).saturating_sub(1);
+                let root = self
+                    .security_scanner
+                    .package_dir(package)
+                    .unwrap_or_default();
+                for issue in &mut issues {
+                    issue.dependency_path = path.clone();
+                    issue.other_paths = other_paths;
+                    issue.fingerprint = issue.compute_fingerprint(&package.name, &root);
+                }
+            }
+
+            issues.retain(|issue| match self.accepted.accept(issue) {
+                Some(mut accepted) => {
+                    if let Some(redactor) = &redactor {
+                        redactor.issue(&mut accepted.issue);
+                    }
+                    accepted_issues[index].push(accepted);
+                    false
+                }
+                None => true,
+            });
+
+            let before = issues.len();
+            issues.retain(|issue| !ignore_rules.is_ignored(issue));
+            suppressed_issues += before - issues.len();
+
+            if self.config.merge {
+                issues = merge_overlapping(issues);
+            }
+            if self.config.group_by_rule {
+                issues = group_by_rule(issues);
+            }
+            if let Some(redactor) = &redactor {
+                issues.iter_mut().for_each(|issue| redactor.issue(issue));
+            }
+
+            for issue in &issues {
+                on_issue(&package.name, issue);
+            }
+            issues
+        };
+        let started = Instant::now();
+        // Rayon's threads do not inherit the current span
+        #[cfg(feature = "otel")]
+        let analyze_span = tracing::Span::current();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                packages.par_iter().enumerate().for_each_with(
+                    sender,
+                    |sender, (index, &package)| {
+                        #[cfg(feature = "otel")]
+                        let _span = analyze_span.enter();
+                        let skipped =
+                            self.config.duplicates_only || unchanged.contains(&package.id);
+                        // Packages are only skipped, never cut off mid-scan
+                        let timed_out =
+                            !skipped && deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                        let (issues, stages) = if skipped || timed_out {
+                            Default::default()
+                        } else {
+                            let features = active_features
+                                .as_ref()
+                                .map(|active| active.get(&package.id).unwrap_or(&no_features));
+                            let (mut issues, stages) = self
+                                .security_scanner
+                                .scan_package_timed(package, features)
+                                .unwrap_or_default();
+                            issues.extend(self.plugins.run(package));
+                            (issues, stages)
+                        };
+                        progress.advance(&package.name);
+                        // Only fails if the receiving loop below panicked
+                        let _ = sender.send((index, package, issues, stages, timed_out));
+                    },
+                );
+            });
+
+            for (index, package, mut issues, stages, timed_out) in receiver {
+                unscanned[index] = timed_out;
+                if self.config.profile {
+                    timings.record(package_key(package), &stages);
+                }
+                // Taken before any finding is disabled, overridden or ignored
+                own_capabilities.insert(package_key(package), CapabilitySet::of(package, &issues));
+                let found = advisories
+                    .as_ref()
+                    .map(|db| db.lookup(&package.name, &package.version))
+                    .unwrap_or_default();
+                if !found.is_empty() {
+                    let requirements = requirements_on(&metadata, package);
+                    issues.extend(
+                        found
+                            .iter()
+                            .map(|advisory| advisory.to_issue(&package.version, &requirements)),
+                    );
+                }
+
+                if let Some(issue) = yanked.remove(&package.id) {
+                    issues.push(issue);
+                }
+
+                if let Some(issue) = stale.remove(&package.id) {
+                    issues.push(issue);
+                }
+
+                if let Some(entry) = outdated.as_mut().and_then(|map| map.remove(&package.id)) {
+                    issues.push(entry.to_issue(package));
+                }
+
+                if let Some(issue) = duplicates.remove(&package.name) {
+                    issues.push(issue);
+                }
+
+                issues.extend(patches.remove(&package.name).into_iter().flatten());
+                issues.extend(registries.remove(&package.name).into_iter().flatten());
+
+                // Only dependencies are held to the license and source policies
+                if !member_ids.contains(&&package.id) && !self.config.duplicates_only {
+                    if let Some(checker) = &licenses {
+                        issues.extend(checker.check(package));
+                    }
+                    if let Some(linked) =
+                        linked.as_ref().filter(|_| !macros::is_proc_macro(package))
+                    {
+                        let path = linked.shortest_path(&package.id).unwrap_or_default();
+                        let project = self.config.license.project.as_deref();
+                        issues.extend(license::check_compatibility(&path, project));
+                    }
+                    issues.extend(sources.check(package, &graph.dependents(&package.id)));
+                    if let Some(checker) = &confusion {
+                        issues.extend(checker.check(package));
+                    }
+                    if let Some(checker) = &typosquats {
+                        issues.extend(checker.check(package));
+                    }
+                    issues.extend(self.config.bans.check(package));
+                    issues.extend(edition::check(package, self.config.min_edition));
+                    issues.extend(default_features.remove(&package.id));
+                    let pins = pinned_by.remove(&package.id).unwrap_or_default();
+                    issues.extend(pins::check(package, &pins, &found));
+                    if macros::is_proc_macro(package) {
+                        let pulled_in_by = direct_dependencies_to(&graph, &member_ids, package);
+                        issues.extend(macros::check(package, &pulled_in_by));
+                    }
+                }
+
+                finished[index] = finish(index, package, issues);
+            }
+        });
+        // Known only once every package's own sources have been scanned
+        if !self.config.duplicates_only {
+            for (index, &package) in packages.iter().enumerate() {
+                if member_ids.contains(&&package.id) {
+                    continue;
+                }
+                let key = package_key(package);
+                let Some(issue) =
+                    capabilities::transitive_process(package, &key, &own_capabilities, &dep_tree)
+                else {
+                    continue;
+                };
+                let issues = finish(index, package, vec![issue]);
+                finished[index].extend(issues);
+                scanner::sort_issues(&mut finished[index]);
+            }
+        }
+        progress.finish();
+        timings.wall_ms = timings::millis(started.elapsed());
+        if let Some((hits, misses)) = self.security_scanner.cache_stats() {
+            debug!("File scan cache: {} hits, {} misses", hits, misses);
+        }
+
+        let mut security_issues = BTreeMap::new();
+        for (package, issues) in packages.iter().zip(finished) {
+            if !issues.is_empty() {
+                security_issues
+                    .entry(package.name.clone())
+                    .or_insert_with(Vec::new)
+                    .extend(issues);
+            }
+        }
+
+        let mut accepted = BTreeMap::new();
+        for (package, issues) in packages.iter().zip(accepted_issues) {
+            if !issues.is_empty() {
+                accepted
+                    .entry(package.name.clone())
+                    .or_insert_with(Vec::new)
+                    .extend(issues);
+            }
+        }
+        if advisories.is_some() {
+            self.accepted.warn_unmatched(&accepted);
+        }
+
+        let weights = &self.config.risk_weights;
+        let package_risk: BTreeMap<String, u32> = security_issues
+            .iter()
+            .map(|(package, issues)| {
+                let score = issues.iter().fold(0u32, |total, issue| {
+                    total.saturating_add(weights.weight(&issue.severity))
+                });
+                (package.clone(), score)
+            })
+            .collect();
+        let risk_score = package_risk
+            .values()
+            .fold(0u32, |total, &score| total.saturating_add(score));
+
+        let resolved_packages = packages
+            .iter()
+            .map(|package| {
+                let resolved = ResolvedPackage {
+                    name: package.name.clone(),
+                    version: package.version.to_string(),
+                    license: package.license.clone(),
+                    source: package.source.as_ref().map(|source| match &redactor {
+                        Some(redactor) => redactor.text(&source.repr),
+                        None => source.repr.clone(),
+                    }),
+                    member: member_ids.contains(&&package.id),
+                };
+                (package_key(package), resolved)
+            })
+            .collect();
+
+        let capabilities = capabilities::aggregate(&own_capabilities, &dep_tree);
+        let unchanged_packages = self
+            .config
+            .since
+            .clone()
+            .filter(|_| locked_before.is_some())
+            .map(|since| UnchangedPackages {
+                since,
+                packages: packages
+                    .iter()
+                    .filter(|package| unchanged.contains(&package.id))
+                    .map(|package| package_key(package))
+                    .collect(),
+            });
+        let incomplete = unscanned.contains(&true).then(|| {
+            let timeout = self.config.scan_timeout.unwrap_or_default();
+            let unscanned_packages: Vec<String> = packages
+                .iter()
+                .zip(&unscanned)
+                .filter(|(_, &unscanned)| unscanned)
+                .map(|(package, _)| package_key(package))
+                .collect();
+            warn!(
+                "Scan timeout of {} reached, {} packages not scanned",
+                humantime::format_duration(timeout),
+                unscanned_packages.len()
+            );
+            Incomplete {
+                timeout: humantime::format_duration(timeout).to_string(),
+                unscanned_packages,
+            }
+        });
+        #[cfg(feature = "otel")]
+        tracing::Span::current()
+            .record("packages", packages.len())
+            .record(
+                "issues",
+                security_issues.values().map(Vec::len).sum::<usize>(),
+            );
+        let analysis = DependencyAnalysis {
+            total_dependencies: packages.len() - members.len(),
             direct_dependencies: direct_deps,
             dependency_tree: dep_tree,
+            packages: resolved_packages,
             security_issues,
-        })
+            suppressed_issues,
+            accepted_issues: accepted,
+            duplicate_crates,
+            proc_macro_crates,
+            patched_crates,
+            outdated_crates,
+            capabilities,
+            risk_score,
+            package_risk,
+            filtered_issues: low_confidence_issues,
+            baseline: None,
+            timings: self.config.profile.then_some(timings),
+            fix_checks: None,
+            unchanged_packages,
+            incomplete,
+        };
+
+        // A run without the advisory database or cut short by the timeout
+        // is incomplete; don't let it stand in for a full one
+        let complete =
+            (!self.config.advisories || advisories.is_some()) && analysis.incomplete.is_none();
+        if let Some(cache) = cache.filter(|_| complete) {
+            if let Err(err) = cache.store(&analysis) {
+                warn!("Failed to cache analysis: {}", err);
+            }
+        }
+
+        Ok(analysis)
+    }
+
+    /// The cache entry for this run, keyed by the contents of Cargo.lock,
+    /// the tool and pattern-set versions, the advisory database revision
+    /// and every setting that affects the result. Without a lockfile there
+    /// is nothing stable to key on.
+    ///
+    /// Entries expire with the advisory database and registry index data
+    /// they were computed from, so that new advisories and yanked releases
+    /// are reported.
+    async fn analysis_cache(&self) -> Option<AnalysisCache> {
+        if self.config.metadata_json.is_some() {
+            debug!("Analyzing saved metadata, analysis cache disabled");
+            return None;
+        }
+        let manifest_path = Path::new(&self.manifest_path).canonicalize().ok()?;
+        let Some(root) = manifest_path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join("Cargo.lock").is_file())
+        else {
+            debug!("No Cargo.lock found, analysis cache disabled");
+            return None;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        fs::read(root.join("Cargo.lock")).ok()?.hash(&mut hasher);
+        fs::read(root.join(IGNORE_FILE)).ok().hash(&mut hasher);
+        // `[patch]` and `[replace]` findings come from the root manifest
+        fs::read(root.join("Cargo.toml")).ok().hash(&mut hasher);
+        // and registry findings from the cargo configuration and credentials
+        for path in cargo_config::files(root) {
+            fs::read(path).ok().hash(&mut hasher);
+        }
+        cargo_config::credentials().hash(&mut hasher);
+        manifest_path.hash(&mut hasher);
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        scanner::PATTERN_SET_VERSION.hash(&mut hasher);
+        let config = &self.config;
+        let revision = if config.advisories {
+            AdvisoryDatabase::revision(config.advisory_db.as_deref()).await
+        } else {
+            None
+        };
+        revision.hash(&mut hasher);
+
+        // The accepted advisories that have not expired, rather than the
+        // setting, so that an acceptance expiring invalidates the cache
+        let settings: [&dyn fmt::Debug; 43] = [
+            &config.package,
+            &config.skip_dev,
+            &config.skip_build,
+            &config.max_depth,
+            &config.features,
+            &config.all_features,
+            &config.no_default_features,
+            &config.active_features_only,
+            &config.advisory_db,
+            &config.offline,
+            &config.advisories,
+            &config.yanked,
+            &config.maintenance,
+            &config.max_age_months,
+            &config.outdated,
+            &config.include_comments,
+            &config.exclude_dirs,
+            &config.include_target,
+            &config.vendor_dir,
+            &config.secrets,
+            &config.secret_rules,
+            &config.redact_paths,
+            &config.min_confidence,
+            &config.group_by_rule,
+            &config.merge,
+            &config.duplicates_only,
+            &config.ignore,
+            &config.disabled_rules,
+            &self.accepted,
+            &config.severity_overrides,
+            &config.dependency_severity,
+            &config.patterns,
+            &config.license,
+            &config.allow_git,
+            &config.bans,
+            &config.typosquat,
+            &config.allow_similar,
+            &config.internal_crates,
+            &config.internal_registry,
+            &config.min_edition,
+            &config.sensitive_features,
+            &config.plugin_dir,
+            &config.risk_weights,
+        ];
+        format!("{:?}", settings).hash(&mut hasher);
+        // A rebuilt plugin changes the findings without changing a setting
+        if let Some(dir) = &config.plugin_dir {
+            let mut stamps: Vec<_> = fs::read_dir(dir)
+                .ok()?
+                .flatten()
+                .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+                .collect();
+            stamps.sort();
+            stamps.hash(&mut hasher);
+        }
+
+        match AnalysisCache::new(hasher.finish()) {
+            // Offline, nothing is refreshed; a pre-cloned git checkout is
+            // covered by its revision
+            Ok(cache) if config.offline => Some(cache),
+            Ok(mut cache) => {
+                if config.advisories && (config.advisory_db.is_none() || revision.is_none()) {
+                    cache = cache.max_age(Duration::from_secs(config.advisory_db_ttl));
+                }
+                if config.yanked || config.outdated {
+                    cache = cache.max_age(registry::CACHE_TTL);
+                }
+                if config.maintenance {
+                    cache = cache.max_age(maintenance::CACHE_TTL);
+                }
+                Some(cache)
+            }
+            Err(err) => {
+                warn!("Analysis cache unavailable: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Dependency kinds excluded with `skip-dev` and `skip-build`.
+    fn skipped_kinds(&self) -> Vec<CargoDependencyKind> {
+        let mut skip = Vec::new();
+        if self.config.skip_dev {
+            skip.push(CargoDependencyKind::Development);
+        }
+        if self.config.skip_build {
+            skip.push(CargoDependencyKind::Build);
+        }
+        skip
+    }
+
+    /// Resolves the dependency graph with `cargo metadata`, with the
+    /// configured features.
+    #[cfg_attr(
+        feature = "otel",
+        tracing::instrument(name = "resolve_metadata", target = "telemetry", skip_all)
+    )]
+    fn metadata(&self) -> Result<Metadata> {
+        let mut command = MetadataCommand::new();
+        command.manifest_path(&self.manifest_path);
+        if !self.config.features.is_empty() {
+            command.features(CargoOpt::SomeFeatures(self.config.features.clone()));
+        }
+        if self.config.all_features {
+            command.features(CargoOpt::AllFeatures);
+        }
+        if self.config.no_default_features {
+            command.features(CargoOpt::NoDefaultFeatures);
+        }
+        Ok(command.exec()?)
+    }
+
+    /// The packages to analyze: the `--package` member if one was requested,
+    /// the root package, or every member of a virtual workspace.
+    fn select_members<'a>(&self, metadata: &'a Metadata) -> Result<Vec<&'a Package>> {
+        let members: Vec<&Package> = metadata
+            .packages
+            .iter()
+            .filter(|package| metadata.workspace_members.contains(&package.id))
+            .collect();
+
+        if let Some(name) = &self.config.package {
+            let member = members
+                .into_iter()
+                .find(|member| &member.name == name)
+                .ok_or_else(|| GuardianError::Config {
+                    message: format!("No workspace member named {}", name),
+                    source: None,
+                })?;
+            return Ok(vec![member]);
+        }
+
+        match metadata.root_package() {
+            Some(root) => Ok(vec![root]),
+            None if members.is_empty() => Err(GuardianError::Config {
+                message: "No root package found".to_string(),
+                source: None,
+            }),
+            None => {
+                info!("Virtual workspace, analyzing {} members", members.len());
+                Ok(members)
+            }
+        }
     }
 
+    /// Builds the resolved dependency graph, keyed by `name@version`, so that
+    /// several versions of one crate stay distinct.
     fn build_dependency_tree(
         &self,
-        packages: &[Package],
-        tree: &mut HashMap<String, Vec<String>>,
+        metadata: &Metadata,
+        graph: &DependencyGraph,
+        skip: &[CargoDependencyKind],
+        tree: &mut BTreeMap<String, Vec<DependencyEdge>>,
     ) -> Result<()> {
-        for package in packages {
-            let deps: Vec<String> = package
-                .dependencies
+        let Some(resolve) = &metadata.resolve else {
+            warn!("Dependency resolution unavailable, dependency tree will be empty");
+            return Ok(());
+        };
+
+        let names: HashMap<_, _> = metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package_key(package)))
+            .collect();
+
+        for node in resolve.nodes.iter().filter(|node| graph.contains(&node.id)) {
+            let Some(name) = names.get(&node.id) else {
+                continue;
+            };
+
+            let edges = node
+                .deps
                 .iter()
-                .map(|dep| dep.name.clone())
+                .filter_map(|dep| {
+                    if !graph::is_followed(dep, skip) || !graph.contains(&dep.pkg) {
+                        return None;
+                    }
+                    let mut kinds: Vec<DependencyKind> = dep
+                        .dep_kinds
+                        .iter()
+                        .filter(|info| !skip.contains(&info.kind))
+                        .map(|info| match info.kind {
+                            CargoDependencyKind::Development => DependencyKind::Dev,
+                            CargoDependencyKind::Build => DependencyKind::Build,
+                            _ => DependencyKind::Normal,
+                        })
+                        .collect();
+                    kinds.sort();
+                    kinds.dedup();
+
+                    Some(DependencyEdge {
+                        package: names.get(&dep.pkg)?.clone(),
+                        kinds,
+                    })
+                })
                 .collect();
 
-            tree.insert(package.name.clone(), deps);
+            tree.insert(name.clone(), edges);
         }
 
         Ok(())
     }
 }
+
+/// Flags crates resolved at two or more versions, listing each version with
+/// the packages that require it. Issues are keyed by crate name.
+fn duplicate_issues(
+    metadata: &Metadata,
+    graph: &DependencyGraph,
+    packages: &[&Package],
+    bans: &Bans,
+) -> HashMap<String, SecurityIssue> {
+    let mut requested_by: HashMap<&PackageId, Vec<&str>> = HashMap::new();
+    if let Some(resolve) = &metadata.resolve {
+        for node in resolve.nodes.iter().filter(|node| graph.contains(&node.id)) {
+            let Some(dependent) = graph.package(&node.id) else {
+                continue;
+            };
+            for dep in &node.deps {
+                requested_by
+                    .entry(&dep.pkg)
+                    .or_default()
+                    .push(&dependent.name);
+            }
+        }
+    }
+
+    let mut by_name: BTreeMap<&str, Vec<&Package>> = BTreeMap::new();
+    for &package in packages.iter().filter(|package| !bans.skips(package)) {
+        by_name.entry(&package.name).or_default().push(package);
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, mut versions)| {
+            versions.sort_by(|a, b| a.version.cmp(&b.version));
+            let details: Vec<String> = versions
+                .iter()
+                .map(|package| {
+                    let mut dependents = requested_by.get(&package.id).cloned().unwrap_or_default();
+                    dependents.sort_unstable();
+                    dependents.dedup();
+                    format!(
+                        "{} (required by {})",
+                        package.version,
+                        dependents.join(", ")
+                    )
+                })
+                .collect();
+
+            let issue = SecurityIssue {
+                severity: Severity::Low,
+                description: format!(
+                    "{} versions of {} are in the tree: {}",
+                    versions.len(),
+                    name,
+                    details.join("; ")
+                ),
+                affected_versions: versions
+                    .iter()
+                    .map(|package| package.version.to_string())
+                    .collect(),
+                rule_id: Some("GUARD016".to_string()),
+                ..Default::default()
+            };
+            (name.to_string(), issue)
+        })
+        .collect()
+}
+
+/// Collapses located findings of the same rule into one issue per rule,
+/// keeping every location and the position of the first finding.
+fn group_by_rule(issues: Vec<SecurityIssue>) -> Vec<SecurityIssue> {
+    let mut grouped: Vec<SecurityIssue> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for issue in issues {
+        let (Some(rule), Some(location)) = (issue.rule_id.clone(), issue.location.clone()) else {
+            grouped.push(issue);
+            continue;
+        };
+
+        match index.get(&rule) {
+            Some(&i) => grouped[i].locations.push(location),
+            None => {
+                index.insert(rule, grouped.len());
+                grouped.push(SecurityIssue {
+                    locations: vec![location],
+                    ..issue
+                });
+            }
+        }
+    }
+
+    // A rule that matched once is not a rollup
+    for issue in &mut grouped {
+        if issue.locations.len() == 1 {
+            issue.locations.clear();
+        }
+    }
+    grouped
+}
+
+/// Merges findings of different rules on the same line of the same file
+/// into one issue, led by the most severe rule (then the lowest rule ID),
+/// with the others in `related_rules` and every description joined.
+/// Repeated matches of a single rule on a line stay separate.
+fn merge_overlapping(issues: Vec<SecurityIssue>) -> Vec<SecurityIssue> {
+    let mut groups: Vec<Vec<SecurityIssue>> = Vec::new();
+    let mut index: HashMap<(PathBuf, usize), usize> = HashMap::new();
+
+    for issue in issues {
+        let (Some(_), Some(location)) = (&issue.rule_id, &issue.location) else {
+            groups.push(vec![issue]);
+            continue;
+        };
+        let key = (location.file.clone(), location.line);
+        match index.get(&key) {
+            Some(&i) if !groups[i].iter().any(|other| other.rule_id == issue.rule_id) => {
+                groups[i].push(issue)
+            }
+            Some(_) => groups.push(vec![issue]),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(vec![issue]);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            group.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.rule_id.cmp(&b.rule_id)));
+            let others = group.split_off(1);
+            let mut merged = group.pop().expect("groups are never empty");
+            for other in others {
+                merged.description = format!("{}; {}", merged.description, other.description);
+                merged.related_rules.extend(other.rule_id);
+            }
+            merged
+        })
+        .collect()
+}
+
+/// The package that `dep`, declared by `member`, resolved to.
+fn resolved_dependency<'a>(
+    metadata: &Metadata,
+    graph: &DependencyGraph<'a>,
+    member: &Package,
+    dep: &cargo_metadata::Dependency,
+) -> Option<&'a Package> {
+    let node = metadata
+        .resolve
+        .as_ref()?
+        .nodes
+        .iter()
+        .find(|node| node.id == member.id)?;
+    node.deps
+        .iter()
+        .filter_map(|node_dep| graph.package(&node_dep.pkg))
+        .find(|package| package.name == dep.name && dep.req.matches(&package.version))
+}
+
+/// Reads `cargo metadata --format-version 1` output saved to `path`.
+fn load_metadata(path: &Path) -> Result<Metadata> {
+    let json = fs::read_to_string(path).map_err(|err| GuardianError::io(path, err))?;
+    let metadata: serde_json::Value = serde_json::from_str(&json).map_err(|err| {
+        GuardianError::config(format!("{} is not valid JSON", path.display()), err)
+    })?;
+    match metadata.get("version").and_then(serde_json::Value::as_u64) {
+        Some(1) => {}
+        Some(version) => {
+            return Err(GuardianError::Config {
+                message: format!(
+                    "{} is cargo metadata format version {}, only version 1 is supported",
+                    path.display(),
+                    version
+                ),
+                source: None,
+            })
+        }
+        None => {
+            return Err(GuardianError::Config {
+                message: format!(
+                    "{} is not cargo metadata output: it has no format version",
+                    path.display()
+                ),
+                source: None,
+            })
+        }
+    }
+    serde_json::from_value(metadata).map_err(|err| {
+        GuardianError::config(
+            format!("{} is not valid cargo metadata output", path.display()),
+            err,
+        )
+    })
+}
+
+/// Names of the members' direct dependencies that `package` is reached
+/// through, `package` itself if it is one.
+fn direct_dependencies_to(
+    graph: &DependencyGraph,
+    member_ids: &[&PackageId],
+    package: &Package,
+) -> Vec<String> {
+    let mut direct = BTreeSet::new();
+    let mut seen = BTreeSet::from([&package.id]);
+    let mut pending = vec![package];
+    while let Some(current) = pending.pop() {
+        for dependent in graph.dependents(&current.id) {
+            if member_ids.contains(&&dependent.id) {
+                direct.insert(current.name.clone());
+            } else if seen.insert(&dependent.id) {
+                pending.push(dependent);
+            }
+        }
+    }
+    direct.into_iter().collect()
+}
+
+/// Package names along the shortest path from the root to `package`.
+pub fn dependency_path(graph: &DependencyGraph, package: &Package) -> Vec<String> {
+    graph
+        .shortest_path(&package.id)
+        .map(|path| path.iter().map(|package| package.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// The version requirements that dependents resolved to `package` declare
+/// on it.
+fn requirements_on<'a>(metadata: &'a Metadata, package: &Package) -> Vec<&'a VersionReq> {
+    metadata
+        .packages
+        .iter()
+        .flat_map(|dependent| &dependent.dependencies)
+        .filter(|dep| dep.name == package.name && dep.req.matches(&package.version))
+        .map(|dep| &dep.req)
+        .collect()
+}
+
+/// Identifies a resolved package as `name@version`.
+pub fn package_key(package: &Package) -> String {
+    format!("{}@{}", package.name, package.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AcceptedAdvisory, FailOn};
+    use crate::models::Location;
+    use crate::testing::Project;
+
+    /// `app` depends on `log`, on `insta` in tests and on `cc` to build;
+    /// `insta` pulls `log` in as well. `insta` and `log` are vulnerable.
+    fn project() -> Project {
+        let mut project = Project::new();
+        project
+            .add("log", "0.4.0")
+            .add("insta", "1.0.0")
+            .add("cc", "1.0.0")
+            .depend("app", "log", CargoDependencyKind::Normal)
+            .depend("app", "insta", CargoDependencyKind::Development)
+            .depend("app", "cc", CargoDependencyKind::Build)
+            .depend("insta", "log", CargoDependencyKind::Normal);
+        project
+            .advisory("insta", "RUSTSEC-2024-0001", &[">= 2.0.0"])
+            .advisory("log", "RUSTSEC-2024-0002", &[">= 0.4.1"]);
+        project
+    }
+
+    #[tokio::test]
+    async fn analyzes_every_dependency_kind_by_default() {
+        let project = project();
+        let analysis = project.analyze(project.config()).await;
+
+        assert_eq!(analysis.total_dependencies, 3);
+        assert!(analysis.security_issues.contains_key("insta"));
+        assert!(analysis.security_issues.contains_key("log"));
+    }
+
+    #[tokio::test]
+    async fn skip_dev_drops_dev_only_dependencies() {
+        let project = project();
+        let config = Config {
+            skip_dev: true,
+            ..project.config()
+        };
+        let analysis = project.analyze(config).await;
+
+        assert_eq!(analysis.total_dependencies, 2);
+        assert!(!analysis.security_issues.contains_key("insta"));
+        // Also a normal dependency of `app`
+        assert!(analysis.security_issues.contains_key("log"));
+        let direct: Vec<&str> = analysis
+            .direct_dependencies
+            .iter()
+            .map(|dep| dep.name.as_str())
+            .collect();
+        assert_eq!(direct, ["log", "cc"]);
+    }
+
+    #[tokio::test]
+    async fn skip_build_drops_build_dependencies() {
+        let project = project();
+        let config = Config {
+            skip_build: true,
+            ..project.config()
+        };
+        let analysis = project.analyze(config).await;
+
+        assert_eq!(analysis.total_dependencies, 2);
+        assert!(!analysis.packages.contains_key("cc@1.0.0"));
+        assert!(analysis.security_issues.contains_key("insta"));
+    }
+
+    #[tokio::test]
+    async fn analyses_round_trip_through_json() {
+        let project = project();
+        let analysis = project.analyze(project.config()).await;
+
+        let json = serde_json::to_value(&analysis).unwrap();
+        let parsed: DependencyAnalysis = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        // Pre-1.0 version, serialized as it is displayed
+        assert_eq!(
+            json["security_issues"]["log"][0]["severity"],
+            Severity::Low.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn accepted_advisories_are_reported_apart_until_they_expire() {
+        let project = project();
+        let accept = |id: &str, expires: &str| AcceptedAdvisory {
+            id: id.to_string(),
+            expires: Some(expires.to_string()),
+            reason: Some("Not reachable".to_string()),
+        };
+        let config = Config {
+            accepted_advisories: vec![
+                accept("RUSTSEC-2024-0001", "2999-01-01"),
+                accept("RUSTSEC-2024-0002", "2000-01-01"),
+                // Affects no dependency; only warned about
+                accept("RUSTSEC-2099-0001", "2999-01-01"),
+            ],
+            ..project.config()
+        };
+        let analysis = project.analyze(config).await;
+
+        let advisories = |issues: Option<&Vec<SecurityIssue>>| -> Vec<String> {
+            issues
+                .into_iter()
+                .flatten()
+                .filter_map(|issue| issue.advisory_id.clone())
+                .collect()
+        };
+        assert!(advisories(analysis.security_issues.get("insta")).is_empty());
+        assert_eq!(
+            advisories(analysis.security_issues.get("log")),
+            ["RUSTSEC-2024-0002"]
+        );
+        let accepted = &analysis.accepted_issues["insta"];
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(
+            accepted[0].issue.advisory_id.as_deref(),
+            Some("RUSTSEC-2024-0001")
+        );
+        assert_eq!(accepted[0].reason.as_deref(), Some("Not reachable"));
+        assert!(!analysis.accepted_issues.contains_key("log"));
+    }
+
+    #[tokio::test]
+    async fn severity_overrides_apply_to_every_use_of_the_severity() {
+        let project = project();
+        let baseline = project.analyze(project.config()).await;
+        let config = Config {
+            severity_overrides: BTreeMap::from([
+                ("GUARD009".to_string(), Severity::Critical),
+                ("RUSTSEC-2024-0001".to_string(), Severity::Low),
+            ]),
+            fail_on: Some(FailOn::Critical),
+            ..project.config()
+        };
+        let mut analysis = project.analyze(config.clone()).await;
+
+        let severity = |analysis: &DependencyAnalysis, package: &str, id: &str| {
+            analysis.security_issues[package]
+                .iter()
+                .find(|issue| [&issue.rule_id, &issue.advisory_id].contains(&&Some(id.to_string())))
+                .map(|issue| issue.severity.clone())
+        };
+        assert_eq!(
+            severity(&analysis, "log", "GUARD009"),
+            Some(Severity::Critical)
+        );
+        assert_eq!(
+            severity(&analysis, "insta", "RUSTSEC-2024-0001"),
+            Some(Severity::Low)
+        );
+        // Both pre-1.0 findings (`app` and `log`) raised from Low to
+        // Critical, the advisory lowered from High to Low
+        assert_eq!(
+            analysis.risk_score,
+            baseline.risk_score + 2 * (10 - 1) - (5 - 1)
+        );
+
+        let failing: Vec<&SecurityIssue> = analysis
+            .security_issues
+            .values()
+            .flatten()
+            .filter(|issue| {
+                config
+                    .fail_threshold(issue)
+                    .is_some_and(|threshold| issue.severity.meets(&threshold))
+            })
+            .collect();
+        assert_eq!(failing.len(), 2);
+        assert!(failing
+            .iter()
+            .all(|issue| issue.rule_id.as_deref() == Some("GUARD009")));
+
+        analysis.retain_min_severity(&Severity::Medium);
+        assert_eq!(
+            severity(&analysis, "log", "GUARD009"),
+            Some(Severity::Critical)
+        );
+        assert!(!analysis.security_issues.contains_key("insta"));
+    }
+
+    #[tokio::test]
+    async fn dependency_severity_escalates_matching_crates_only() {
+        let project = project();
+        let config = Config {
+            dependency_severity: BTreeMap::from([("ins*".to_string(), Escalation::Levels(1))]),
+            fail_on: Some(FailOn::Critical),
+            ..project.config()
+        };
+        let analysis = project.analyze(config.clone()).await;
+
+        let advisory = |package: &str| {
+            analysis.security_issues[package]
+                .iter()
+                .find(|issue| issue.advisory_id.is_some())
+                .cloned()
+                .unwrap()
+        };
+        let insta = advisory("insta");
+        assert_eq!(insta.severity, Severity::Critical);
+        assert_eq!(insta.escalated_from, Some(Severity::High));
+        let log = advisory("log");
+        assert_eq!(log.severity, Severity::High);
+        assert_eq!(log.escalated_from, None);
+
+        // Escalated before `--fail-on`
+        let threshold = config.fail_threshold(&insta).unwrap();
+        assert!(insta.severity.meets(&threshold));
+        assert!(!log.severity.meets(&config.fail_threshold(&log).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn max_depth_stops_below_the_direct_dependencies_children() {
+        // app -> tokio -> mio -> libc, and tokio -> bytes
+        let mut project = Project::new();
+        project
+            .add("tokio", "1.0.0")
+            .add("mio", "1.0.0")
+            .add("bytes", "1.0.0")
+            .add("libc", "1.0.0")
+            .depend("app", "tokio", CargoDependencyKind::Normal)
+            .depend("tokio", "mio", CargoDependencyKind::Normal)
+            .depend("tokio", "bytes", CargoDependencyKind::Normal)
+            .depend("mio", "libc", CargoDependencyKind::Normal);
+        project.advisory("libc", "RUSTSEC-2024-0003", &[">= 2.0.0"]);
+
+        let unlimited = project.analyze(project.config()).await;
+        let config = Config {
+            max_depth: Some(1),
+            ..project.config()
+        };
+        let analysis = project.analyze(config).await;
+
+        assert_eq!(unlimited.total_dependencies, 4);
+        assert!(unlimited.security_issues.contains_key("libc"));
+        assert!(unlimited.dependency_tree["mio@1.0.0"]
+            .iter()
+            .any(|edge| edge.package == "libc@1.0.0"));
+        assert_eq!(analysis.total_dependencies, 3);
+        let packages: Vec<&str> = analysis.packages.keys().map(String::as_str).collect();
+        assert_eq!(
+            packages,
+            ["app@0.1.0", "bytes@1.0.0", "mio@1.0.0", "tokio@1.0.0"]
+        );
+        assert!(!analysis.security_issues.contains_key("libc"));
+        assert!(!analysis
+            .dependency_tree
+            .values()
+            .flatten()
+            .any(|edge| edge.package == "libc@1.0.0"));
+
+        let config = Config {
+            max_depth: Some(0),
+            ..project.config()
+        };
+        assert_eq!(project.analyze(config).await.total_dependencies, 1);
+    }
+
+    #[tokio::test]
+    async fn fingerprints_are_stable_across_scans_and_checkouts() {
+        /// Fingerprints of the findings on a fresh copy of the same project.
+        async fn fingerprints() -> Vec<(String, String)> {
+            let project = project();
+            project.file(
+                "log",
+                "src/lib.rs",
+                "pub fn get(v: &[u8]) -> u8 { unsafe { *v.get_unchecked(0) } }\n",
+            );
+            let analysis = project.analyze(project.config()).await;
+            let mut fingerprints: Vec<(String, String)> = analysis
+                .security_issues
+                .into_iter()
+                .flat_map(|(package, issues)| {
+                    issues.into_iter().map(move |issue| {
+                        assert_eq!(issue.fingerprint.len(), 64);
+                        (package.clone(), issue.fingerprint)
+                    })
+                })
+                .collect();
+            fingerprints.sort();
+            fingerprints
+        }
+
+        let first = fingerprints().await;
+        let second = fingerprints().await;
+
+        assert!(first.iter().any(|(package, _)| package == "log"));
+        assert_eq!(first, second);
+    }
+
+    /// A finding of `rule` on `line` of `src/lib.rs`.
+    fn finding(rule: &str, line: usize, severity: Severity) -> SecurityIssue {
+        SecurityIssue {
+            severity,
+            description: format!("{} finding", rule),
+            rule_id: Some(rule.to_string()),
+            location: Some(Location {
+                file: PathBuf::from("src/lib.rs"),
+                line,
+                column: 1,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_on_the_same_line_merge_under_the_most_severe() {
+        let merged = merge_overlapping(vec![
+            finding("GUARD009", 3, Severity::Low),
+            finding("GUARD001", 3, Severity::High),
+        ]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].rule_id.as_deref(), Some("GUARD001"));
+        assert_eq!(merged[0].severity, Severity::High);
+        assert_eq!(merged[0].related_rules, ["GUARD009"]);
+        assert_eq!(merged[0].description, "GUARD001 finding; GUARD009 finding");
+    }
+
+    #[test]
+    fn repeated_matches_and_other_lines_stay_separate() {
+        let same_rule = merge_overlapping(vec![
+            finding("GUARD001", 3, Severity::High),
+            finding("GUARD001", 3, Severity::High),
+        ]);
+        assert_eq!(same_rule.len(), 2);
+        assert!(same_rule.iter().all(|issue| issue.related_rules.is_empty()));
+
+        let other_lines = merge_overlapping(vec![
+            finding("GUARD001", 3, Severity::High),
+            finding("GUARD009", 4, Severity::Low),
+        ]);
+        assert_eq!(other_lines.len(), 2);
+        assert!(other_lines
+            .iter()
+            .all(|issue| issue.related_rules.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn no_merge_reports_every_rule_on_its_own() {
+        let project = project();
+        // An `unsafe` block and a pointer dereference on one line
+        project.file(
+            "log",
+            "src/lib.rs",
+            "pub fn second(p: *const u8) -> u8 { unsafe { *p.add(1) } }\n",
+        );
+        let rules = |analysis: &DependencyAnalysis| -> Vec<(String, Vec<String>)> {
+            analysis.security_issues["log"]
+                .iter()
+                .filter(|issue| issue.location.is_some())
+                .filter_map(|issue| Some((issue.rule_id.clone()?, issue.related_rules.clone())))
+                .collect()
+        };
+
+        let merged = project.analyze(project.config()).await;
+        assert_eq!(
+            rules(&merged),
+            [("GUARD001".to_string(), vec!["GUARD056".to_string()])]
+        );
+
+        let config = Config {
+            merge: false,
+            ..project.config()
+        };
+        let unmerged = project.analyze(config).await;
+        assert_eq!(
+            rules(&unmerged),
+            [
+                ("GUARD001".to_string(), Vec::new()),
+                ("GUARD056".to_string(), Vec::new())
+            ]
+        );
+    }
+}
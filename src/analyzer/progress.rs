@@ -0,0 +1,63 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tracing::info;
+
+/// Operator feedback while packages are scanned: a progress bar on stderr
+/// when it is a terminal, otherwise a log line every tenth of the way.
+pub struct ScanProgress {
+    bar: Option<ProgressBar>,
+    total: usize,
+    done: AtomicUsize,
+    started: Instant,
+}
+
+impl ScanProgress {
+    pub fn new(total: usize, show_bar: bool) -> Self {
+        let bar = show_bar.then(|| {
+            let bar = ProgressBar::new(total as u64);
+            if let Ok(style) = ProgressStyle::with_template(
+                "{spinner} [{elapsed_precise}] {bar:40} {pos}/{len} {msg}",
+            ) {
+                bar.set_style(style);
+            }
+            bar
+        });
+
+        Self {
+            bar,
+            total,
+            done: AtomicUsize::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// Records that `package` has been scanned.
+    pub fn advance(&self, package: &str) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        match &self.bar {
+            Some(bar) => {
+                bar.set_message(package.to_string());
+                bar.inc(1);
+            }
+            None => {
+                let step = (self.total / 10).max(1);
+                if done.is_multiple_of(step) && done < self.total {
+                    info!("Scanned {}/{} packages", done, self.total);
+                }
+            }
+        }
+    }
+
+    pub fn finish(&self) {
+        let message = format!(
+            "Scanned {} packages in {:.1?}",
+            self.total,
+            self.started.elapsed()
+        );
+        match &self.bar {
+            Some(bar) => bar.finish_with_message(message),
+            None => info!("{}", message),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::scanner::StageTimings;
+
+/// Where the package scans spent their time, recorded with `--profile`.
+/// Times are in milliseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timings {
+    /// Wall-clock time of the scan phase, with packages scanned in parallel
+    pub wall_ms: f64,
+    /// Time of each stage, summed over all packages
+    pub stages: BTreeMap<String, f64>,
+    /// Scan time of each package, keyed like `dependency_tree`
+    pub packages: BTreeMap<String, PackageTimings>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageTimings {
+    pub total_ms: f64,
+    pub stages: BTreeMap<String, f64>,
+}
+
+impl Timings {
+    pub fn record(&mut self, package: String, timings: &StageTimings) {
+        let mut stages = BTreeMap::new();
+        for (stage, elapsed) in timings.stages() {
+            *self.stages.entry(stage.to_string()).or_default() += millis(elapsed);
+            stages.insert(stage.to_string(), millis(elapsed));
+        }
+        let total_ms = millis(timings.total());
+        self.packages
+            .insert(package, PackageTimings { total_ms, stages });
+    }
+
+    /// Time summed over all packages, which exceeds `wall_ms` when they
+    /// were scanned in parallel.
+    pub fn cpu_ms(&self) -> f64 {
+        self.packages.values().map(|package| package.total_ms).sum()
+    }
+}
+
+pub(super) fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+use tokio::process::Command;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::Severity;
+use crate::report::SeverityCounts;
+
+/// One line of the `--audit-log` ledger: the outcome of a run, without its
+/// findings.
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    timestamp: String,
+    manifest_path: String,
+    version: &'static str,
+    /// `HEAD` of the git repository holding the manifest, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    total_dependencies: usize,
+    issues: usize,
+    by_severity: SeverityCounts,
+    risk_score: u32,
+    fail_on: Option<&'a Severity>,
+    failing_issues: usize,
+    passed: bool,
+}
+
+/// Appends a record of the analysis of `manifest_path` and its `--fail-on`
+/// outcome in `failing` to the JSON Lines file at `path`, creating it if
+/// needed.
+///
+/// The record is written with a single append, which the OS does not
+/// interleave with the appends of other runs sharing the log.
+pub async fn append(
+    path: &Path,
+    manifest_path: &str,
+    analysis: &DependencyAnalysis,
+    failing: Option<&(Severity, usize)>,
+) -> Result<()> {
+    let manifest = Path::new(manifest_path);
+    let failing_issues = failing.map_or(0, |(_, count)| *count);
+    let record = AuditRecord {
+        timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        manifest_path: manifest
+            .canonicalize()
+            .map_or(manifest_path.to_string(), |path| path.display().to_string()),
+        version: env!("CARGO_PKG_VERSION"),
+        commit: commit(manifest).await,
+        total_dependencies: analysis.total_dependencies,
+        issues: analysis.security_issues.values().map(Vec::len).sum(),
+        by_severity: SeverityCounts::of(analysis),
+        risk_score: analysis.risk_score,
+        fail_on: failing.map(|(threshold, _)| threshold),
+        failing_issues,
+        passed: failing_issues == 0,
+    };
+
+    let mut line = serde_json::to_string(&record).map_err(GuardianError::report)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|err| GuardianError::io(path, err))
+}
+
+/// The commit checked out in the git repository holding `manifest`, when
+/// it is a local file rather than stdin or a URL.
+async fn commit(manifest: &Path) -> Option<String> {
+    if !manifest.is_file() {
+        return None;
+    }
+    let dir = manifest
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
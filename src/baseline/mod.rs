@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+use crate::report;
+
+/// How the current findings compare with a saved baseline analysis.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BaselineDiff {
+    /// Issues not present in the baseline
+    pub new: usize,
+    /// Baseline issues that are no longer reported
+    pub fixed: BTreeMap<String, Vec<SecurityIssue>>,
+    /// Issues already present in the baseline
+    pub unchanged: BTreeMap<String, Vec<SecurityIssue>>,
+}
+
+/// Loads an analysis previously saved with `--write-baseline` (or any
+/// `--output json` report).
+pub fn load(path: &Path) -> Result<DependencyAnalysis> {
+    let content = fs::read_to_string(path).map_err(|err| GuardianError::io(path, err))?;
+    serde_json::from_str(&content)
+        .map_err(|err| GuardianError::config(format!("Invalid baseline {}", path.display()), err))
+}
+
+pub fn write(path: &Path, analysis: &DependencyAnalysis) -> Result<()> {
+    let content = report::json(analysis, report::SCHEMA_VERSION)?;
+    fs::write(path, content).map_err(|err| GuardianError::io(path, err))
+}
+
+/// Rewrites the baseline at `path` with `current`, the analysis before
+/// [`apply`], logging the issues that `compared`, the same analysis after
+/// it, found new and fixed. Left untouched when neither changed.
+pub fn update(
+    path: &Path,
+    current: &DependencyAnalysis,
+    compared: &DependencyAnalysis,
+) -> Result<()> {
+    let fixed = compared.baseline.as_ref().map(|diff| &diff.fixed);
+    let changes = [
+        ("Adding to", Some(&compared.security_issues)),
+        ("Removing from", fixed),
+    ];
+    let mut changed = false;
+    for (change, issues) in changes {
+        for (package, issues) in issues.into_iter().flatten() {
+            for issue in issues {
+                let id = issue.rule_id.as_deref().or(issue.advisory_id.as_deref());
+                info!(
+                    "{} the baseline: {} {}: {}",
+                    change,
+                    package,
+                    id.unwrap_or("-"),
+                    issue.description
+                );
+                changed = true;
+            }
+        }
+    }
+
+    if !changed {
+        info!("Baseline {} is up to date", path.display());
+        return Ok(());
+    }
+    write(path, current)?;
+    info!("Baseline {} updated", path.display());
+    Ok(())
+}
+
+/// Fingerprints of every issue in `analysis`.
+pub fn fingerprints(analysis: &DependencyAnalysis) -> HashSet<String> {
+    analysis
+        .security_issues
+        .iter()
+        .flat_map(|(package, issues)| issues.iter().map(|issue| fingerprint(package, issue)))
+        .collect()
+}
+
+/// Identity of an issue across runs: rule or advisory, package, location
+/// and description, independent of anything else that may have changed.
+pub fn fingerprint(package: &str, issue: &SecurityIssue) -> String {
+    let rule = issue
+        .rule_id
+        .as_deref()
+        .or(issue.advisory_id.as_deref())
+        .unwrap_or_default();
+    let location = issue
+        .location
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    format!("{}|{}|{}|{}", rule, package, location, issue.description)
+}
+
+/// Keeps only new issues in `analysis.security_issues` and records the
+/// fixed and unchanged ones in `analysis.baseline`.
+pub fn apply(analysis: &mut DependencyAnalysis, baseline: DependencyAnalysis) {
+    let known = fingerprints(&baseline);
+    let current = fingerprints(analysis);
+
+    let mut diff = BaselineDiff::default();
+    for (package, issues) in std::mem::take(&mut analysis.security_issues) {
+        let (unchanged, new): (Vec<_>, Vec<_>) = issues
+            .into_iter()
+            .partition(|issue| known.contains(&fingerprint(&package, issue)));
+
+        diff.new += new.len();
+        if !new.is_empty() {
+            analysis.security_issues.insert(package.clone(), new);
+        }
+        if !unchanged.is_empty() {
+            diff.unchanged.insert(package, unchanged);
+        }
+    }
+
+    for (package, issues) in baseline.security_issues {
+        let fixed: Vec<_> = issues
+            .into_iter()
+            .filter(|issue| !current.contains(&fingerprint(&package, issue)))
+            .collect();
+        if !fixed.is_empty() {
+            diff.fixed.insert(package, fixed);
+        }
+    }
+
+    analysis.baseline = Some(diff);
+}
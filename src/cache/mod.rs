@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+
+/// Root of the per-user cache directory shared by all cached data.
+pub fn cache_root() -> Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("dep-sense"))
+        .ok_or(GuardianError::NoCacheDir)
+}
+
+fn analysis_dir() -> Result<PathBuf> {
+    Ok(cache_root()?.join("analysis"))
+}
+
+fn files_dir() -> Result<PathBuf> {
+    Ok(cache_root()?.join("files"))
+}
+
+/// A cached `DependencyAnalysis`, stored under a key that covers everything
+/// the result depends on (lockfile, tool and pattern-set version, settings).
+pub struct AnalysisCache {
+    path: PathBuf,
+    max_age: Option<Duration>,
+}
+
+impl AnalysisCache {
+    pub fn new(key: u64) -> Result<Self> {
+        Ok(Self {
+            path: analysis_dir()?.join(format!("{:016x}.json", key)),
+            max_age: None,
+        })
+    }
+
+    /// Treats an entry older than `max_age` as missing, for results drawn
+    /// from data that changes without the key changing, like new advisories
+    /// or yanked releases. The shortest of several limits applies.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(self.max_age.map_or(max_age, |limit| limit.min(max_age)));
+        self
+    }
+
+    pub fn load(&self) -> Option<DependencyAnalysis> {
+        if let Some(max_age) = self.max_age {
+            if !is_fresh(&self.path, max_age) {
+                debug!(
+                    "No cached analysis from the last {}",
+                    humantime::format_duration(max_age)
+                );
+                return None;
+            }
+        }
+        let content = fs::read_to_string(&self.path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(analysis) => Some(analysis),
+            Err(err) => {
+                debug!("Ignoring unreadable cached analysis: {}", err);
+                None
+            }
+        }
+    }
+
+    pub fn store(&self, analysis: &DependencyAnalysis) -> Result<()> {
+        let body = serde_json::to_string(analysis)
+            .map_err(|err| GuardianError::io(&self.path, err.into()))?;
+        write_cache(&self.path, &body)
+    }
+
+    /// Removes every cached analysis.
+    pub fn clear() -> Result<()> {
+        let dir = analysis_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|err| GuardianError::io(&dir, err))?;
+        }
+        info!("Cleared analysis cache in {}", dir.display());
+        Ok(())
+    }
+}
+
+/// Whether the cache file at `path` exists and was written less than `ttl`
+/// ago.
+pub fn is_fresh(path: &Path, ttl: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < ttl)
+}
+
+pub fn read_cache(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| GuardianError::io(path, err))
+}
+
+/// Writes a cache file, creating its directory if needed.
+pub fn write_cache(path: &Path, body: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| GuardianError::io(parent, err))?;
+    }
+    fs::write(path, body).map_err(|err| GuardianError::io(path, err))
+}
+
+/// Scan results of single source files, keyed by a hash of the file's
+/// contents and the checks it was scanned with. Entries are written to a
+/// temporary file and renamed into place, so parallel scans can share it.
+#[derive(Debug)]
+pub struct FileCache {
+    dir: PathBuf,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    temp_files: AtomicUsize,
+}
+
+impl FileCache {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            dir: files_dir()?,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            temp_files: AtomicUsize::new(0),
+        })
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(format!("{}.json", key))
+    }
+
+    /// Returns the cached issues for `key`, with their locations pointing
+    /// at `file`. Missing or unreadable entries count as a miss.
+    pub fn load(&self, key: &str, file: &Path) -> Option<Vec<SecurityIssue>> {
+        let issues = fs::read_to_string(self.entry_path(key))
+            .ok()
+            .and_then(
+                |content| match serde_json::from_str::<Vec<SecurityIssue>>(&content) {
+                    Ok(issues) => Some(issues),
+                    Err(err) => {
+                        debug!("Ignoring unreadable scan cache entry {}: {}", key, err);
+                        None
+                    }
+                },
+            );
+
+        match issues {
+            Some(mut issues) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                for location in issues
+                    .iter_mut()
+                    .filter_map(|issue| issue.location.as_mut())
+                {
+                    location.file = file.to_path_buf();
+                }
+                Some(issues)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn store(&self, key: &str, issues: &[SecurityIssue]) -> Result<()> {
+        let path = self.entry_path(key);
+        let temp = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            self.temp_files.fetch_add(1, Ordering::Relaxed)
+        ));
+        let body =
+            serde_json::to_string(issues).map_err(|err| GuardianError::io(&temp, err.into()))?;
+        write_cache(&temp, &body)?;
+        fs::rename(&temp, &path).map_err(|err| GuardianError::io(&path, err))
+    }
+
+    /// Cache hits and misses so far.
+    pub fn stats(&self) -> (usize, usize) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Removes every cached file scan.
+    pub fn clear() -> Result<()> {
+        let dir = files_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|err| GuardianError::io(&dir, err))?;
+        }
+        info!("Cleared file scan cache in {}", dir.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use cargo_metadata::Package;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::BitOrAssign;
+
+use crate::macros;
+use crate::models::{DependencyEdge, DependencyKind, SecurityIssue, Severity};
+
+/// Crates whose purpose is network access; anything depending on them can
+/// reach the network.
+const NETWORK_CRATES: &[&str] = &[
+    "attohttpc",
+    "curl",
+    "h2",
+    "hyper",
+    "isahc",
+    "mio",
+    "reqwest",
+    "socket2",
+    "surf",
+    "tokio",
+    "ureq",
+];
+
+/// Crates whose purpose is file system access.
+const FILESYSTEM_CRATES: &[&str] = &["fs_extra", "notify", "tempfile", "walkdir"];
+
+/// Crates whose purpose is running other processes.
+const PROCESS_CRATES: &[&str] = &["duct", "subprocess"];
+
+/// What a package, or anything it depends on, is able to do at run time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilitySet {
+    pub network: bool,
+    pub filesystem: bool,
+    pub process: bool,
+    #[serde(rename = "unsafe")]
+    pub unsafe_code: bool,
+    pub ffi: bool,
+}
+
+impl CapabilitySet {
+    /// The capabilities of `package` itself, from its name and the findings
+    /// of its own source scan. Build scripts and procedural macros only run
+    /// at build time, which their own rules cover, so they add nothing.
+    pub fn of(package: &Package, issues: &[SecurityIssue]) -> Self {
+        if macros::is_proc_macro(package) {
+            return Self::default();
+        }
+        let name = package.name.as_str();
+        let mut own = Self {
+            network: NETWORK_CRATES.contains(&name),
+            filesystem: FILESYSTEM_CRATES.contains(&name),
+            process: PROCESS_CRATES.contains(&name),
+            unsafe_code: false,
+            ffi: package.links.is_some() || name.ends_with("-sys"),
+        };
+        for rule_id in issues.iter().filter_map(|issue| issue.rule_id.as_deref()) {
+            match rule_id {
+                "GUARD001" | "GUARD002" | "GUARD052" | "GUARD053" | "GUARD054" | "GUARD055"
+                | "GUARD056" => own.unsafe_code = true,
+                "GUARD004" | "GUARD039" => own.ffi = true,
+                "GUARD006" => own.process = true,
+                "GUARD007" => own.filesystem = true,
+                "GUARD008" => own.network = true,
+                _ => {}
+            }
+        }
+        own
+    }
+
+    /// The capabilities by name, in column order.
+    pub fn flags(&self) -> [(&'static str, bool); 5] {
+        [
+            ("network", self.network),
+            ("filesystem", self.filesystem),
+            ("process", self.process),
+            ("unsafe", self.unsafe_code),
+            ("ffi", self.ffi),
+        ]
+    }
+}
+
+impl BitOrAssign for CapabilitySet {
+    fn bitor_assign(&mut self, other: Self) {
+        self.network |= other.network;
+        self.filesystem |= other.filesystem;
+        self.process |= other.process;
+        self.unsafe_code |= other.unsafe_code;
+        self.ffi |= other.ffi;
+    }
+}
+
+/// Adds to each package's own capabilities those of its normal
+/// dependencies, transitively. Build and dev dependencies do not run with
+/// the package, so they are not followed.
+pub fn aggregate(
+    own: &BTreeMap<String, CapabilitySet>,
+    tree: &BTreeMap<String, Vec<DependencyEdge>>,
+) -> BTreeMap<String, CapabilitySet> {
+    let mut all = BTreeMap::new();
+    for key in own.keys() {
+        collect(key, own, tree, &mut all);
+    }
+    all
+}
+
+fn collect(
+    key: &str,
+    own: &BTreeMap<String, CapabilitySet>,
+    tree: &BTreeMap<String, Vec<DependencyEdge>>,
+    all: &mut BTreeMap<String, CapabilitySet>,
+) -> CapabilitySet {
+    if let Some(&capabilities) = all.get(key) {
+        return capabilities;
+    }
+    // Normal dependencies cannot form a cycle, but guard against one
+    all.insert(key.to_string(), CapabilitySet::default());
+
+    let mut capabilities = own.get(key).copied().unwrap_or_default();
+    let normal = tree
+        .get(key)
+        .into_iter()
+        .flatten()
+        .filter(|edge| edge.kinds.contains(&DependencyKind::Normal));
+    for edge in normal {
+        capabilities |= collect(&edge.package, own, tree, all);
+    }
+    all.insert(key.to_string(), capabilities);
+    capabilities
+}
+
+/// Flags `package`, keyed `key`, when it does not run processes itself but
+/// a normal dependency, direct or not, does, with the shortest chain of
+/// dependencies that brings the capability in.
+pub fn transitive_process(
+    package: &Package,
+    key: &str,
+    own: &BTreeMap<String, CapabilitySet>,
+    tree: &BTreeMap<String, Vec<DependencyEdge>>,
+) -> Option<SecurityIssue> {
+    let spawns = |key: &str| {
+        own.get(key)
+            .is_some_and(|capabilities| capabilities.process)
+    };
+    if spawns(key) || macros::is_proc_macro(package) {
+        return None;
+    }
+
+    let mut parents: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([key]);
+    while let Some(current) = queue.pop_front() {
+        let normal = tree
+            .get(current)
+            .into_iter()
+            .flatten()
+            .filter(|edge| edge.kinds.contains(&DependencyKind::Normal));
+        for edge in normal {
+            let dep = edge.package.as_str();
+            if dep == key || parents.contains_key(dep) {
+                continue;
+            }
+            parents.insert(dep, current);
+            if !spawns(dep) {
+                queue.push_back(dep);
+                continue;
+            }
+
+            let mut chain = vec![dep];
+            while let Some(&parent) = chain.last().and_then(|last| parents.get(last)) {
+                chain.push(parent);
+            }
+            chain.reverse();
+            return Some(SecurityIssue {
+                severity: Severity::Low,
+                description: format!(
+                    "{} can spawn processes through its dependencies: {}",
+                    package.name,
+                    chain.join(" → ")
+                ),
+                affected_versions: vec![package.version.to_string()],
+                rule_id: Some("GUARD050".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use cargo_metadata::DependencyKind as CargoDependencyKind;
+
+    /// `app` -> `cli` -> `runner` -> `spawner`, where `spawner` runs
+    /// processes; `cli` also depends on `log`.
+    fn project() -> Project {
+        let mut project = Project::new();
+        project
+            .add("cli", "1.0.0")
+            .add("runner", "1.0.0")
+            .add("spawner", "1.0.0")
+            .add("log", "1.0.0")
+            .depend("app", "cli", CargoDependencyKind::Normal)
+            .depend("cli", "runner", CargoDependencyKind::Normal)
+            .depend("cli", "log", CargoDependencyKind::Normal)
+            .depend("runner", "spawner", CargoDependencyKind::Normal);
+        project.file(
+            "spawner",
+            "src/lib.rs",
+            "pub fn run() { std::process::Command::new(\"sh\").status().unwrap(); }\n",
+        );
+        project
+    }
+
+    #[tokio::test]
+    async fn reports_the_chain_bringing_in_process_spawning() {
+        let project = project();
+        let analysis = project.analyze(project.config()).await;
+
+        let transitive = |name: &str| {
+            let issues = analysis.security_issues.get(name)?;
+            issues
+                .iter()
+                .find(|issue| issue.rule_id.as_deref() == Some("GUARD050"))
+                .cloned()
+        };
+        let cli = transitive("cli").unwrap();
+        assert_eq!(cli.severity, Severity::Low);
+        assert_eq!(
+            cli.description,
+            "cli can spawn processes through its dependencies: \
+             cli@1.0.0 → runner@1.0.0 → spawner@1.0.0"
+        );
+        assert_eq!(
+            transitive("runner").unwrap().description,
+            "runner can spawn processes through its dependencies: \
+             runner@1.0.0 → spawner@1.0.0"
+        );
+        // Reported as its own finding, not a transitive one
+        assert!(transitive("spawner").is_none());
+        assert!(transitive("log").is_none());
+
+        assert!(analysis.capabilities["cli@1.0.0"].process);
+        assert!(analysis.capabilities["spawner@1.0.0"].process);
+        assert!(!analysis.capabilities["log@1.0.0"].process);
+    }
+
+    #[test]
+    fn build_dependencies_do_not_bring_capabilities_in() {
+        let edge = |package: &str, kind| DependencyEdge {
+            package: package.to_string(),
+            kinds: vec![kind],
+        };
+        let own = BTreeMap::from([
+            ("a".to_string(), CapabilitySet::default()),
+            (
+                "net".to_string(),
+                CapabilitySet {
+                    network: true,
+                    ..CapabilitySet::default()
+                },
+            ),
+            (
+                "build".to_string(),
+                CapabilitySet {
+                    process: true,
+                    ..CapabilitySet::default()
+                },
+            ),
+        ]);
+        let tree = BTreeMap::from([(
+            "a".to_string(),
+            vec![
+                edge("net", DependencyKind::Normal),
+                edge("build", DependencyKind::Build),
+            ],
+        )]);
+
+        let all = aggregate(&own, &tree);
+
+        assert_eq!(
+            all["a"],
+            CapabilitySet {
+                network: true,
+                ..CapabilitySet::default()
+            }
+        );
+    }
+}
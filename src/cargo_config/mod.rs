@@ -0,0 +1,346 @@
+use cargo_metadata::Package;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::models::{Location, SecurityIssue, Severity};
+use crate::scanner::{line_starts, locate};
+
+/// The cargo configuration files that apply to a build in `workspace_root`,
+/// from the closest: `.cargo/config.toml`, or the legacy `.cargo/config`,
+/// in the directory and each of its ancestors.
+pub fn files(workspace_root: &Path) -> Vec<PathBuf> {
+    workspace_root
+        .ancestors()
+        .flat_map(|dir| ["config.toml", "config"].map(|name| dir.join(".cargo").join(name)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// A parsed configuration file, kept with its text to locate findings.
+struct ConfigFile {
+    path: PathBuf,
+    content: String,
+    table: toml::Table,
+}
+
+impl ConfigFile {
+    fn read(path: PathBuf) -> Option<Self> {
+        let content = fs::read_to_string(&path)
+            .map_err(|err| debug!("Failed to read {}: {}", path.display(), err))
+            .ok()?;
+        let table = content
+            .parse()
+            .map_err(|err| debug!("Failed to parse {}: {}", path.display(), err))
+            .ok()?;
+        Some(Self {
+            path,
+            content,
+            table,
+        })
+    }
+
+    fn get(&self, keys: &[&str]) -> Option<&toml::Value> {
+        let (first, rest) = keys.split_first()?;
+        rest.iter()
+            .try_fold(self.table.get(*first)?, |value, key| value.get(key))
+    }
+
+    fn tables(&self, key: &str) -> impl Iterator<Item = (&String, &toml::Value)> {
+        self.table
+            .get(key)
+            .and_then(toml::Value::as_table)
+            .into_iter()
+            .flatten()
+    }
+
+    /// Where `text` first appears in the file.
+    fn locate(&self, text: &str) -> Option<Location> {
+        let offset = self.content.find(text)?;
+        Some(locate_in(&self.path, &self.content, offset))
+    }
+}
+
+/// Reports insecure registry settings of the cargo configuration in
+/// `workspace_root` and of the `members`' dependencies, keyed by the member
+/// they are reported on: registries and sources fetched over plain HTTP or
+/// `git://` (GUARD041), alternate registries without credentials
+/// (GUARD042), and `net.git-fetch-with-cli` (GUARD043). Configuration
+/// findings go to the first member, as they apply to the whole workspace.
+pub fn check(workspace_root: &Path, members: &[&Package]) -> HashMap<String, Vec<SecurityIssue>> {
+    let mut issues: HashMap<String, Vec<SecurityIssue>> = HashMap::new();
+
+    for member in members {
+        let manifest = member.manifest_path.as_std_path();
+        let content = fs::read_to_string(manifest).unwrap_or_default();
+        for dep in &member.dependencies {
+            let Some(url) = dep.registry.as_deref().filter(|url| is_insecure(url)) else {
+                continue;
+            };
+            let location = content
+                .find(url)
+                .map(|offset| locate_in(manifest, &content, offset));
+            let issue = insecure_url(format!("The registry of {}", dep.name), url, location);
+            issues.entry(member.name.clone()).or_default().push(issue);
+        }
+    }
+
+    let configs: Vec<ConfigFile> = files(workspace_root)
+        .into_iter()
+        .filter_map(ConfigFile::read)
+        .collect();
+    let Some(member) = members.first() else {
+        return issues;
+    };
+    let found = issues.entry(member.name.clone()).or_default();
+
+    let global_credentials = configs.iter().any(|config| {
+        config
+            .get(&["registry", "global-credential-providers"])
+            .is_some()
+    });
+    let authenticated: HashSet<&String> = configs
+        .iter()
+        .flat_map(|config| config.tables("registries"))
+        .filter(|(_, registry)| {
+            registry
+                .get("token")
+                .or(registry.get("credential-provider"))
+                .is_some()
+        })
+        .map(|(name, _)| name)
+        .collect();
+    let credentials = credentials();
+
+    for config in &configs {
+        for (name, registry) in config.tables("registries") {
+            let Some(index) = registry.get("index").and_then(toml::Value::as_str) else {
+                continue;
+            };
+            let location = config.locate(index);
+            if is_insecure(index) {
+                let key = format!("registries.{}.index", name);
+                found.push(insecure_url(key, index, location.clone()));
+            }
+            let has_credentials = global_credentials
+                || authenticated.contains(name)
+                || credentials.contains(&variable_name(name));
+            if !has_credentials {
+                found.push(SecurityIssue {
+                    severity: Severity::Medium,
+                    description: format!(
+                        "Alternate registry {} ({}) is used without credentials - no token or credential-provider is configured for it",
+                        name, index
+                    ),
+                    rule_id: Some("GUARD042".to_string()),
+                    location,
+                    ..Default::default()
+                });
+            }
+        }
+
+        for (name, source) in config.tables("source") {
+            for key in ["registry", "git"] {
+                let Some(url) = source.get(key).and_then(toml::Value::as_str) else {
+                    continue;
+                };
+                if is_insecure(url) {
+                    let key = format!("source.{}.{}", name, key);
+                    found.push(insecure_url(key, url, config.locate(url)));
+                }
+            }
+        }
+
+        if let Some(url) = config
+            .get(&["registry", "index"])
+            .and_then(toml::Value::as_str)
+            .filter(|url| is_insecure(url))
+        {
+            found.push(insecure_url("registry.index", url, config.locate(url)));
+        }
+
+        if let Some(true) = config
+            .get(&["net", "git-fetch-with-cli"])
+            .and_then(toml::Value::as_bool)
+        {
+            found.push(SecurityIssue {
+                severity: Severity::Low,
+                description: "net.git-fetch-with-cli is enabled - git dependencies and indexes are fetched by the git command line, under the system's git configuration".to_string(),
+                rule_id: Some("GUARD043".to_string()),
+                location: config.locate("git-fetch-with-cli"),
+                ..Default::default()
+            });
+        }
+    }
+
+    issues.retain(|_, issues| !issues.is_empty());
+    issues
+}
+
+fn insecure_url(key: impl Display, url: &str, location: Option<Location>) -> SecurityIssue {
+    SecurityIssue {
+        severity: Severity::High,
+        description: format!(
+            "{} is fetched without TLS from {} - it can be read and tampered with in transit",
+            key, url
+        ),
+        rule_id: Some("GUARD041".to_string()),
+        location,
+        ..Default::default()
+    }
+}
+
+/// Whether `url`, with or without a `sparse+` or `registry+` prefix, uses
+/// an unencrypted transport.
+fn is_insecure(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    let url = url
+        .split_once('+')
+        .filter(|(kind, _)| ["sparse", "registry", "git"].contains(kind))
+        .map_or(url.as_str(), |(_, url)| url);
+    url.starts_with("http://") || url.starts_with("git://")
+}
+
+/// The registries with a token outside the configuration: in cargo's
+/// credentials file or a `CARGO_REGISTRIES_<NAME>_TOKEN` variable, by
+/// their name in the variable's form.
+pub fn credentials() -> BTreeSet<String> {
+    let mut names: BTreeSet<String> = env::vars_os()
+        .filter_map(|(key, _)| {
+            let key = key.into_string().ok()?;
+            let name = key
+                .strip_prefix("CARGO_REGISTRIES_")?
+                .strip_suffix("_TOKEN")?;
+            Some(name.to_string())
+        })
+        .collect();
+
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+    let Some(cargo_home) = cargo_home else {
+        return names;
+    };
+    let stored = ["credentials.toml", "credentials"]
+        .into_iter()
+        .find_map(|name| fs::read_to_string(cargo_home.join(name)).ok())
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .unwrap_or_default();
+    let registries = stored.get("registries").and_then(toml::Value::as_table);
+    for (name, registry) in registries.into_iter().flatten() {
+        if registry.get("token").is_some() {
+            names.insert(variable_name(name));
+        }
+    }
+    names
+}
+
+/// A registry name as it appears in cargo's environment variables.
+fn variable_name(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+fn locate_in(path: &Path, content: &str, offset: usize) -> Location {
+    let line_starts = line_starts(content);
+    locate(path, content, &line_starts, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+
+    /// The rule, severity and line of each finding with the cargo
+    /// configuration `config` in the root of a project.
+    fn findings(config: &str) -> Vec<(String, Severity, usize)> {
+        let project = Project::new();
+        project.file("app", ".cargo/config.toml", config);
+        let app = project.package("app");
+        let mut issues = check(&project.root(), &[&app]);
+        let found = issues.remove("app").unwrap_or_default();
+        assert!(issues.is_empty());
+        found
+            .into_iter()
+            .map(|issue| {
+                let location = issue.location.expect("location");
+                assert!(location.file.ends_with(".cargo/config.toml"));
+                (
+                    issue.rule_id.unwrap_or_default(),
+                    issue.severity,
+                    location.line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_an_http_alternate_registry_without_credentials() {
+        let found = findings(
+            "[registries.guardian-test-internal]\n\
+             index = \"sparse+http://registry.internal/index/\"\n\
+             \n\
+             [net]\n\
+             git-fetch-with-cli = true\n",
+        );
+
+        assert_eq!(
+            found,
+            [
+                ("GUARD041".to_string(), Severity::High, 2),
+                ("GUARD042".to_string(), Severity::Medium, 2),
+                ("GUARD043".to_string(), Severity::Low, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_insecure_source_replacements() {
+        let found = findings(
+            "[source.crates-io]\n\
+             replace-with = \"mirror\"\n\
+             \n\
+             [source.mirror]\n\
+             registry = \"http://mirror.internal/index\"\n\
+             \n\
+             [source.vendored]\n\
+             git = \"git://example.com/vendored.git\"\n",
+        );
+
+        assert_eq!(
+            found,
+            [
+                ("GUARD041".to_string(), Severity::High, 5),
+                ("GUARD041".to_string(), Severity::High, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn secure_and_authenticated_registries_are_clean() {
+        assert!(findings(
+            "[registries.guardian-test-internal]\n\
+             index = \"sparse+https://registry.internal/index/\"\n\
+             token = \"secret\"\n\
+             \n\
+             [net]\n\
+             git-fetch-with-cli = false\n",
+        )
+        .is_empty());
+
+        let project = Project::new();
+        assert!(check(&project.root(), &[&project.package("app")]).is_empty());
+    }
+
+    #[test]
+    fn insecure_urls_are_recognized_behind_their_prefix() {
+        assert!(is_insecure("http://example.com"));
+        assert!(is_insecure("sparse+HTTP://example.com"));
+        assert!(is_insecure("registry+git://example.com/index"));
+        assert!(!is_insecure("https://example.com"));
+        assert!(!is_insecure("sparse+https://example.com"));
+        assert!(!is_insecure("ssh://git@example.com/index"));
+    }
+}
@@ -0,0 +1,290 @@
+use cargo_metadata::Package;
+use semver::VersionReq;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use super::Config;
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+
+/// The parts of a cargo-deny `deny.toml` that guardian honors; other keys
+/// are ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DenyToml {
+    advisories: Advisories,
+    bans: BansTable,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Advisories {
+    ignore: Vec<IgnoredAdvisory>,
+    yanked: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum IgnoredAdvisory {
+    Id(String),
+    Table { id: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct BansTable {
+    multiple_versions: Option<String>,
+    deny: Vec<Spec>,
+    skip: Vec<Spec>,
+}
+
+/// A crate as cargo-deny names it: `"name"`, `"name@requirement"`, or a
+/// table with `crate = "name@requirement"` or `name` and `version` keys.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Spec {
+    Spec(String),
+    Table {
+        #[serde(rename = "crate")]
+        spec: Option<String>,
+        name: Option<String>,
+        version: Option<String>,
+        reason: Option<String>,
+    },
+}
+
+/// Crates banned outright and crate versions left out of the duplicate
+/// versions check, from the `[bans]` table of `deny-toml`.
+#[derive(Debug, Clone, Default)]
+pub struct Bans {
+    pub deny: Vec<CrateSpec>,
+    pub skip: Vec<CrateSpec>,
+}
+
+/// A crate name, optionally restricted to matching versions.
+#[derive(Debug, Clone)]
+pub struct CrateSpec {
+    pub name: String,
+    pub version: Option<VersionReq>,
+    pub reason: Option<String>,
+}
+
+impl CrateSpec {
+    pub fn matches(&self, package: &Package) -> bool {
+        self.name == package.name
+            && self
+                .version
+                .as_ref()
+                .is_none_or(|req| req.matches(&package.version))
+    }
+}
+
+impl Bans {
+    /// Reports `package` as GUARD035 when a `deny` entry matches it.
+    pub fn check(&self, package: &Package) -> Option<SecurityIssue> {
+        let ban = self.deny.iter().find(|ban| ban.matches(package))?;
+        let mut description = format!("{} {} is banned", package.name, package.version);
+        if let Some(reason) = &ban.reason {
+            description.push_str(&format!(": {}", reason));
+        }
+        Some(SecurityIssue {
+            severity: Severity::High,
+            description,
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD035".to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Whether `package` is left out of the duplicate versions check.
+    pub fn skips(&self, package: &Package) -> bool {
+        self.skip.iter().any(|skip| skip.matches(package))
+    }
+}
+
+/// Applies the bans and advisory settings of the cargo-deny configuration
+/// at `path` to `config`. Settings in `config` win where both set the same
+/// thing.
+pub fn apply(config: &mut Config, path: &Path) -> Result<()> {
+    let invalid = || format!("Invalid cargo-deny configuration {}", path.display());
+    let content = fs::read_to_string(path).map_err(|err| GuardianError::io(path, err))?;
+    let deny: DenyToml =
+        toml::from_str(&content).map_err(|err| GuardianError::config(invalid(), err))?;
+
+    for ignored in deny.advisories.ignore {
+        let (IgnoredAdvisory::Id(id) | IgnoredAdvisory::Table { id }) = ignored;
+        config.ignore.push(id);
+    }
+    if deny.advisories.yanked.as_deref() == Some("allow") {
+        config.yanked = false;
+    }
+
+    match deny.bans.multiple_versions.as_deref() {
+        Some("allow") => config.disabled_rules.push("GUARD016".to_string()),
+        Some("deny") => {
+            config
+                .severity_overrides
+                .entry("GUARD016".to_string())
+                .or_insert(Severity::High);
+        }
+        _ => {}
+    }
+    for (specs, list) in [
+        (deny.bans.deny, &mut config.bans.deny),
+        (deny.bans.skip, &mut config.bans.skip),
+    ] {
+        for spec in specs {
+            list.push(spec.parse().map_err(|err| GuardianError::Config {
+                message: format!("{}: {}", invalid(), err),
+                source: None,
+            })?);
+        }
+    }
+    Ok(())
+}
+
+impl Spec {
+    fn parse(self) -> std::result::Result<CrateSpec, String> {
+        let (spec, name, version, reason) = match self {
+            Spec::Spec(spec) => (Some(spec), None, None, None),
+            Spec::Table {
+                spec,
+                name,
+                version,
+                reason,
+            } => (spec, name, version, reason),
+        };
+        let (name, version) = match (spec, name) {
+            (Some(spec), _) => match spec.split_once('@') {
+                Some((name, version)) => (name.to_string(), Some(version.to_string())),
+                None => (spec, version),
+            },
+            (None, Some(name)) => (name, version),
+            (None, None) => return Err("a bans entry names no crate".to_string()),
+        };
+        let version = match version.filter(|version| version != "*") {
+            Some(version) => Some(
+                VersionReq::parse(&version)
+                    .map_err(|err| format!("invalid version {} of {}: {}", version, name, err))?,
+            ),
+            None => None,
+        };
+        Ok(CrateSpec {
+            name,
+            version,
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use tempfile::TempDir;
+
+    const DENY_TOML: &str = r#"
+[graph]
+all-features = true
+
+[advisories]
+ignore = ["RUSTSEC-2024-0001", { id = "RUSTSEC-2024-0002", reason = "not reachable" }]
+yanked = "allow"
+
+[bans]
+multiple-versions = "deny"
+deny = [
+    "openssl",
+    { crate = "time@<0.2", reason = "unsound" },
+    { name = "chrono", version = "*" },
+]
+skip = ["syn@1"]
+"#;
+
+    /// Applies `content`, as a deny.toml, to the default configuration.
+    fn apply_str(content: &str) -> Result<Config> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("deny.toml");
+        fs::write(&path, content).unwrap();
+        let mut config = Config::default();
+        apply(&mut config, &path)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn applies_advisory_and_ban_settings() {
+        let config = apply_str(DENY_TOML).unwrap();
+
+        assert_eq!(config.ignore, ["RUSTSEC-2024-0001", "RUSTSEC-2024-0002"]);
+        assert!(!config.yanked);
+        assert_eq!(
+            config.severity_overrides.get("GUARD016"),
+            Some(&Severity::High)
+        );
+        assert!(!config.disabled_rules.contains(&"GUARD016".to_string()));
+        let denied: Vec<(&str, Option<String>)> = config
+            .bans
+            .deny
+            .iter()
+            .map(|spec| {
+                (
+                    spec.name.as_str(),
+                    spec.version.as_ref().map(ToString::to_string),
+                )
+            })
+            .collect();
+        assert_eq!(
+            denied,
+            [
+                ("openssl", None),
+                ("time", Some("<0.2".to_string())),
+                ("chrono", None),
+            ]
+        );
+        assert_eq!(config.bans.skip.len(), 1);
+        assert_eq!(config.bans.skip[0].name, "syn");
+    }
+
+    #[test]
+    fn multiple_versions_allow_disables_the_duplicates_check() {
+        let config = apply_str("[bans]\nmultiple-versions = \"allow\"\n").unwrap();
+
+        assert_eq!(config.disabled_rules, ["GUARD016"]);
+        assert!(config.severity_overrides.is_empty());
+    }
+
+    #[test]
+    fn bans_match_names_and_versions() {
+        let config = apply_str(DENY_TOML).unwrap();
+        let mut project = Project::new();
+        project
+            .add("openssl", "0.10.0")
+            .add("time", "0.1.45")
+            .add("syn", "1.0.109")
+            .add("serde", "1.0.0");
+
+        let issue = config.bans.check(&project.package("time")).unwrap();
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD035"));
+        assert_eq!(issue.description, "time 0.1.45 is banned: unsound");
+        assert!(config.bans.check(&project.package("openssl")).is_some());
+        assert!(config.bans.check(&project.package("serde")).is_none());
+        assert!(config.bans.skips(&project.package("syn")));
+        assert!(!config.bans.skips(&project.package("serde")));
+
+        let mut project = Project::new();
+        project.add("time", "0.3.0");
+        assert!(config.bans.check(&project.package("time")).is_none());
+    }
+
+    #[test]
+    fn invalid_bans_are_reported() {
+        let err = apply_str("[bans]\ndeny = [{ reason = \"no crate\" }]\n").unwrap_err();
+        assert!(err.to_string().contains("a bans entry names no crate"));
+
+        let err = apply_str("[bans]\ndeny = [\"time@not-a-version\"]\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid version not-a-version of time"));
+    }
+}
@@ -0,0 +1,841 @@
+use cargo_metadata::{Edition, MetadataCommand};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env::{self, VarError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{GuardianError, Result};
+use crate::models::{Confidence, SecurityIssue, Severity};
+use crate::retry::RetryPolicy;
+use crate::secrets::SecretProfile;
+
+mod deny;
+
+pub use deny::{Bans, CrateSpec};
+
+pub const CONFIG_FILE: &str = "guardian.toml";
+
+/// Environment variables read over the file and the manifest, for CI, and
+/// the setting each one stands for. `GUARDIAN_OFFLINE` and
+/// `GUARDIAN_NO_CACHE` take a boolean.
+const ENV_SETTINGS: &[(&str, &str)] = &[
+    ("GUARDIAN_OUTPUT", "output"),
+    ("GUARDIAN_FAIL_ON", "fail-on"),
+    ("GUARDIAN_MIN_SEVERITY", "min-severity"),
+    ("GUARDIAN_OFFLINE", "offline"),
+    ("GUARDIAN_NO_CACHE", "cache"),
+];
+
+const OUTPUT_FORMATS: &[&str] = &[
+    "text", "json", "ndjson", "toml", "html", "junit", "markdown", "gitlab",
+];
+
+#[derive(Clone, Copy, Debug, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    Critical,
+    High,
+    Medium,
+    Low,
+    None,
+}
+
+impl FailOn {
+    pub fn threshold(self) -> Option<Severity> {
+        match self {
+            FailOn::Critical => Some(Severity::Critical),
+            FailOn::High => Some(Severity::High),
+            FailOn::Medium => Some(Severity::Medium),
+            FailOn::Low => Some(Severity::Low),
+            FailOn::None => None,
+        }
+    }
+}
+
+/// A user-defined source pattern.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternConfig {
+    pub id: String,
+    pub regex: String,
+    pub description: String,
+    pub severity: Severity,
+    /// `medium` when unset
+    pub confidence: Option<Confidence>,
+}
+
+/// A RustSec advisory accepted as a known risk, reported apart from the
+/// other findings until it expires.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptedAdvisory {
+    pub id: String,
+    /// Date (`YYYY-MM-DD`) from which the advisory is reported again
+    pub expires: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// How far findings on a crate listed in `[dependency-severity]` are
+/// raised: by a number of severity levels, or to at least a severity.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "EscalationValue")]
+pub enum Escalation {
+    Levels(u8),
+    Minimum(Severity),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EscalationValue {
+    Levels(u8),
+    Severity(String),
+}
+
+impl TryFrom<EscalationValue> for Escalation {
+    type Error = GuardianError;
+
+    fn try_from(value: EscalationValue) -> std::result::Result<Self, Self::Error> {
+        match value {
+            EscalationValue::Levels(levels) => Ok(Escalation::Levels(levels)),
+            EscalationValue::Severity(severity) => Ok(Escalation::Minimum(severity.parse()?)),
+        }
+    }
+}
+
+impl Escalation {
+    /// `severity` once escalated, capped at Critical.
+    pub fn apply(&self, severity: &Severity) -> Severity {
+        const LEVELS: [Severity; 5] = [
+            Severity::Info,
+            Severity::Low,
+            Severity::Medium,
+            Severity::High,
+            Severity::Critical,
+        ];
+        match self {
+            Escalation::Levels(levels) => {
+                let rank = (severity.rank() as usize + *levels as usize).min(LEVELS.len() - 1);
+                LEVELS[rank].clone()
+            }
+            Escalation::Minimum(minimum) => severity.clone().max(minimum.clone()),
+        }
+    }
+}
+
+/// A standalone pattern file, in TOML (`[[patterns]]` tables) or JSON
+/// (`{"patterns": [...]}`) form.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PatternFile {
+    patterns: Vec<PatternConfig>,
+}
+
+/// Loads and validates user-defined patterns from `path`, reporting the
+/// line of any entry whose regex does not compile.
+pub fn load_patterns(path: &Path) -> Result<Vec<PatternConfig>> {
+    let content = fs::read_to_string(path).map_err(|err| GuardianError::io(path, err))?;
+
+    let invalid = || format!("Invalid pattern file {}", path.display());
+    let file: PatternFile = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&content).map_err(|err| GuardianError::config(invalid(), err))?
+    } else {
+        toml::from_str(&content).map_err(|err| GuardianError::config(invalid(), err))?
+    };
+
+    for pattern in &file.patterns {
+        if let Err(err) = Regex::new(&pattern.regex) {
+            let line = [format!("\"{}\"", pattern.id), format!("'{}'", pattern.id)]
+                .iter()
+                .find_map(|quoted| content.find(quoted.as_str()))
+                .map_or(0, |offset| content[..offset].lines().count().max(1));
+            return Err(GuardianError::config(
+                format!(
+                    "{}:{}: invalid regex for pattern {}",
+                    path.display(),
+                    line,
+                    pattern.id
+                ),
+                err,
+            ));
+        }
+    }
+
+    Ok(file.patterns)
+}
+
+/// License policy from the `[license]` table. Entries are SPDX identifiers
+/// and may use glob wildcards, e.g. `GPL-*`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LicensePolicy {
+    /// When non-empty, only these licenses are accepted
+    pub allow: Vec<String>,
+    /// Licenses that are always rejected
+    pub deny: Vec<String>,
+    /// Report linked dependencies whose license conflicts with the
+    /// project's
+    pub compatibility: bool,
+    /// The license the project is distributed under, instead of the one
+    /// its manifest declares
+    pub project: Option<String>,
+}
+
+impl LicensePolicy {
+    pub fn is_configured(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+}
+
+/// Points each issue adds to the risk score, by severity, from the
+/// `[risk-weights]` table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskWeights {
+    pub critical: u32,
+    pub high: u32,
+    pub medium: u32,
+    pub low: u32,
+    pub info: u32,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            critical: 10,
+            high: 5,
+            medium: 2,
+            low: 1,
+            info: 0,
+        }
+    }
+}
+
+impl RiskWeights {
+    pub fn weight(&self, severity: &Severity) -> u32 {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::High => self.high,
+            Severity::Medium => self.medium,
+            Severity::Low => self.low,
+            Severity::Info => self.info,
+        }
+    }
+}
+
+/// Settings from `guardian.toml`, with command line flags merged on top.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Output format (text, json, ndjson, toml, html, junit, markdown, gitlab)
+    pub output: Option<String>,
+    /// Size limit in bytes of the markdown report, which drops package
+    /// sections beyond it
+    pub markdown_max_size: usize,
+    /// Print only the totals and the `fail-on` result (text and json)
+    pub summary: bool,
+    pub fail_on: Option<FailOn>,
+    /// `fail-on` threshold for informational advisories, such as
+    /// unmaintained crates; `fail-on` applies when unset
+    pub fail_on_informational: Option<FailOn>,
+    /// Issues below this severity are left out of the report
+    pub min_severity: Option<Severity>,
+    /// Issues of rules less reliable than this are dropped, before
+    /// `fail-on` and the risk score
+    pub min_confidence: Option<Confidence>,
+    pub advisory_db: Option<PathBuf>,
+    /// Seconds for which an updated advisory database is not fetched again
+    pub advisory_db_ttl: u64,
+    /// Directory of check plugins (dynamic libraries) to load
+    pub plugin_dir: Option<PathBuf>,
+    pub offline: bool,
+    /// Retries for failed network requests
+    pub retries: u32,
+    /// Seconds before a single network request is abandoned
+    pub timeout: u64,
+    /// Leave dev-dependencies out of the analysis
+    pub skip_dev: bool,
+    /// Leave build-dependencies out of the analysis
+    pub skip_build: bool,
+    /// Leave out dependencies deeper than this, direct ones being at depth 0
+    pub max_depth: Option<usize>,
+    /// Skip findings in code that the resolved features leave out
+    pub active_features_only: bool,
+    /// Check packages against the RustSec advisory database
+    pub advisories: bool,
+    /// Check resolved versions for yanked releases
+    pub yanked: bool,
+    /// Query crates.io for crates that are no longer released
+    pub maintenance: bool,
+    /// Months without a release after which a crate is reported
+    pub max_age_months: u64,
+    /// Report dependencies behind their newest release on crates.io
+    pub outdated: bool,
+    /// Reuse the previous analysis while Cargo.lock and settings are unchanged
+    pub cache: bool,
+    /// Extra suppression rules, in `.guardianignore` syntax
+    pub ignore: Vec<String>,
+    /// Rule IDs whose findings are never reported
+    pub disabled_rules: Vec<String>,
+    /// Advisories listed apart from the other findings, not failing the run
+    pub accepted_advisories: Vec<AcceptedAdvisory>,
+    /// Severity of every finding of a rule or advisory ID, replacing the
+    /// built-in one before filtering, scoring and `fail-on`
+    pub severity_overrides: BTreeMap<String, Severity>,
+    /// Escalation of the findings on crates whose name matches a glob key,
+    /// applied after `severity-overrides`
+    pub dependency_severity: BTreeMap<String, Escalation>,
+    pub patterns: Vec<PatternConfig>,
+    pub license: LicensePolicy,
+    /// Git repository URLs (glob patterns) that dependencies may come from
+    pub allow_git: Vec<String>,
+    /// Report dependencies named like a misspelled popular crate
+    pub typosquat: bool,
+    /// Crate names never reported as typosquats
+    pub allow_similar: Vec<String>,
+    /// Names (glob patterns) of crates published to a private registry,
+    /// which must not resolve from crates.io
+    pub internal_crates: Vec<String>,
+    /// Index URL of the private registry internal crates must come from
+    pub internal_registry: Option<String>,
+    /// Dependencies on an older edition are reported
+    pub min_edition: Option<Edition>,
+    /// Feature names that raise the default features check to Medium
+    pub sensitive_features: Vec<String>,
+    pub risk_weights: RiskWeights,
+    /// Report pattern matches inside comments and string literals too
+    pub include_comments: bool,
+    /// Names of directories source scans do not enter, besides `target`
+    /// and `.git`
+    pub exclude_dirs: Vec<String>,
+    /// Scan `target` directories too; command line only
+    #[serde(skip)]
+    pub include_target: bool,
+    /// Scan sources for hard-coded secrets
+    pub secrets: bool,
+    /// Minimum length, charset and entropy of each secret rule, keyed by
+    /// rule ID, over the built-in ones
+    pub secret_rules: BTreeMap<String, SecretProfile>,
+    /// Collapse repeated findings of a rule within a package into one
+    pub group_by_rule: bool,
+    /// Report findings of different rules on the same line as one issue
+    pub merge: bool,
+    /// `cargo vendor` directory to scan the sources of registry and git
+    /// packages from
+    pub vendor_dir: Option<PathBuf>,
+    /// cargo-deny configuration whose bans and ignored advisories apply too
+    pub deny_toml: Option<PathBuf>,
+    /// Banned crates and duplicate versions check skips, from `deny-toml`
+    #[serde(skip)]
+    pub bans: Bans,
+    /// JSON Lines file a record of each run is appended to
+    pub audit_log: Option<PathBuf>,
+    /// Rewrite absolute paths in findings so they do not reveal user names
+    pub redact_paths: bool,
+    /// File the report is written to instead of stdout; command line only
+    #[serde(skip)]
+    pub output_file: Option<PathBuf>,
+    /// Features passed to `cargo metadata`; command line only
+    #[serde(skip)]
+    pub features: Vec<String>,
+    #[serde(skip)]
+    pub all_features: bool,
+    #[serde(skip)]
+    pub no_default_features: bool,
+    /// Record how long each package and stage of the scan takes; command
+    /// line only
+    #[serde(skip)]
+    pub profile: bool,
+    /// Saved `cargo metadata` output to analyze instead of running cargo;
+    /// command line only
+    #[serde(skip)]
+    pub metadata_json: Option<PathBuf>,
+    /// Workspace member to restrict analysis to; command line only
+    #[serde(skip)]
+    pub package: Option<String>,
+    /// Git ref whose `Cargo.lock` decides which dependencies are unchanged
+    /// and not scanned again; command line only
+    #[serde(skip)]
+    pub since: Option<String>,
+    /// How long the whole scan may take before the remaining packages are
+    /// left unscanned; command line only
+    #[serde(skip)]
+    pub scan_timeout: Option<Duration>,
+    /// Only check for duplicate crate versions; command line only
+    #[serde(skip)]
+    pub duplicates_only: bool,
+    /// Re-running on file changes: cached analyses are not reused, cached
+    /// file scans are; command line only
+    #[serde(skip)]
+    pub watch: bool,
+    /// No progress bar or informational output; command line only
+    #[serde(skip)]
+    pub quiet: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            output: None,
+            markdown_max_size: 65_000,
+            summary: false,
+            fail_on: None,
+            fail_on_informational: None,
+            min_severity: None,
+            min_confidence: None,
+            advisory_db: None,
+            advisory_db_ttl: 3600,
+            plugin_dir: None,
+            offline: false,
+            retries: 3,
+            timeout: 60,
+            skip_dev: false,
+            skip_build: false,
+            max_depth: None,
+            active_features_only: false,
+            advisories: true,
+            yanked: true,
+            maintenance: false,
+            max_age_months: 24,
+            outdated: false,
+            cache: true,
+            ignore: Vec::new(),
+            disabled_rules: Vec::new(),
+            accepted_advisories: Vec::new(),
+            severity_overrides: BTreeMap::new(),
+            dependency_severity: BTreeMap::new(),
+            patterns: Vec::new(),
+            license: LicensePolicy::default(),
+            allow_git: Vec::new(),
+            typosquat: true,
+            allow_similar: Vec::new(),
+            internal_crates: Vec::new(),
+            internal_registry: None,
+            min_edition: None,
+            sensitive_features: Vec::new(),
+            risk_weights: RiskWeights::default(),
+            include_comments: false,
+            exclude_dirs: Vec::new(),
+            include_target: false,
+            secrets: false,
+            secret_rules: BTreeMap::new(),
+            group_by_rule: false,
+            merge: true,
+            vendor_dir: None,
+            deny_toml: None,
+            bans: Bans::default(),
+            audit_log: None,
+            redact_paths: false,
+            output_file: None,
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            profile: false,
+            metadata_json: None,
+            package: None,
+            since: None,
+            scan_timeout: None,
+            duplicates_only: false,
+            watch: false,
+            quiet: false,
+        }
+    }
+}
+
+/// Settings from one source, named for error messages.
+type Layer = (String, toml::Table);
+
+impl Config {
+    /// Loads `path`, or `guardian.toml` next to the manifest when no path is
+    /// given, with the `GUARDIAN_*` environment variables over it. A missing
+    /// default file yields the default configuration.
+    pub fn load(path: Option<&Path>, manifest_path: &Path) -> Result<Self> {
+        let mut layers: Vec<Layer> = file_layer(path, manifest_path)?.into_iter().collect();
+        layers.extend(env_layers()?);
+        from_layers(layers)
+    }
+
+    /// Like [`load`](Self::load), with the settings in the manifest merged
+    /// over the file: first `[workspace.metadata.guardian]`, then the
+    /// `[package.metadata.guardian]` of `package`, or of the manifest's own
+    /// package, and the environment variables last. Each key replaces the
+    /// same key of the layers below it; tables such as `license` are merged
+    /// key by key.
+    pub fn load_with_manifest(
+        path: Option<&Path>,
+        manifest_path: &Path,
+        package: Option<&str>,
+    ) -> Result<Self> {
+        let mut layers: Vec<Layer> = file_layer(path, manifest_path)?.into_iter().collect();
+        // A missing manifest is reported by the analysis
+        if manifest_path.is_file() {
+            layers.extend(manifest_layers(manifest_path, package)?);
+        }
+        layers.extend(env_layers()?);
+        from_layers(layers)
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries,
+            timeout: Duration::from_secs(self.timeout),
+        }
+    }
+
+    pub fn is_rule_enabled(&self, id: &str) -> bool {
+        !self.disabled_rules.iter().any(|rule| rule == id)
+    }
+
+    /// The `fail-on` threshold that applies to `issue`, if any.
+    pub fn fail_threshold(&self, issue: &SecurityIssue) -> Option<Severity> {
+        let fail_on = match issue.informational {
+            Some(_) => self.fail_on_informational.or(self.fail_on),
+            None => self.fail_on,
+        };
+        fail_on.and_then(FailOn::threshold)
+    }
+
+    /// Applies the `deny-toml` cargo-deny configuration, if any, over the
+    /// settings loaded so far.
+    pub(crate) fn apply_deny_toml(&mut self) -> Result<()> {
+        match self.deny_toml.clone() {
+            Some(path) => deny::apply(self, &path),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies `severity-overrides` to `issue`, by rule or advisory ID.
+    pub fn override_severity(&self, issue: &mut SecurityIssue) {
+        let severity = [&issue.rule_id, &issue.advisory_id]
+            .into_iter()
+            .flatten()
+            .find_map(|id| self.severity_overrides.get(id));
+        if let Some(severity) = severity {
+            issue.severity = severity.clone();
+        }
+    }
+}
+
+fn file_layer(path: Option<&Path>, manifest_path: &Path) -> Result<Option<Layer>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let default = manifest_path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(CONFIG_FILE);
+            if !default.exists() {
+                return Ok(None);
+            }
+            default
+        }
+    };
+
+    let content = fs::read_to_string(&path).map_err(|err| GuardianError::io(&path, err))?;
+    let source = path.display().to_string();
+    let table = toml::from_str(&content)
+        .map_err(|err| GuardianError::config(format!("Invalid {}", source), err))?;
+    Ok(Some((source, table)))
+}
+
+/// The `guardian` tables of the workspace and package metadata, read with
+/// `cargo metadata --no-deps`.
+fn manifest_layers(manifest_path: &Path, package: Option<&str>) -> Result<Vec<Layer>> {
+    let metadata = MetadataCommand::new()
+        .manifest_path(manifest_path)
+        .no_deps()
+        .exec()?;
+    let mut layers = Vec::new();
+
+    if let Some(settings) = metadata.workspace_metadata.get("guardian") {
+        let source = "[workspace.metadata.guardian]".to_string();
+        layers.push((source.clone(), metadata_table(settings, &source)?));
+    }
+
+    let manifest_path = manifest_path.canonicalize().ok();
+    let package = metadata.packages.iter().find(|candidate| match package {
+        Some(name) => candidate.name == name,
+        None => Some(candidate.manifest_path.as_std_path()) == manifest_path.as_deref(),
+    });
+    if let Some(package) = package {
+        if let Some(settings) = package.metadata.get("guardian") {
+            let source = format!("[package.metadata.guardian] of {}", package.name);
+            layers.push((source.clone(), metadata_table(settings, &source)?));
+        }
+    }
+    Ok(layers)
+}
+
+fn metadata_table(settings: &serde_json::Value, source: &str) -> Result<toml::Table> {
+    toml::Table::try_from(settings)
+        .map_err(|err| GuardianError::config(format!("Invalid {}", source), err))
+}
+
+/// A layer for each `ENV_SETTINGS` variable that is set and not empty.
+fn env_layers() -> Result<Vec<Layer>> {
+    let mut layers = Vec::new();
+    for &(name, key) in ENV_SETTINGS {
+        let value = match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => value.trim().to_ascii_lowercase(),
+            Ok(_) | Err(VarError::NotPresent) => continue,
+            Err(err) => return Err(GuardianError::config(format!("Invalid {}", name), err)),
+        };
+        let invalid = |expected: &str| GuardianError::Config {
+            message: format!("Invalid {}: {}, expected {}", name, value, expected),
+            source: None,
+        };
+        let value = match key {
+            "offline" | "cache" => {
+                let flag = match value.as_str() {
+                    "1" | "true" | "yes" | "on" => true,
+                    "0" | "false" | "no" | "off" => false,
+                    _ => return Err(invalid("true or false")),
+                };
+                // GUARDIAN_NO_CACHE turns the setting off
+                toml::Value::Boolean(flag != (key == "cache"))
+            }
+            "output" if !OUTPUT_FORMATS.contains(&value.as_str()) => {
+                return Err(invalid(&OUTPUT_FORMATS.join(", ")));
+            }
+            _ => toml::Value::String(value),
+        };
+        layers.push((
+            name.to_string(),
+            toml::Table::from_iter([(key.to_string(), value)]),
+        ));
+    }
+    Ok(layers)
+}
+
+/// Merges `layers`, later ones taking precedence, after checking each on
+/// its own so that errors name the source they come from.
+fn from_layers(layers: Vec<Layer>) -> Result<Config> {
+    let mut merged = toml::Table::new();
+    for (source, table) in layers {
+        toml::Value::Table(table.clone())
+            .try_into::<Config>()
+            .map_err(|err| GuardianError::config(format!("Invalid {}", source), err))?;
+        debug!("Using settings from {}", source);
+        merge(&mut merged, table);
+    }
+    toml::Value::Table(merged)
+        .try_into()
+        .map_err(|err| GuardianError::config("Invalid configuration", err))
+}
+
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Held by the tests that read the `GUARDIAN_*` variables, which are
+    /// shared by the whole test process.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Loads `file` as the configuration file with the variables `vars`
+    /// set, and every other `GUARDIAN_*` variable unset.
+    fn load_with_env(file: &str, vars: &[(&str, &str)]) -> Result<Config> {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, file).unwrap();
+        for (name, _) in ENV_SETTINGS {
+            env::remove_var(name);
+        }
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+        let config = Config::load(Some(&path), &dir.path().join("Cargo.toml"));
+        for (name, _) in vars {
+            env::remove_var(name);
+        }
+        config
+    }
+
+    #[test]
+    fn invalid_pattern_regexes_report_their_line() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("patterns.toml");
+        fs::write(
+            &path,
+            "[[patterns]]\n\
+             id = \"ORG001\"\n\
+             regex = 'legacy_crypto::'\n\
+             description = \"Deprecated internal crypto\"\n\
+             severity = \"high\"\n\
+             \n\
+             [[patterns]]\n\
+             id = \"ORG002\"\n\
+             regex = 'legacy_hash('\n\
+             description = \"Deprecated internal hash\"\n\
+             severity = \"high\"\n",
+        )
+        .unwrap();
+
+        let err = load_patterns(&path).unwrap_err().to_string();
+
+        assert!(
+            err.ends_with(":8: invalid regex for pattern ORG002"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn manifest_settings_take_precedence_over_the_file() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\n\
+             name = \"app\"\n\
+             version = \"0.1.0\"\n\
+             \n\
+             [package.metadata.guardian]\n\
+             disabled-rules = [\"GUARD001\"]\n\
+             license = { allow = [\"MIT\"] }\n\
+             \n\
+             [workspace]\n\
+             \n\
+             [workspace.metadata.guardian]\n\
+             fail-on = \"medium\"\n\
+             disabled-rules = [\"GUARD002\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "fail-on = \"high\"\n\
+             disabled-rules = [\"GUARD006\"]\n\
+             \n\
+             [license]\n\
+             deny = [\"GPL-3.0\"]\n",
+        )
+        .unwrap();
+
+        let config = {
+            let _lock = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+            Config::load_with_manifest(None, &dir.path().join("Cargo.toml"), None).unwrap()
+        };
+
+        // The package's list replaces the workspace's, which replaced the file's
+        assert!(!config.is_rule_enabled("GUARD001"));
+        assert!(config.is_rule_enabled("GUARD002"));
+        assert!(config.is_rule_enabled("GUARD006"));
+        assert!(matches!(config.fail_on, Some(FailOn::Medium)));
+        // Tables are merged key by key
+        assert_eq!(config.license.allow, ["MIT"]);
+        assert_eq!(config.license.deny, ["GPL-3.0"]);
+    }
+
+    #[test]
+    fn escalation_raises_by_levels_capped_at_critical() {
+        let two = Escalation::Levels(2);
+        assert_eq!(two.apply(&Severity::Info), Severity::Medium);
+        assert_eq!(two.apply(&Severity::Medium), Severity::Critical);
+        assert_eq!(two.apply(&Severity::High), Severity::Critical);
+        assert_eq!(Escalation::Levels(0).apply(&Severity::Low), Severity::Low);
+        assert_eq!(
+            Escalation::Levels(u8::MAX).apply(&Severity::Info),
+            Severity::Critical
+        );
+    }
+
+    #[test]
+    fn escalation_to_a_minimum_never_lowers() {
+        let high = Escalation::Minimum(Severity::High);
+        assert_eq!(high.apply(&Severity::Low), Severity::High);
+        assert_eq!(high.apply(&Severity::High), Severity::High);
+        assert_eq!(high.apply(&Severity::Critical), Severity::Critical);
+    }
+
+    #[test]
+    fn escalations_parse_from_levels_or_severities() {
+        let escalations: BTreeMap<String, Escalation> =
+            toml::from_str("ring = 1\n\"openssl*\" = \"high\"\n").unwrap();
+
+        assert!(matches!(escalations["ring"], Escalation::Levels(1)));
+        assert!(matches!(
+            escalations["openssl*"],
+            Escalation::Minimum(Severity::High)
+        ));
+        assert!(toml::from_str::<BTreeMap<String, Escalation>>("ring = \"severe\"\n").is_err());
+    }
+
+    #[test]
+    fn environment_variables_take_precedence_over_the_file() {
+        let file = "output = \"html\"\nfail-on = \"low\"\nmin-severity = \"low\"\n";
+
+        let config = load_with_env(
+            file,
+            &[
+                ("GUARDIAN_OUTPUT", " JSON "),
+                ("GUARDIAN_FAIL_ON", "critical"),
+                ("GUARDIAN_MIN_SEVERITY", ""),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.output.as_deref(), Some("json"));
+        assert!(matches!(config.fail_on, Some(FailOn::Critical)));
+        // Empty variables are ignored
+        assert_eq!(config.min_severity, Some(Severity::Low));
+    }
+
+    #[test]
+    fn no_cache_inverts_the_cache_setting() {
+        let cache = |value: &str| {
+            load_with_env("", &[("GUARDIAN_NO_CACHE", value)])
+                .unwrap()
+                .cache
+        };
+
+        assert!(!cache("1"));
+        assert!(!cache("true"));
+        assert!(cache("off"));
+        assert!(!load_with_env("cache = false\n", &[]).unwrap().cache);
+        assert!(
+            load_with_env("cache = false\n", &[("GUARDIAN_NO_CACHE", "no")])
+                .unwrap()
+                .cache
+        );
+
+        let offline = load_with_env("", &[("GUARDIAN_OFFLINE", "Yes")]).unwrap();
+        assert!(offline.offline);
+        assert!(offline.cache);
+    }
+
+    #[test]
+    fn invalid_environment_values_name_the_variable() {
+        let err = load_with_env("", &[("GUARDIAN_NO_CACHE", "maybe")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid GUARDIAN_NO_CACHE: maybe, expected true or false"));
+
+        let err = load_with_env("", &[("GUARDIAN_OUTPUT", "pdf")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Invalid GUARDIAN_OUTPUT: pdf, expected text, json"));
+
+        let err = load_with_env("", &[("GUARDIAN_FAIL_ON", "severe")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid GUARDIAN_FAIL_ON"), "{}", err);
+    }
+}
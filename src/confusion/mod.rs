@@ -0,0 +1,162 @@
+use cargo_metadata::Package;
+use glob::Pattern;
+
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+
+/// Flags dependencies named like an internal crate that resolve from
+/// crates.io, or from another registry than the private one, where a
+/// public crate of the same name may have been substituted.
+pub struct ConfusionChecker {
+    internal: Vec<Pattern>,
+    registry: Option<String>,
+}
+
+impl ConfusionChecker {
+    /// `internal` lists internal crate names, which may use glob wildcards
+    /// such as `acme-*`; `registry` is the index URL of the private
+    /// registry, if internal crates must come from that one.
+    pub fn new(internal: &[String], registry: Option<&str>) -> Result<Self> {
+        let internal = internal
+            .iter()
+            .map(|name| {
+                Pattern::new(&normalize(name)).map_err(|err| {
+                    GuardianError::config(format!("Invalid internal-crates pattern {}", name), err)
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            internal,
+            registry: registry.map(|url| registry_url(url).to_string()),
+        })
+    }
+
+    pub fn check(&self, package: &Package) -> Option<SecurityIssue> {
+        // Path and git dependencies are not fetched by name
+        let source = package.source.as_ref()?;
+        if !source.repr.starts_with("registry+") && !source.repr.starts_with("sparse+") {
+            return None;
+        }
+        let name = normalize(&package.name);
+        if !self.internal.iter().any(|pattern| pattern.matches(&name)) {
+            return None;
+        }
+
+        let description = if source.is_crates_io() {
+            format!(
+                "{} is an internal crate name but resolves from crates.io - a public crate may have been substituted for the private one",
+                package.name
+            )
+        } else {
+            let url = registry_url(&source.repr);
+            match &self.registry {
+                Some(registry) if url != registry => format!(
+                    "{} is an internal crate name but resolves from {}, not the private registry {}",
+                    package.name, url, registry
+                ),
+                _ => return None,
+            }
+        };
+
+        Some(SecurityIssue {
+            severity: Severity::High,
+            description,
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD045".to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// Crate names are compared case-insensitively, with `-` and `_` treated
+/// alike, as crates.io does.
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
+}
+
+/// The index URL of a registry source or setting, without the source kind
+/// and trailing slash.
+fn registry_url(url: &str) -> &str {
+    let url = url.strip_prefix("registry+").unwrap_or(url);
+    let url = url.strip_prefix("sparse+").unwrap_or(url);
+    url.trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+
+    const PRIVATE: &str = "sparse+https://crates.acme.internal/index/";
+
+    /// The package `name` resolved from `source`.
+    fn package(name: &str, source: Option<&str>) -> Package {
+        let mut project = Project::new();
+        project.add_from(name, "1.0.0", source);
+        project.package(name)
+    }
+
+    #[test]
+    fn internal_names_resolving_from_crates_io_are_reported() {
+        let checker = ConfusionChecker::new(&["acme-*".to_string()], None).unwrap();
+        let public = package(
+            "acme_auth",
+            Some("registry+https://github.com/rust-lang/crates.io-index"),
+        );
+
+        let issue = checker.check(&public).unwrap();
+        assert_eq!(issue.severity, Severity::High);
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD045"));
+        assert!(issue
+            .description
+            .starts_with("acme_auth is an internal crate name"));
+
+        // The same name from the private registry, or as a path dependency
+        assert!(checker
+            .check(&package("acme_auth", Some(PRIVATE)))
+            .is_none());
+        assert!(checker.check(&package("acme_auth", None)).is_none());
+        // Public crates are not internal
+        let serde = package(
+            "serde",
+            Some("registry+https://github.com/rust-lang/crates.io-index"),
+        );
+        assert!(checker.check(&serde).is_none());
+    }
+
+    #[test]
+    fn internal_names_must_come_from_the_internal_registry_when_set() {
+        let checker = ConfusionChecker::new(
+            &["Acme-Auth".to_string()],
+            Some("https://crates.acme.internal/index"),
+        )
+        .unwrap();
+
+        assert!(checker
+            .check(&package("acme-auth", Some(PRIVATE)))
+            .is_none());
+        let issue = checker
+            .check(&package(
+                "acme-auth",
+                Some("sparse+https://mirror.example.com/index/"),
+            ))
+            .unwrap();
+        assert_eq!(
+            issue.description,
+            "acme-auth is an internal crate name but resolves from \
+             https://mirror.example.com/index, not the private registry \
+             https://crates.acme.internal/index"
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        let err = ConfusionChecker::new(&["acme-[".to_string()], None)
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("Invalid internal-crates pattern acme-["));
+    }
+}
@@ -0,0 +1,112 @@
+use cargo_metadata::{Edition, Package};
+
+use crate::models::{SecurityIssue, Severity};
+
+/// Flags a dependency still on the 2015 edition or without a declared
+/// `rust-version`, both common in unmaintained crates, and one below the
+/// configured `min_edition`.
+pub fn check(package: &Package, min_edition: Option<Edition>) -> Vec<SecurityIssue> {
+    let mut issues = Vec::new();
+    let affected_versions = vec![package.version.to_string()];
+
+    match min_edition {
+        Some(min) if package.edition < min => issues.push(SecurityIssue {
+            severity: Severity::Medium,
+            description: format!(
+                "{} uses the {} edition, below the required {} edition",
+                package.name, package.edition, min
+            ),
+            affected_versions: affected_versions.clone(),
+            rule_id: Some("GUARD029".to_string()),
+            ..Default::default()
+        }),
+        _ if package.edition == Edition::E2015 => issues.push(SecurityIssue {
+            severity: Severity::Info,
+            description: format!(
+                "{} still uses the 2015 edition - it may be unmaintained",
+                package.name
+            ),
+            affected_versions: affected_versions.clone(),
+            rule_id: Some("GUARD027".to_string()),
+            ..Default::default()
+        }),
+        _ => {}
+    }
+
+    if package.rust_version.is_none() {
+        issues.push(SecurityIssue {
+            severity: Severity::Info,
+            description: format!("{} does not declare a rust-version (MSRV)", package.name),
+            affected_versions,
+            rule_id: Some("GUARD028".to_string()),
+            ..Default::default()
+        });
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use serde_json::json;
+
+    /// `old` on 2015 without a rust-version, `mid` on 2018 and `new` on
+    /// 2021, both with one.
+    fn project() -> Project {
+        let mut project = Project::new();
+        project
+            .add("old", "0.1.0")
+            .set("old", "edition", json!("2015"))
+            .add("mid", "1.0.0")
+            .set("mid", "edition", json!("2018"))
+            .set("mid", "rust_version", json!("1.31"))
+            .add("new", "2.0.0")
+            .set("new", "rust_version", json!("1.70"));
+        project
+    }
+
+    /// The rules reported for each package.
+    fn rules(project: &Project, min_edition: Option<Edition>) -> Vec<(&'static str, Vec<String>)> {
+        ["old", "mid", "new"]
+            .into_iter()
+            .map(|name| {
+                let issues = check(&project.package(name), min_edition);
+                let rules = issues
+                    .into_iter()
+                    .filter_map(|issue| issue.rule_id)
+                    .collect();
+                (name, rules)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flags_the_2015_edition_and_a_missing_rust_version() {
+        let project = project();
+
+        assert_eq!(
+            rules(&project, None),
+            [
+                ("old", vec!["GUARD027".to_string(), "GUARD028".to_string()]),
+                ("mid", vec![]),
+                ("new", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_editions_below_the_minimum() {
+        let project = project();
+
+        assert_eq!(
+            rules(&project, Some(Edition::E2021)),
+            [
+                ("old", vec!["GUARD029".to_string(), "GUARD028".to_string()]),
+                ("mid", vec!["GUARD029".to_string()]),
+                ("new", vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Boxed source of an error raised by a dependency or an internal helper.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T, E = GuardianError> = std::result::Result<T, E>;
+
+/// Errors returned by the library, by the stage that failed. The error
+/// that caused each one is kept as its `source`.
+#[derive(Debug, thiserror::Error)]
+pub enum GuardianError {
+    #[error("manifest {} not found", path.display())]
+    ManifestNotFound { path: PathBuf },
+
+    #[error("failed to access {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("failed to resolve dependencies with cargo metadata")]
+    Metadata(#[from] cargo_metadata::Error),
+
+    #[error("{message}")]
+    Config {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+
+    #[error("failed to scan {package}")]
+    Scanner {
+        package: String,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("failed to load the advisory database")]
+    Advisory(#[source] BoxError),
+
+    #[error("request to {url} failed")]
+    Network {
+        url: String,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("failed to load plugin {}", path.display())]
+    Plugin {
+        path: PathBuf,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("no cache directory available on this platform")]
+    NoCacheDir,
+
+    #[error("failed to watch {}", path.display())]
+    Watch {
+        path: PathBuf,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("terminal I/O failed")]
+    Terminal(#[source] io::Error),
+
+    #[error("failed to render the report")]
+    Report(#[source] BoxError),
+}
+
+impl From<fmt::Error> for GuardianError {
+    fn from(err: fmt::Error) -> Self {
+        GuardianError::Report(err.into())
+    }
+}
+
+impl GuardianError {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        GuardianError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn config(message: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        GuardianError::Config {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    pub(crate) fn network(url: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        GuardianError::Network {
+            url: url.into(),
+            source: source.into(),
+        }
+    }
+
+    pub(crate) fn report(source: impl Into<BoxError>) -> Self {
+        GuardianError::Report(source.into())
+    }
+}
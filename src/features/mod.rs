@@ -0,0 +1,79 @@
+use cargo_metadata::{Dependency, Package};
+use std::collections::BTreeSet;
+
+use crate::models::{SecurityIssue, Severity};
+
+/// Features that `package` turns on by default, following the `default`
+/// feature through the package's feature table. Entries that reach into a
+/// dependency (`dep:name`, `name/feature`) are listed as written.
+pub fn default_features(package: &Package) -> Vec<String> {
+    let mut enabled = BTreeSet::new();
+    let mut pending: Vec<&str> = package
+        .features
+        .get("default")
+        .map(|features| features.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+    while let Some(feature) = pending.pop() {
+        if !enabled.insert(feature.to_string()) {
+            continue;
+        }
+        if let Some(implied) = package.features.get(feature) {
+            pending.extend(implied.iter().map(String::as_str));
+        }
+    }
+    enabled.into_iter().collect()
+}
+
+/// Reports a direct dependency declared without `default-features = false`
+/// whose defaults enable features, at Medium severity when one of them is
+/// listed in `sensitive`.
+pub fn check(dep: &Dependency, package: &Package, sensitive: &[String]) -> Option<SecurityIssue> {
+    if !dep.uses_default_features {
+        return None;
+    }
+    let enabled = default_features(package);
+    if enabled.is_empty() {
+        return None;
+    }
+
+    let flagged: Vec<&str> = enabled
+        .iter()
+        .map(String::as_str)
+        .filter(|feature| {
+            // `dep:name` and `name/feature` count under either part
+            let parts = feature.trim_start_matches("dep:").split('/');
+            parts
+                .map(|part| part.trim_end_matches('?'))
+                .any(|part| sensitive.iter().any(|name| name == part))
+        })
+        .collect();
+
+    let (severity, description) = if flagged.is_empty() {
+        (
+            Severity::Info,
+            format!(
+                "{} is used with default features, which enable: {}",
+                package.name,
+                enabled.join(", ")
+            ),
+        )
+    } else {
+        (
+            Severity::Medium,
+            format!(
+                "{} is used with default features, which enable sensitive features {} (all: {}) - consider default-features = false",
+                package.name,
+                flagged.join(", "),
+                enabled.join(", ")
+            ),
+        )
+    };
+
+    Some(SecurityIssue {
+        severity,
+        description,
+        affected_versions: vec![package.version.to_string()],
+        rule_id: Some("GUARD031".to_string()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,265 @@
+use clap::ValueEnum;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::path::Path;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::FixKind;
+
+/// Which upgrades `--apply-fixes` writes to Cargo.lock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FixScope {
+    /// Only semver-compatible upgrades
+    #[default]
+    Compatible,
+    /// Breaking upgrades too
+    Major,
+}
+
+/// Whether upgrading a dependency to the fix version of its findings
+/// resolves against the rest of the workspace's requirements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixCheck {
+    pub package: String,
+    pub version: String,
+    pub fix_version: String,
+    pub fix_kind: FixKind,
+    /// Findings on `package` that name a fix version
+    pub issues: usize,
+    pub status: FixStatus,
+    /// cargo's reason for rejecting the upgrade
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+    /// For applied upgrades, whether a scan of the updated lockfile no
+    /// longer reports the old version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleared: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixStatus {
+    /// The upgrade resolves and was left for you to apply
+    Safe,
+    /// The upgrade was written to Cargo.lock
+    Applied,
+    /// The upgrade resolves but is breaking, outside the `--apply-fixes`
+    /// scope
+    Breaking,
+    /// cargo could not resolve the upgrade
+    Conflict,
+}
+
+/// Tries `cargo update --precise` for every package with a fixable finding
+/// in `analysis`, to the highest fix version named by its findings.
+/// Cargo.lock is only written with `apply`, for the upgrades within its
+/// scope that the dry run accepted, one after the other, so a later upgrade
+/// can still conflict with an earlier one.
+pub async fn check(
+    manifest_path: &Path,
+    analysis: &DependencyAnalysis,
+    offline: bool,
+    apply: Option<FixScope>,
+) -> Result<Vec<FixCheck>> {
+    // The highest fix of each vulnerable package version, with the number
+    // of findings it addresses
+    let mut fixes: BTreeMap<(&str, &str), (Version, usize)> = BTreeMap::new();
+    for (package, issues) in &analysis.security_issues {
+        for issue in issues {
+            let (Some(fix), Some(version)) = (&issue.fix_version, issue.affected_versions.first())
+            else {
+                continue;
+            };
+            let Ok(fix) = Version::parse(fix) else {
+                continue;
+            };
+            let entry = fixes
+                .entry((package.as_str(), version.as_str()))
+                .or_insert((fix.clone(), 0));
+            entry.0 = entry.0.clone().max(fix);
+            entry.1 += 1;
+        }
+    }
+
+    let mut checks = Vec::new();
+    for ((package, version), (fix, issues)) in fixes {
+        let Ok(current) = Version::parse(version) else {
+            continue;
+        };
+        let fix_kind = FixKind::between(&current, &fix);
+        let spec = format!("{}@{}", package, version);
+        let fix_version = fix.to_string();
+
+        let mut conflict = update(manifest_path, &spec, &fix_version, offline, true).await?;
+        let status = match (&conflict, resolved_status(fix_kind, apply)) {
+            (Some(_), _) => FixStatus::Conflict,
+            (None, FixStatus::Applied) => {
+                conflict = update(manifest_path, &spec, &fix_version, offline, false).await?;
+                match conflict {
+                    Some(_) => FixStatus::Conflict,
+                    None => {
+                        info!("Updated {} to {} in Cargo.lock", spec, fix_version);
+                        FixStatus::Applied
+                    }
+                }
+            }
+            (None, status) => status,
+        };
+        checks.push(FixCheck {
+            package: package.to_string(),
+            version: version.to_string(),
+            fix_version,
+            fix_kind,
+            issues,
+            status,
+            conflict,
+            cleared: None,
+        });
+    }
+    Ok(checks)
+}
+
+/// The status of an upgrade of `fix_kind` that passed the dry run: applied
+/// when it is within the `apply` scope, which still has to be done.
+fn resolved_status(fix_kind: FixKind, apply: Option<FixScope>) -> FixStatus {
+    match apply {
+        None => FixStatus::Safe,
+        Some(FixScope::Compatible) if fix_kind == FixKind::Major => FixStatus::Breaking,
+        Some(_) => FixStatus::Applied,
+    }
+}
+
+/// Records in each applied check whether `rescan`, an analysis of the
+/// updated lockfile, still reports the version that was upgraded.
+pub fn confirm(checks: &mut [FixCheck], rescan: &DependencyAnalysis) {
+    for check in checks {
+        if check.status != FixStatus::Applied {
+            continue;
+        }
+        let issues = rescan.security_issues.get(&check.package);
+        let remaining = issues.into_iter().flatten().any(|issue| {
+            issue.fix_version.is_some() && issue.affected_versions.contains(&check.version)
+        });
+        check.cleared = Some(!remaining);
+    }
+}
+
+/// Runs `cargo update -p spec --precise version`, returning cargo's error
+/// when the update does not resolve.
+async fn update(
+    manifest_path: &Path,
+    spec: &str,
+    version: &str,
+    offline: bool,
+    dry_run: bool,
+) -> Result<Option<String>> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let mut command = Command::new(cargo);
+    command
+        .arg("update")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .args(["-p", spec, "--precise", version]);
+    if dry_run {
+        command.arg("--dry-run");
+    }
+    if offline {
+        command.arg("--offline");
+    }
+    debug!("Running {:?}", command);
+
+    let output = command
+        .output()
+        .await
+        .map_err(|err| GuardianError::io("cargo", err))?;
+    if output.status.success() {
+        return Ok(None);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("error: "))
+        .unwrap_or(stderr.trim());
+    Ok(Some(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SecurityIssue;
+    use serde_json::json;
+
+    #[test]
+    fn only_upgrades_within_the_scope_are_applied() {
+        for kind in [FixKind::Patch, FixKind::Minor, FixKind::Major] {
+            assert_eq!(resolved_status(kind, None), FixStatus::Safe);
+            assert_eq!(
+                resolved_status(kind, Some(FixScope::Major)),
+                FixStatus::Applied
+            );
+        }
+        assert_eq!(
+            resolved_status(FixKind::Patch, Some(FixScope::Compatible)),
+            FixStatus::Applied
+        );
+        assert_eq!(
+            resolved_status(FixKind::Minor, Some(FixScope::Compatible)),
+            FixStatus::Applied
+        );
+        assert_eq!(
+            resolved_status(FixKind::Major, Some(FixScope::Compatible)),
+            FixStatus::Breaking
+        );
+    }
+
+    fn fix_check(package: &str, version: &str, status: FixStatus) -> FixCheck {
+        FixCheck {
+            package: package.to_string(),
+            version: version.to_string(),
+            fix_version: "9.0.0".to_string(),
+            fix_kind: FixKind::Major,
+            issues: 1,
+            status,
+            conflict: None,
+            cleared: None,
+        }
+    }
+
+    #[test]
+    fn applied_upgrades_are_cleared_unless_the_old_version_is_still_reported() {
+        let issue = |version: &str| SecurityIssue {
+            description: "Vulnerable".to_string(),
+            affected_versions: vec![version.to_string()],
+            fix_version: Some("9.0.0".to_string()),
+            ..Default::default()
+        };
+        let rescan: DependencyAnalysis = serde_json::from_value(json!({
+            "total_dependencies": 3,
+            "direct_dependencies": [],
+            "dependency_tree": {},
+            "security_issues": {
+                "pinned": [issue("1.0.0")],
+                "upgraded": [issue("1.0.0")],
+            },
+            "suppressed_issues": 0,
+        }))
+        .unwrap();
+        let mut checks = [
+            fix_check("pinned", "1.0.0", FixStatus::Applied),
+            fix_check("upgraded", "0.5.0", FixStatus::Applied),
+            fix_check("clean", "1.0.0", FixStatus::Applied),
+            fix_check("pinned", "1.0.0", FixStatus::Breaking),
+        ];
+
+        confirm(&mut checks, &rescan);
+
+        let cleared: Vec<Option<bool>> = checks.iter().map(|check| check.cleared).collect();
+        assert_eq!(cleared, [Some(false), Some(true), Some(true), None]);
+    }
+}
@@ -0,0 +1,182 @@
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::Result;
+use crate::models::{DependencyKind, ResolvedPackage, Severity};
+
+/// Dependency graph formats for `--graph`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT, for `dot -Tsvg`
+    Dot,
+}
+
+/// Renders the resolved dependency graph of `analysis`.
+pub fn render(format: GraphFormat, analysis: &DependencyAnalysis) -> Result<String> {
+    match format {
+        GraphFormat::Dot => dot(analysis),
+    }
+}
+
+/// Renders the resolved graph as Graphviz DOT. Nodes are labelled
+/// `name@version` and filled by their worst finding: red for Critical,
+/// orange for High, yellow for Medium and Low. Workspace members are drawn
+/// as bold boxes, and edges only needed for dev or build dependencies are
+/// dashed.
+pub fn dot(analysis: &DependencyAnalysis) -> Result<String> {
+    let worst = worst_severities(analysis);
+    let mut out = String::new();
+
+    writeln!(out, "digraph dependencies {{")?;
+    writeln!(out, "    node [shape=ellipse];")?;
+    for (key, package) in &analysis.packages {
+        let mut attributes = vec![format!("label={}", quote(key))];
+        let mut style = Vec::new();
+        if package.member {
+            attributes.push("shape=box".to_string());
+            style.push("bold");
+        }
+        if let Some(color) = worst.get(key.as_str()).copied().and_then(fill_color) {
+            attributes.push(format!("fillcolor={}", color));
+            style.push("filled");
+        }
+        if !style.is_empty() {
+            attributes.push(format!("style={}", quote(&style.join(","))));
+        }
+        writeln!(out, "    {} [{}];", quote(key), attributes.join(", "))?;
+    }
+    for (key, edges) in &analysis.dependency_tree {
+        for edge in edges {
+            let attributes = if edge.kinds.contains(&DependencyKind::Normal) {
+                ""
+            } else {
+                " [style=dashed]"
+            };
+            writeln!(
+                out,
+                "    {} -> {}{};",
+                quote(key),
+                quote(&edge.package),
+                attributes
+            )?;
+        }
+    }
+    writeln!(out, "}}")?;
+    Ok(out)
+}
+
+/// The worst severity reported for each resolved package, keyed like
+/// `analysis.packages`. Issues are reported per crate name, so an advisory
+/// only marks the versions it affects and other findings mark every
+/// version of the crate.
+fn worst_severities(analysis: &DependencyAnalysis) -> BTreeMap<&str, &Severity> {
+    let mut worst: BTreeMap<&str, &Severity> = BTreeMap::new();
+    for (key, package) in &analysis.packages {
+        let Some(issues) = analysis.security_issues.get(&package.name) else {
+            continue;
+        };
+        let severity = issues
+            .iter()
+            .filter(|issue| affects(&issue.affected_versions, package))
+            .map(|issue| &issue.severity)
+            .max();
+        if let Some(severity) = severity {
+            worst.insert(key, severity);
+        }
+    }
+    worst
+}
+
+fn affects(affected_versions: &[String], package: &ResolvedPackage) -> bool {
+    affected_versions.is_empty() || affected_versions.contains(&package.version)
+}
+
+fn fill_color(severity: &Severity) -> Option<&'static str> {
+    match severity {
+        Severity::Critical => Some("red"),
+        Severity::High => Some("orange"),
+        Severity::Medium | Severity::Low => Some("yellow"),
+        Severity::Info => None,
+    }
+}
+
+/// A DOT quoted string.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SecurityIssue;
+    use serde_json::json;
+
+    #[test]
+    fn renders_nodes_and_resolve_edges() {
+        let package = |name: &str, version: &str, member: bool| ResolvedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            license: None,
+            source: None,
+            member,
+        };
+        let advisory = SecurityIssue {
+            severity: Severity::High,
+            description: "log is vulnerable".to_string(),
+            affected_versions: vec!["0.4.0".to_string()],
+            advisory_id: Some("RUSTSEC-2024-0002".to_string()),
+            ..Default::default()
+        };
+        let analysis: DependencyAnalysis = serde_json::from_value(json!({
+            "total_dependencies": 3,
+            "direct_dependencies": [],
+            "dependency_tree": {
+                "app@0.1.0": [
+                    { "package": "cc@1.0.0", "kinds": ["build"] },
+                    { "package": "log@0.4.0", "kinds": ["normal"] },
+                ],
+                "log@0.4.0": [{ "package": "cfg-if@1.0.0", "kinds": ["normal"] }],
+            },
+            "packages": {
+                "app@0.1.0": package("app", "0.1.0", true),
+                "cc@1.0.0": package("cc", "1.0.0", false),
+                "cfg-if@1.0.0": package("cfg-if", "1.0.0", false),
+                "log@0.4.0": package("log", "0.4.0", false),
+            },
+            "security_issues": { "log": [advisory] },
+            "suppressed_issues": 0,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            dot(&analysis).unwrap(),
+            r#"digraph dependencies {
+    node [shape=ellipse];
+    "app@0.1.0" [label="app@0.1.0", shape=box, style="bold"];
+    "cc@1.0.0" [label="cc@1.0.0"];
+    "cfg-if@1.0.0" [label="cfg-if@1.0.0"];
+    "log@0.4.0" [label="log@0.4.0", fillcolor=orange, style="filled"];
+    "app@0.1.0" -> "cc@1.0.0" [style=dashed];
+    "app@0.1.0" -> "log@0.4.0";
+    "log@0.4.0" -> "cfg-if@1.0.0";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn colors_nodes_by_their_worst_severity() {
+        assert_eq!(fill_color(&Severity::Critical), Some("red"));
+        assert_eq!(fill_color(&Severity::High), Some("orange"));
+        assert_eq!(fill_color(&Severity::Medium), Some("yellow"));
+        assert_eq!(fill_color(&Severity::Low), Some("yellow"));
+        assert_eq!(fill_color(&Severity::Info), None);
+    }
+
+    #[test]
+    fn escapes_quoted_ids() {
+        assert_eq!(quote(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+}
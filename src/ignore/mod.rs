@@ -0,0 +1,205 @@
+use glob::Pattern;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+
+pub const IGNORE_FILE: &str = ".guardianignore";
+
+enum IgnoreRule {
+    Path(Pattern),
+    Rule(String),
+    PathRule(Pattern, String),
+}
+
+/// Suppression rules read from a `.guardianignore` file.
+///
+/// Each non-empty line is a glob path (relative to the manifest directory),
+/// a rule or advisory ID, or a `path:rule` pair. `#` starts a comment.
+/// A `:` only separates a pair when a rule ID follows it.
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Loads `.guardianignore` from `root`, followed by any `extra` rules
+    /// from the configuration; a missing file yields no rules of its own.
+    pub fn load(root: &Path, extra: &[String]) -> Result<Self> {
+        let path = root.join(IGNORE_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(GuardianError::io(path, err)),
+        };
+
+        let mut rules = Self::parse(root, &content)
+            .map_err(|err| GuardianError::config(format!("Invalid {}", path.display()), err))?;
+        rules.rules.extend(
+            Self::parse(root, &extra.join("\n"))
+                .map_err(|err| GuardianError::config("Invalid ignore rule in config", err))?
+                .rules,
+        );
+        Ok(rules)
+    }
+
+    pub fn parse(root: &Path, content: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            // Only a rule ID after the last `:` makes a pair, so that paths
+            // such as `C:\vendor` and globs with a `:` stay paths
+            let rule = match line.rsplit_once(':') {
+                Some((_, "")) => {
+                    return Err(GuardianError::Config {
+                        message: format!("line {}: missing rule ID after `:`", index + 1),
+                        source: None,
+                    })
+                }
+                Some((path, rule)) if is_rule_id(rule) => {
+                    IgnoreRule::PathRule(glob(path, index)?, rule.to_string())
+                }
+                _ if is_rule_id(line) => IgnoreRule::Rule(line.to_string()),
+                _ => IgnoreRule::Path(glob(line, index)?),
+            };
+            rules.push(rule);
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            rules,
+        })
+    }
+
+    pub fn is_ignored(&self, issue: &SecurityIssue) -> bool {
+        self.rules.iter().any(|rule| match rule {
+            IgnoreRule::Path(pattern) => self.matches_path(pattern, issue),
+            IgnoreRule::Rule(id) => matches_rule(id, issue),
+            IgnoreRule::PathRule(pattern, id) => {
+                matches_rule(id, issue) && self.matches_path(pattern, issue)
+            }
+        })
+    }
+
+    fn matches_path(&self, pattern: &Pattern, issue: &SecurityIssue) -> bool {
+        let Some(location) = &issue.location else {
+            return false;
+        };
+
+        let relative = location.file.strip_prefix(&self.root).unwrap_or(&location.file);
+        pattern.matches_path(relative) || pattern.matches_path(&location.file)
+    }
+}
+
+fn matches_rule(id: &str, issue: &SecurityIssue) -> bool {
+    issue.rule_id.as_deref() == Some(id) || issue.advisory_id.as_deref() == Some(id)
+}
+
+/// Rule IDs look like `GUARD001` or `RUSTSEC-2021-0001`.
+fn is_rule_id(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-')
+        && value.chars().any(|c| c.is_ascii_digit())
+}
+
+fn glob(path: &str, index: usize) -> Result<Pattern> {
+    // A trailing slash ignores everything below that directory
+    let path = match path.strip_suffix('/') {
+        Some(dir) => format!("{}/**", dir),
+        None => path.to_string(),
+    };
+
+    Pattern::new(&path).map_err(|err| {
+        GuardianError::config(format!("line {}: invalid glob `{}`", index + 1, path), err)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Location;
+
+    const ROOT: &str = "/project";
+
+    fn issue(file: &str, rule: &str) -> SecurityIssue {
+        SecurityIssue {
+            rule_id: Some(rule.to_string()),
+            location: Some(Location {
+                file: Path::new(ROOT).join(file),
+                line: 1,
+                column: 1,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn parse(content: &str) -> IgnoreRules {
+        IgnoreRules::parse(Path::new(ROOT), content).unwrap()
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let rules = parse("# vetted modules\n\n   \nGUARD001 # unsafe is reviewed\n");
+        assert_eq!(rules.rules.len(), 1);
+        assert!(rules.is_ignored(&issue("src/lib.rs", "GUARD001")));
+    }
+
+    #[test]
+    fn ignores_by_path() {
+        let rules = parse("src/alloc/\nsrc/ffi.rs");
+        assert!(rules.is_ignored(&issue("src/alloc/arena.rs", "GUARD001")));
+        assert!(rules.is_ignored(&issue("src/ffi.rs", "GUARD004")));
+        assert!(!rules.is_ignored(&issue("src/lib.rs", "GUARD001")));
+    }
+
+    #[test]
+    fn ignores_by_rule() {
+        let rules = parse("GUARD002\nRUSTSEC-2021-0001");
+        assert!(rules.is_ignored(&issue("src/lib.rs", "GUARD002")));
+        assert!(!rules.is_ignored(&issue("src/lib.rs", "GUARD001")));
+
+        let advisory = SecurityIssue {
+            advisory_id: Some("RUSTSEC-2021-0001".to_string()),
+            ..Default::default()
+        };
+        assert!(rules.is_ignored(&advisory));
+    }
+
+    #[test]
+    fn ignores_by_path_and_rule() {
+        let rules = parse("src/alloc/*.rs:GUARD001");
+        assert!(rules.is_ignored(&issue("src/alloc/arena.rs", "GUARD001")));
+        assert!(!rules.is_ignored(&issue("src/alloc/arena.rs", "GUARD002")));
+        assert!(!rules.is_ignored(&issue("src/lib.rs", "GUARD001")));
+    }
+
+    #[test]
+    fn keeps_colons_that_do_not_precede_a_rule_id_in_the_path() {
+        let rules = parse(r"C:\vendor\*.rs");
+        assert!(matches!(rules.rules[..], [IgnoreRule::Path(_)]));
+
+        let rules = parse(r"C:\vendor\*.rs:GUARD001");
+        assert!(matches!(&rules.rules[..], [IgnoreRule::PathRule(_, id)] if id == "GUARD001"));
+    }
+
+    #[test]
+    fn rejects_invalid_entries_with_their_line() {
+        let root = Path::new(ROOT);
+        let err = IgnoreRules::parse(root, "GUARD001\nsrc/lib.rs:")
+            .err()
+            .expect("invalid entry accepted");
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = IgnoreRules::parse(root, "src/[lib.rs")
+            .err()
+            .expect("invalid entry accepted");
+        assert!(err.to_string().contains("line 1"), "{}", err);
+    }
+}
@@ -0,0 +1,76 @@
+//! Dependency analysis and security scanning for Rust projects.
+//!
+//! The `dep-sense` binary is a thin command line wrapper around this crate;
+//! the same analysis can be embedded in other tools:
+//!
+//! ```no_run
+//! use rustsec_guardian::{Analyzer, Config};
+//!
+//! # async fn run() -> rustsec_guardian::error::Result<()> {
+//! let analyzer = Analyzer::new("Cargo.toml".to_string(), Config::default())?;
+//! let analysis = analyzer.analyze().await?;
+//! for (package, issues) in &analysis.security_issues {
+//!     println!("{}: {} issues", package, issues.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Failures are reported as [`GuardianError`], whose variants tell apart a
+//! missing manifest, `cargo metadata` failures, invalid configuration,
+//! scanner, advisory database, network and plugin errors.
+
+pub mod advisory;
+pub mod analyzer;
+pub mod audit;
+pub mod baseline;
+pub mod cache;
+pub mod capabilities;
+pub mod cargo_config;
+pub mod config;
+pub mod confusion;
+pub mod edition;
+pub mod error;
+pub mod features;
+pub mod fixes;
+pub mod graph;
+pub mod ignore;
+pub mod license;
+pub mod macros;
+pub mod maintenance;
+pub mod manifest;
+pub mod models;
+pub mod overrides;
+pub mod pins;
+pub mod plugin;
+pub mod redact;
+pub mod registry;
+pub mod report;
+pub mod retry;
+pub mod rules;
+pub mod sbom;
+pub mod scanner;
+pub mod secrets;
+pub mod since;
+pub mod sources;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod typosquat;
+pub mod watch;
+
+/// Plugins implement [`plugin::Check`] over this crate's `Package` type.
+pub use cargo_metadata;
+
+pub use analyzer::{Analyzer, DependencyAnalysis, DependencyTree};
+pub use capabilities::CapabilitySet;
+pub use config::Config;
+pub use error::GuardianError;
+pub use models::{
+    AcceptedIssue, Confidence, DependencyEdge, DependencyInfo, DependencyKind, FixKind, Location,
+    ResolvedPackage, SecurityIssue, Severity,
+};
+pub use scanner::SecurityScanner;
@@ -0,0 +1,441 @@
+use cargo_metadata::Package;
+use glob::{MatchOptions, Pattern};
+
+use crate::config::LicensePolicy;
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+
+/// A parsed SPDX license expression.
+#[derive(Debug)]
+enum Expr {
+    License(String),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// Checks package licenses against the configured allow and deny lists.
+pub struct LicenseChecker {
+    allow: Vec<Pattern>,
+    deny: Vec<Pattern>,
+}
+
+impl LicenseChecker {
+    pub fn new(policy: &LicensePolicy) -> Result<Self> {
+        let compile = |ids: &[String]| -> Result<Vec<Pattern>> {
+            ids.iter()
+                .map(|id| {
+                    Pattern::new(id).map_err(|err| {
+                        GuardianError::config(format!("Invalid license pattern {}", id), err)
+                    })
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            allow: compile(&policy.allow)?,
+            deny: compile(&policy.deny)?,
+        })
+    }
+
+    /// Reports a package whose license is missing or not permitted by the
+    /// policy. Dual-licensed (`OR`) crates pass if any alternative does.
+    pub fn check(&self, package: &Package) -> Option<SecurityIssue> {
+        let Some(license) = &package.license else {
+            if package.license_file.is_some() {
+                // Non-standard license text; nothing to evaluate
+                return None;
+            }
+            return Some(SecurityIssue {
+                severity: Severity::Medium,
+                description: format!("Package {} declares no license", package.name),
+                affected_versions: vec![package.version.to_string()],
+                rule_id: Some("GUARD015".to_string()),
+                ..Default::default()
+            });
+        };
+
+        let description = match parse(license) {
+            Some(expr) if self.satisfies(&expr) => return None,
+            Some(_) => format!(
+                "License {} of {} is not permitted by the license policy",
+                license, package.name
+            ),
+            None => format!(
+                "License expression {:?} of {} could not be parsed",
+                license, package.name
+            ),
+        };
+
+        Some(SecurityIssue {
+            severity: Severity::High,
+            description,
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD014".to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn satisfies(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::License(id) => self.permits(id.trim_end_matches('+')),
+            Expr::And(left, right) => self.satisfies(left) && self.satisfies(right),
+            Expr::Or(left, right) => self.satisfies(left) || self.satisfies(right),
+        }
+    }
+
+    fn permits(&self, id: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let matches = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches_with(id, options))
+        };
+
+        !matches(&self.deny) && (self.allow.is_empty() || matches(&self.allow))
+    }
+}
+
+/// License families of the compatibility matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    /// MIT, BSD and the like, with no conditions on the combined work
+    Permissive,
+    /// Permissive, but its patent terms conflict with GPL-2.0-only
+    Apache2,
+    /// File-level copyleft that allows a GPL secondary license
+    Mpl2,
+    Lgpl21,
+    Lgpl3,
+    /// File-level copyleft incompatible with the GPL
+    Epl,
+    Gpl2Only,
+    Gpl2OrLater,
+    Gpl3,
+    Agpl3,
+}
+
+impl Family {
+    fn of(id: &str) -> Option<Self> {
+        let id = id.to_ascii_uppercase();
+        let family = match id.as_str() {
+            "MIT" | "MIT-0" | "BSD-2-CLAUSE" | "BSD-3-CLAUSE" | "ISC" | "ZLIB" | "0BSD"
+            | "UNLICENSE" | "CC0-1.0" | "BSL-1.0" | "UNICODE-DFS-2016" | "UNICODE-3.0" => {
+                Self::Permissive
+            }
+            "APACHE-2.0" => Self::Apache2,
+            "MPL-2.0" => Self::Mpl2,
+            "EPL-1.0" | "EPL-2.0" | "CDDL-1.0" => Self::Epl,
+            "GPL-2.0" | "GPL-2.0-ONLY" => Self::Gpl2Only,
+            "GPL-2.0+" | "GPL-2.0-OR-LATER" => Self::Gpl2OrLater,
+            _ if id.starts_with("LGPL-2.") => Self::Lgpl21,
+            _ if id.starts_with("LGPL-3.0") => Self::Lgpl3,
+            _ if id.starts_with("GPL-3.0") => Self::Gpl3,
+            _ if id.starts_with("AGPL-3.0") => Self::Agpl3,
+            _ => return None,
+        };
+        Some(family)
+    }
+
+    fn is_strong_copyleft(self) -> bool {
+        matches!(
+            self,
+            Self::Gpl2Only | Self::Gpl2OrLater | Self::Gpl3 | Self::Agpl3
+        )
+    }
+
+    /// Whether code under `dependency` may be linked into a work
+    /// distributed under `self`.
+    fn accepts(self, dependency: Family) -> bool {
+        use Family::*;
+        match (self, dependency) {
+            (_, Permissive) => true,
+            // A GPL dependency makes the combined work GPL
+            (project, dependency) if !project.is_strong_copyleft() => {
+                !dependency.is_strong_copyleft()
+            }
+            (_, Epl) => false,
+            (Gpl2Only, Apache2 | Lgpl3 | Gpl3 | Agpl3) => false,
+            (Gpl2OrLater | Gpl3 | Agpl3, Gpl2Only) => false,
+            // GPL-2.0-or-later works are then distributed under GPL-3.0,
+            // which may be combined with the AGPL
+            _ => true,
+        }
+    }
+}
+
+/// Reports `path`'s last package, a dependency linked into its first, when
+/// none of the dependency's license alternatives may be distributed under
+/// the project license: `project_license`, or else the first package's
+/// own. Licenses outside the compatibility matrix are assumed compatible.
+pub fn check_compatibility(
+    path: &[&Package],
+    project_license: Option<&str>,
+) -> Option<SecurityIssue> {
+    let [project, .., package] = path else {
+        return None;
+    };
+    let project_license = project_license.or(project.license.as_deref())?;
+    let license = package.license.as_deref()?;
+    if compatible(&parse(project_license)?, &parse(license)?) {
+        return None;
+    }
+
+    let via: Vec<&str> = path.iter().map(|package| package.name.as_str()).collect();
+    Some(SecurityIssue {
+        severity: Severity::High,
+        description: format!(
+            "License {} of {} is incompatible with the license {} of {}, which links it through {}",
+            license,
+            package.name,
+            project_license,
+            project.name,
+            via.join(" → ")
+        ),
+        affected_versions: vec![package.version.to_string()],
+        rule_id: Some("GUARD044".to_string()),
+        ..Default::default()
+    })
+}
+
+/// Whether some alternative of the project license accepts some
+/// alternative of the dependency's. `AND` requires every part.
+fn compatible(project: &Expr, dependency: &Expr) -> bool {
+    match project {
+        Expr::Or(left, right) => compatible(left, dependency) || compatible(right, dependency),
+        Expr::And(left, right) => compatible(left, dependency) && compatible(right, dependency),
+        Expr::License(id) => match Family::of(id) {
+            Some(family) => accepted_by(family, dependency),
+            None => true,
+        },
+    }
+}
+
+fn accepted_by(project: Family, dependency: &Expr) -> bool {
+    match dependency {
+        Expr::Or(left, right) => accepted_by(project, left) || accepted_by(project, right),
+        Expr::And(left, right) => accepted_by(project, left) && accepted_by(project, right),
+        Expr::License(id) => Family::of(id).is_none_or(|family| project.accepts(family)),
+    }
+}
+
+/// Parses an SPDX expression. `WITH` exceptions are folded into the license
+/// they modify, and the legacy `/` separator is read as `OR`.
+fn parse(expression: &str) -> Option<Expr> {
+    let spaced = expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+
+    let mut pos = 0;
+    let expr = parse_or(&tokens, &mut pos)?;
+    (pos == tokens.len()).then_some(expr)
+}
+
+fn parse_or(tokens: &[&str], pos: &mut usize) -> Option<Expr> {
+    let mut expr = parse_and(tokens, pos)?;
+    while tokens.get(*pos) == Some(&"OR") {
+        *pos += 1;
+        expr = Expr::Or(Box::new(expr), Box::new(parse_and(tokens, pos)?));
+    }
+    Some(expr)
+}
+
+fn parse_and(tokens: &[&str], pos: &mut usize) -> Option<Expr> {
+    let mut expr = parse_term(tokens, pos)?;
+    while tokens.get(*pos) == Some(&"AND") {
+        *pos += 1;
+        expr = Expr::And(Box::new(expr), Box::new(parse_term(tokens, pos)?));
+    }
+    Some(expr)
+}
+
+fn parse_term(tokens: &[&str], pos: &mut usize) -> Option<Expr> {
+    let token = *tokens.get(*pos)?;
+    *pos += 1;
+
+    match token {
+        "(" => {
+            let expr = parse_or(tokens, pos)?;
+            if tokens.get(*pos) != Some(&")") {
+                return None;
+            }
+            *pos += 1;
+            Some(expr)
+        }
+        ")" | "AND" | "OR" | "WITH" => None,
+        id => {
+            if tokens.get(*pos) == Some(&"WITH") {
+                tokens.get(*pos + 1)?;
+                *pos += 2;
+            }
+            Some(Expr::License(id.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use serde_json::{json, Value};
+
+    fn package(license: Value, license_file: Value) -> Package {
+        let mut project = Project::new();
+        project
+            .add("dep", "1.0.0")
+            .set("dep", "license", license)
+            .set("dep", "license_file", license_file);
+        project.package("dep")
+    }
+
+    fn checker(allow: &[&str], deny: &[&str]) -> LicenseChecker {
+        let policy = LicensePolicy {
+            allow: allow.iter().map(|id| id.to_string()).collect(),
+            deny: deny.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        };
+        LicenseChecker::new(&policy).unwrap()
+    }
+
+    /// The rule reported for a package licensed under `license`.
+    fn rule(checker: &LicenseChecker, license: &str) -> Option<String> {
+        checker
+            .check(&package(json!(license), Value::Null))
+            .and_then(|issue| issue.rule_id)
+    }
+
+    #[test]
+    fn reports_denied_licenses() {
+        let checker = checker(&[], &["GPL-*"]);
+
+        assert_eq!(rule(&checker, "GPL-3.0-only").as_deref(), Some("GUARD014"));
+        assert_eq!(rule(&checker, "gpl-2.0+").as_deref(), Some("GUARD014"));
+        assert_eq!(rule(&checker, "MIT"), None);
+    }
+
+    #[test]
+    fn dual_licensed_crates_pass_if_any_alternative_does() {
+        let checker = checker(&["MIT", "Apache-2.0"], &["GPL-*"]);
+
+        assert_eq!(rule(&checker, "MIT OR GPL-3.0-only"), None);
+        assert_eq!(rule(&checker, "GPL-3.0-only/Apache-2.0"), None);
+        assert_eq!(
+            rule(&checker, "(MIT OR Apache-2.0) AND BSD-3-Clause").as_deref(),
+            Some("GUARD014")
+        );
+        assert_eq!(
+            rule(&checker, "MIT AND GPL-3.0-only").as_deref(),
+            Some("GUARD014")
+        );
+    }
+
+    #[test]
+    fn reports_missing_licenses_unless_a_license_file_is_given() {
+        let checker = checker(&["MIT"], &[]);
+
+        let issue = checker.check(&package(Value::Null, Value::Null)).unwrap();
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD015"));
+        assert_eq!(issue.severity, Severity::Medium);
+        assert!(checker
+            .check(&package(Value::Null, json!("LICENSE.txt")))
+            .is_none());
+    }
+
+    #[test]
+    fn reports_unparsable_expressions() {
+        let checker = checker(&["MIT"], &[]);
+
+        let issue = checker
+            .check(&package(json!("MIT OR"), Value::Null))
+            .unwrap();
+        assert!(issue.description.contains("could not be parsed"));
+    }
+
+    #[test]
+    fn parses_precedence_parentheses_and_exceptions() {
+        let format = |expression| format!("{:?}", parse(expression).unwrap());
+
+        assert_eq!(
+            format("MIT OR Apache-2.0 AND BSD-3-Clause"),
+            format("MIT OR (Apache-2.0 AND BSD-3-Clause)")
+        );
+        assert_eq!(
+            format("GPL-2.0-or-later WITH Classpath-exception-2.0"),
+            r#"License("GPL-2.0-or-later")"#
+        );
+        for invalid in ["", "MIT AND", "(MIT", "MIT)", "MIT WITH", "AND MIT"] {
+            assert!(parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn compatibility_matrix() {
+        // (project, dependency, compatible)
+        let matrix = [
+            ("MIT", "Apache-2.0", true),
+            ("MIT", "MPL-2.0", true),
+            ("MIT", "GPL-3.0-only", false),
+            ("Apache-2.0", "LGPL-2.1-or-later", true),
+            ("Apache-2.0", "AGPL-3.0-only", false),
+            ("GPL-2.0-only", "MIT", true),
+            ("GPL-2.0-only", "Apache-2.0", false),
+            ("GPL-2.0-only", "LGPL-2.1-only", true),
+            ("GPL-2.0-only", "LGPL-3.0-only", false),
+            ("GPL-2.0-only", "GPL-3.0-only", false),
+            ("GPL-2.0-or-later", "Apache-2.0", true),
+            ("GPL-3.0-only", "GPL-2.0-only", false),
+            ("GPL-3.0-only", "GPL-2.0+", true),
+            ("GPL-3.0-only", "EPL-2.0", false),
+            ("AGPL-3.0-only", "GPL-3.0-or-later", true),
+            ("MPL-2.0", "EPL-2.0", true),
+            // Any alternative of either side will do
+            ("MIT OR GPL-3.0-only", "GPL-3.0-only", true),
+            ("GPL-2.0-only", "MIT OR Apache-2.0", true),
+            ("GPL-2.0-only", "MIT AND Apache-2.0", false),
+            // Licenses outside the matrix are assumed compatible
+            ("LicenseRef-Proprietary", "GPL-3.0-only", true),
+            ("MIT", "WTFPL", true),
+        ];
+
+        for (project, dependency, expected) in matrix {
+            assert_eq!(
+                compatible(&parse(project).unwrap(), &parse(dependency).unwrap()),
+                expected,
+                "{} <- {}",
+                project,
+                dependency
+            );
+        }
+    }
+
+    #[test]
+    fn incompatible_licenses_are_reported_with_the_linking_path() {
+        let mut project = Project::new();
+        project
+            .add("wrapper", "1.0.0")
+            .add("readline", "8.0.0")
+            .set("readline", "license", json!("GPL-3.0-only"));
+        let (app, wrapper, readline) = (
+            project.package("app"),
+            project.package("wrapper"),
+            project.package("readline"),
+        );
+
+        let issue = check_compatibility(&[&app, &wrapper, &readline], None).unwrap();
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD044"));
+        assert_eq!(issue.severity, Severity::High);
+        assert_eq!(
+            issue.description,
+            "License GPL-3.0-only of readline is incompatible with the license MIT of app, \
+             which links it through app → wrapper → readline"
+        );
+        // The configured project license wins over the root package's
+        assert!(check_compatibility(&[&app, &readline], Some("GPL-3.0-or-later")).is_none());
+        assert!(check_compatibility(&[&app, &wrapper], None).is_none());
+        assert!(check_compatibility(&[&app], None).is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use cargo_metadata::Package;
+
+use crate::models::{SecurityIssue, Severity};
+
+/// Whether `package` has a `proc-macro` target.
+pub fn is_proc_macro(package: &Package) -> bool {
+    package
+        .targets
+        .iter()
+        .any(|target| target.kind.iter().any(|kind| kind == "proc-macro"))
+}
+
+/// Flags a dependency providing a procedural macro, which runs arbitrary
+/// code inside the compiler. `pulled_in_by` names the direct dependencies
+/// it is reached through.
+pub fn check(package: &Package, pulled_in_by: &[String]) -> Option<SecurityIssue> {
+    if !is_proc_macro(package) {
+        return None;
+    }
+
+    let source = match pulled_in_by {
+        [direct] if *direct == package.name => "a direct dependency".to_string(),
+        _ => format!("pulled in by {}", pulled_in_by.join(", ")),
+    };
+    Some(SecurityIssue {
+        severity: Severity::Info,
+        description: format!(
+            "{} provides a procedural macro, which runs at compile time ({})",
+            package.name, source
+        ),
+        affected_versions: vec![package.version.to_string()],
+        rule_id: Some("GUARD034".to_string()),
+        ..Default::default()
+    })
+}
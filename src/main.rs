@@ -1,74 +1,1397 @@
-use anyhow::Result;
-use clap::Parser;
-use tracing::info;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
 
-mod analyzer;
-mod models;
-mod scanner;
+use rustsec_guardian::cache::{AnalysisCache, FileCache};
+use rustsec_guardian::config::{self, Config, FailOn};
+use rustsec_guardian::fixes::{self, FixScope, FixStatus};
+use rustsec_guardian::graph::{self, GraphFormat};
+use rustsec_guardian::manifest::{self, TempManifest};
+use rustsec_guardian::redact::PathRedactor;
+use rustsec_guardian::report::{ColorChoice, GroupBy, Outcome, Project, Style};
+use rustsec_guardian::sbom::{self, SbomFormat};
+#[cfg(feature = "otel")]
+use rustsec_guardian::telemetry;
+use rustsec_guardian::watch::Watcher;
+use rustsec_guardian::{
+    audit, baseline, report, rules, Analyzer, Confidence, DependencyAnalysis, SecurityScanner,
+    Severity,
+};
 
-use analyzer::Analyzer;
+/// Projects analyzed at the same time by a batch scan
+const MAX_CONCURRENT_PROJECTS: usize = 4;
 
 /// Supply Chain Intelligence Platform for Rust
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    /// Path to Cargo.toml
-    #[arg(short, long, default_value = "Cargo.toml")]
-    manifest_path: String,
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Output format (text, json)
-    #[arg(short, long, default_value = "text")]
-    output: String,
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    /// Without a subcommand, the flags of `audit`
+    #[command(flatten, next_help_heading = "Audit options")]
+    audit: AuditArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the full analysis: advisories, policies and source patterns
+    /// over the dependency graph (the default without a subcommand)
+    Audit(Box<AuditArgs>),
+    /// Print the resolved dependency tree, without running any check
+    Tree(TreeArgs),
+    /// Run the source patterns over a file or directory, which need not be
+    /// part of a cargo project
+    Scan(ScanArgs),
+    /// List the built-in rules, or explain one in depth
+    Rules(RulesArgs),
+}
+
+/// Flags shared by every subcommand.
+#[derive(clap::Args, Debug)]
+struct GlobalArgs {
+    /// Path to Cargo.toml, `-` to read it from stdin, or a URL; repeat it
+    /// to scan several projects into one report [default: Cargo.toml]
+    #[arg(short, long, global = true)]
+    manifest_path: Vec<String>,
+
+    /// Output format (text, json, ndjson, toml, html, junit, markdown, gitlab)
+    /// [default: text]
+    #[arg(short, long, global = true)]
+    output: Option<String>,
+
+    /// When to color the text report; auto colors it on a terminal unless
+    /// NO_COLOR is set
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorChoice::Auto,
+        global = true
+    )]
+    color: ColorChoice,
+
+    /// Write the report to this file instead of stdout, replacing it
+    /// atomically once complete
+    #[arg(long, global = true)]
+    output_file: Option<PathBuf>,
+
+    /// Path to a guardian.toml config file (default: next to Cargo.toml)
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Number of threads used for scanning (defaults to the number of CPUs)
+    #[arg(short, long, global = true)]
+    jobs: Option<usize>,
+
+    /// Print only the report: no progress or informational logs, and no
+    /// decorative headers in text reports
+    #[arg(short, long, conflicts_with = "verbose", global = true)]
+    quiet: bool,
+
+    /// Log more: debug with -v, trace with -vv. Without -q or -v, `RUST_LOG`
+    /// selects what is logged [default: info]
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+}
+
+/// Flags of the full analysis, the `audit` subcommand.
+#[derive(clap::Args, Debug)]
+struct AuditArgs {
+    /// Also scan the manifests listed in this file, one per line; relative
+    /// paths are resolved against the file's directory
+    #[arg(long, value_name = "FILE")]
+    manifests_from: Option<PathBuf>,
+
+    /// Version of the JSON report schema to emit, for integrations not yet
+    /// migrated to the latest one [default: latest]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..=report::SCHEMA_VERSION as i64)
+    )]
+    format_version: Option<u32>,
+
+    /// How the text report groups findings
+    #[arg(long, value_enum, value_name = "GROUPING", default_value_t = GroupBy::Package)]
+    group_by: GroupBy,
+
+    /// Emit a software bill of materials in this format instead of the report
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    sbom: Option<SbomFormat>,
+
+    /// Emit the resolved dependency graph in this format instead of the
+    /// report, with nodes colored by their worst finding
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["sbom", "summary"])]
+    graph: Option<GraphFormat>,
+
+    /// Print only the totals: dependencies, issues by severity, risk score
+    /// and the --fail-on result (text and json output)
+    #[arg(long, conflicts_with = "sbom")]
+    summary: bool,
+
+    /// Browse the findings in a terminal UI instead of printing the report;
+    /// needs a build with the `tui` feature
+    #[arg(
+        long,
+        conflicts_with_all = ["output", "output_file", "sbom", "graph", "summary", "watch"]
+    )]
+    tui: bool,
+
+    /// Load additional source patterns from a TOML or JSON file
+    #[arg(long)]
+    patterns: Option<PathBuf>,
+
+    /// Disable a rule by ID (repeatable)
+    #[arg(long = "disable-rule", value_name = "ID")]
+    disabled_rules: Vec<String>,
+
+    /// Also report pattern matches inside comments and string literals
+    #[arg(long)]
+    include_comments: bool,
+
+    /// Scan sources for hard-coded secrets and API keys
+    #[arg(long)]
+    secrets: bool,
 
     /// Enable deep scanning
     #[arg(long)]
     deep: bool,
+
+    /// Only analyze this workspace member
+    #[arg(short, long)]
+    package: Option<String>,
+
+    /// Use a pre-cloned advisory-db checkout instead of fetching it
+    #[arg(long)]
+    advisory_db: Option<PathBuf>,
+
+    /// Seconds after an update of the advisory database before it is
+    /// fetched again; 0 fetches on every run [default: 3600]
+    #[arg(long, value_name = "SECONDS")]
+    advisory_db_ttl: Option<u64>,
+
+    /// Scan the sources of registry and git dependencies from this `cargo
+    /// vendor` directory [default: their location in the cargo home]
+    #[arg(long, value_name = "DIR")]
+    vendor_dir: Option<PathBuf>,
+
+    /// Apply the bans and ignored advisories of this cargo-deny
+    /// configuration
+    #[arg(long, value_name = "PATH")]
+    deny_toml: Option<PathBuf>,
+
+    /// Append a JSON record of the run (totals, risk score and whether it
+    /// passed) to this file
+    #[arg(long, value_name = "PATH")]
+    audit_log: Option<PathBuf>,
+
+    /// Load check plugins (dynamic libraries) from this directory
+    #[arg(long, value_name = "DIR")]
+    plugin_dir: Option<PathBuf>,
+
+    /// Skip network access; advisories come from the local copy and the
+    /// yanked version check is skipped
+    #[arg(long)]
+    offline: bool,
+
+    /// Only report issues that are not in this saved JSON analysis
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Save the current analysis as a baseline for later --baseline runs
+    #[arg(long, value_name = "FILE")]
+    write_baseline: Option<PathBuf>,
+
+    /// When the run passes --fail-on, rewrite the --baseline file with the
+    /// current issues, dropping fixed ones; a failing run leaves it as is
+    #[arg(long, requires = "baseline")]
+    baseline_update_on_pass: bool,
+
+    /// Collapse repeated findings of a rule within a package into one
+    #[arg(long)]
+    group_by_rule: bool,
+
+    /// Report findings of different rules on the same line separately
+    /// instead of as one issue
+    #[arg(long)]
+    no_merge: bool,
+
+    /// Only report crates resolved at more than one version
+    #[arg(long)]
+    duplicates_only: bool,
+
+    /// Rewrite absolute paths in findings, in every output format: registry
+    /// sources as `<registry>/crate-1.2.3/...`, workspace files as relative
+    /// paths and the home directory as `~`
+    #[arg(long)]
+    redact_paths: bool,
+
+    /// Only scan the sources of dependencies added or upgraded since this
+    /// git ref's Cargo.lock; every other check still covers all of them
+    #[arg(long, value_name = "GIT_REF")]
+    since: Option<String>,
+
+    /// Always run a fresh analysis, rescanning every file, instead of reusing cached results
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Remove cached analyses and file scans and exit
+    #[arg(long)]
+    clear_cache: bool,
+
+    /// Retries for failed network requests [default: 3]
+    #[arg(long)]
+    retries: Option<u32>,
+
+    /// Seconds before a single network request is abandoned [default: 60]
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Stop scanning sources once the whole run has taken this long (e.g.
+    /// 90s, 5m), report the partial results and exit with code 2
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    scan_timeout: Option<Duration>,
+
+    /// Exit with code 1 when an issue at or above this severity is found
+    /// [default: none]
+    #[arg(long, value_enum)]
+    fail_on: Option<FailOn>,
+
+    /// --fail-on threshold for informational advisories (unmaintained,
+    /// unsound, notice) [default: the --fail-on threshold]
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    fail_on_informational: Option<FailOn>,
+
+    /// Only show issues at or above this severity (critical, high, medium,
+    /// low, info); does not change what --fail-on considers
+    #[arg(long, value_name = "LEVEL")]
+    min_severity: Option<Severity>,
+
+    /// Drop findings of rules less reliable than this (low, medium, high),
+    /// before --fail-on and the risk score
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    min_confidence: Option<Confidence>,
+
+    /// Leave dev-dependencies out of the analysis
+    #[arg(long)]
+    skip_dev: bool,
+
+    /// Leave build-dependencies out of the analysis
+    #[arg(long)]
+    skip_build: bool,
+
+    /// Only follow the dependency graph this deep: 0 keeps the direct
+    /// dependencies, 1 their dependencies too, and so on
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Analyze this saved `cargo metadata --format-version 1` output instead
+    /// of running cargo, e.g. in an air-gapped build
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["features", "all_features", "no_default_features", "watch"]
+    )]
+    metadata_json: Option<PathBuf>,
+
+    /// Features to enable, as with cargo (comma-separated or repeated)
+    #[arg(short = 'F', long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Enable every feature of the selected packages, as with cargo
+    #[arg(long)]
+    all_features: bool,
+
+    /// Do not enable the default features, as with cargo
+    #[arg(long)]
+    no_default_features: bool,
+
+    /// Skip findings in code behind `#[cfg(feature = "...")]` or
+    /// `#[cfg(test)]` that the resolved feature set does not build
+    #[arg(long)]
+    active_features_only: bool,
+
+    /// Report crates without a release in the last `max-age-months` (24 by
+    /// default), using the crates.io API
+    #[arg(long)]
+    maintenance: bool,
+
+    /// Report dependencies behind their newest release on crates.io, as
+    /// Info findings; offline, only the cached index entries are used
+    #[arg(long)]
+    outdated: bool,
+
+    /// Print the built-in rules (as JSON with --output json) and exit
+    #[arg(long)]
+    list_rules: bool,
+
+    /// Explain a built-in rule in depth: what it matches, why it is risky,
+    /// false positives and remediation; then exit
+    #[arg(long, value_name = "RULE_ID")]
+    explain: Option<String>,
+
+    /// Print how long the slowest packages and each scan stage took, on
+    /// stderr after the report; JSON reports include the timings
+    #[arg(long)]
+    profile: bool,
+
+    /// Check with a dry `cargo update --precise` whether the fix version of
+    /// each fixable finding resolves against the other requirements
+    #[arg(long, conflicts_with = "metadata_json")]
+    check_fixes: bool,
+
+    /// Upgrade the dependencies whose fix version resolves in Cargo.lock,
+    /// then scan again to confirm; `--apply-fixes=major` includes breaking
+    /// upgrades
+    #[arg(
+        long,
+        value_name = "SCOPE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "compatible",
+        conflicts_with_all = ["metadata_json", "watch"]
+    )]
+    apply_fixes: Option<FixScope>,
+
+    /// Re-run the analysis whenever the manifest, lockfile or sources change
+    #[arg(long)]
+    watch: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct TreeArgs {
+    /// Only show this workspace member
+    #[arg(short, long)]
+    package: Option<String>,
+
+    /// Leave dev-dependencies out of the tree
+    #[arg(long)]
+    skip_dev: bool,
+
+    /// Leave build-dependencies out of the tree
+    #[arg(long)]
+    skip_build: bool,
+
+    /// Only follow the dependency graph this deep: 0 keeps the direct
+    /// dependencies, 1 their dependencies too, and so on
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Resolve this saved `cargo metadata --format-version 1` output
+    /// instead of running cargo
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["features", "all_features", "no_default_features"]
+    )]
+    metadata_json: Option<PathBuf>,
+
+    /// Features to enable, as with cargo (comma-separated or repeated)
+    #[arg(short = 'F', long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Enable every feature of the selected packages, as with cargo
+    #[arg(long)]
+    all_features: bool,
+
+    /// Do not enable the default features, as with cargo
+    #[arg(long)]
+    no_default_features: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct ScanArgs {
+    /// File or directory to scan
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Load additional source patterns from a TOML or JSON file
+    #[arg(long)]
+    patterns: Option<PathBuf>,
+
+    /// Disable a rule by ID (repeatable)
+    #[arg(long = "disable-rule", value_name = "ID")]
+    disabled_rules: Vec<String>,
+
+    /// Also report pattern matches inside comments and string literals
+    #[arg(long)]
+    include_comments: bool,
+
+    /// Also scan for hard-coded secrets and API keys
+    #[arg(long)]
+    secrets: bool,
+
+    /// Also scan `target` directories, which are skipped like `.git` and
+    /// the `exclude-dirs` of the configuration
+    #[arg(long)]
+    include_target: bool,
+
+    /// Rewrite absolute paths in findings: files in the scanned directory as
+    /// relative paths and the home directory as `~`
+    #[arg(long)]
+    redact_paths: bool,
+
+    /// Exit with code 1 when an issue at or above this severity is found
+    /// [default: none]
+    #[arg(long, value_enum)]
+    fail_on: Option<FailOn>,
+
+    /// Only show issues at or above this severity (critical, high, medium,
+    /// low, info)
+    #[arg(long, value_name = "LEVEL")]
+    min_severity: Option<Severity>,
+
+    /// Drop findings of rules less reliable than this (low, medium, high),
+    /// before --fail-on
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    min_confidence: Option<Confidence>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RulesArgs {
+    /// Explain this rule in depth instead of listing every rule
+    #[arg(value_name = "RULE_ID")]
+    rule: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
-
     // Parse command line arguments
-    let args = Args::parse();
+    let cli = Cli::parse();
+    let global = cli.global;
+    init_logging(global.quiet, global.verbose);
+
+    if let Some(jobs) = global.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()?;
+    }
+
+    // Without a subcommand, the flags are those of `audit`
+    let result = match cli.command.unwrap_or(Command::Audit(Box::new(cli.audit))) {
+        Command::Audit(args) => audit(&global, &args).await,
+        Command::Tree(args) => tree(&global, &args).await,
+        Command::Scan(args) => scan(&global, &args),
+        Command::Rules(args) => match &args.rule {
+            Some(id) => explain_rule(id, global.output.as_deref()),
+            None => list_rules(global.output.as_deref()),
+        },
+    };
+    #[cfg(feature = "otel")]
+    telemetry::shutdown();
+    result
+}
+
+/// Exits with `code`, exporting the buffered telemetry first.
+fn exit(code: i32) -> ! {
+    #[cfg(feature = "otel")]
+    telemetry::shutdown();
+    std::process::exit(code)
+}
+
+/// Runs the full analysis of every manifest and writes the report.
+async fn audit(global: &GlobalArgs, args: &AuditArgs) -> Result<()> {
+    if args.clear_cache {
+        AnalysisCache::clear()?;
+        return Ok(FileCache::clear()?);
+    }
+
+    if args.list_rules {
+        return list_rules(global.output.as_deref());
+    }
+    if let Some(id) = &args.explain {
+        return explain_rule(id, global.output.as_deref());
+    }
+
+    let manifests = manifest_paths(global, args)?;
+    if manifests.len() > 1 {
+        if !run_batch(global, args, manifests).await? {
+            exit(1);
+        }
+        return Ok(());
+    }
+    let manifest_path = &manifests[0];
+
+    info!("Starting dependency analysis for: {}", manifest_path);
 
-    info!("Starting dependency analysis for: {}", args.manifest_path);
+    let config = load_config(global, args, manifest_path)?;
+
+    let output = config.output.clone().unwrap_or_else(|| "text".to_string());
+    let document = args.sbom.is_some() || args.graph.is_some();
+    if config.summary && !document && !matches!(output.as_str(), "text" | "json") {
+        anyhow::bail!(
+            "--summary only supports text and json output, not {}",
+            output
+        );
+    }
+    if args.tui && !cfg!(feature = "tui") {
+        anyhow::bail!("--tui is not available: dep-sense was built without the tui feature");
+    }
+    if args.tui && !io::stdout().is_terminal() {
+        anyhow::bail!("--tui needs a terminal");
+    }
+    let min_severity = config.min_severity.clone();
 
     // Create analyzer
-    let analyzer = Analyzer::new(args.manifest_path)?;
+    if args.watch && manifest::is_remote(manifest_path) {
+        anyhow::bail!("--watch needs a manifest on disk");
+    }
+    let temp_manifest = TempManifest::materialize(manifest_path, &config.retry_policy()).await?;
+    let analyzer = match &temp_manifest {
+        Some(temp) => Analyzer::new(temp.path().display().to_string(), config)?,
+        None => Analyzer::new(manifest_path.clone(), config)?,
+    };
     
-    // Run analysis
-    let analysis = analyzer.analyze().await?;
+    // The configuration is loaded once: edits to guardian.toml take effect
+    // on the next start, not on the next run
+    if args.watch {
+        let root = Path::new(manifest_path)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let mut watcher = Watcher::new(root)?;
+        // Only a text report on a terminal is redrawn in place; files and
+        // piped output get one report after the other
+        let clear = io::stdout().is_terminal()
+            && global.output_file.is_none()
+            && output == "text"
+            && !document;
+        loop {
+            if clear {
+                print!("\x1b[2J\x1b[H");
+            }
+            tokio::select! {
+                result = run(&analyzer, global, args, manifest_path, &output, min_severity.as_ref()) => {
+                    if let Err(err) = result {
+                        error!("{:#}", err);
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+            info!("Watching {} for changes (Ctrl-C to exit)", root.display());
+            tokio::select! {
+                changed = watcher.changed() => changed?,
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+        }
+    }
 
-    // Output results based on format
-    match args.output.as_str() {
-        "json" => println!("{}", serde_json::to_string_pretty(&analysis)?),
+    let outcome = run(
+        &analyzer,
+        global,
+        args,
+        manifest_path,
+        &output,
+        min_severity.as_ref(),
+    )
+    .await?;
+    drop(temp_manifest);
+
+    // A partial scan cannot pass, whatever it found
+    if outcome.incomplete {
+        exit(2);
+    }
+    if let Some((threshold, failing)) = outcome.failing {
+        if failing > 0 {
+            exit(1);
+        }
+        info!("No issues at or above the {} threshold", threshold);
+    }
+
+    Ok(())
+}
+
+/// Prints the resolved dependency tree of the manifest.
+async fn tree(global: &GlobalArgs, args: &TreeArgs) -> Result<()> {
+    let manifest_path = match global.manifest_path.as_slice() {
+        [] => "Cargo.toml",
+        [path] => path.as_str(),
+        _ => anyhow::bail!("tree takes a single --manifest-path"),
+    };
+    let output = global.output.as_deref().unwrap_or("text");
+    if !matches!(output, "text" | "json") {
+        anyhow::bail!("tree only supports text and json output, not {}", output);
+    }
+    let file = global
+        .output_file
+        .as_deref()
+        .map(AtomicFile::create)
+        .transpose()?;
+
+    let mut config = if manifest::is_remote(manifest_path) {
+        Config::load(global.config.as_deref(), Path::new("Cargo.toml"))?
+    } else {
+        let path = Path::new(manifest_path);
+        Config::load_with_manifest(global.config.as_deref(), path, args.package.as_deref())?
+    };
+    config.package = args.package.clone();
+    config.skip_dev |= args.skip_dev;
+    config.skip_build |= args.skip_build;
+    config.max_depth = args.max_depth.or(config.max_depth);
+    config.metadata_json = args.metadata_json.clone();
+    config.features = args.features.clone();
+    config.all_features = args.all_features;
+    config.no_default_features = args.no_default_features;
+
+    let temp_manifest = TempManifest::materialize(manifest_path, &config.retry_policy()).await?;
+    let analyzer = match &temp_manifest {
+        Some(temp) => Analyzer::new(temp.path().display().to_string(), config)?,
+        None => Analyzer::new(manifest_path.to_string(), config)?,
+    };
+    let tree = analyzer.dependency_tree()?;
+
+    let report = match output {
+        "json" => serde_json::to_string_pretty(&tree)? + "\n",
         _ => {
-            println!("\nDependency Analysis Results:");
-            println!("==========================");
-            println!("Total Dependencies: {}", analysis.total_dependencies);
-            
-            println!("\nDirect Dependencies:");
-            for dep in &analysis.direct_dependencies {
-                println!("- {} ({})", dep.name, dep.version);
+            let color = global
+                .color
+                .enabled(file.is_none() && io::stdout().is_terminal());
+            report::tree(&tree, Style::new(color))?
+        }
+    };
+    write_report(file, &report)
+}
+
+/// Runs the source patterns over a file or directory and prints the
+/// findings. Exits with code 1 when `--fail-on` finds any.
+fn scan(global: &GlobalArgs, args: &ScanArgs) -> Result<()> {
+    let output = global.output.as_deref().unwrap_or("text");
+    if !matches!(output, "text" | "json") {
+        anyhow::bail!("scan only supports text and json output, not {}", output);
+    }
+    if !args.path.exists() {
+        anyhow::bail!("{} does not exist", args.path.display());
+    }
+    let file = global
+        .output_file
+        .as_deref()
+        .map(AtomicFile::create)
+        .transpose()?;
+
+    // guardian.toml is looked up in the scanned directory
+    let dir = if args.path.is_dir() {
+        args.path.as_path()
+    } else {
+        args.path.parent().unwrap_or(Path::new(""))
+    };
+    let mut config = Config::load(global.config.as_deref(), &dir.join("Cargo.toml"))?;
+    config.include_comments |= args.include_comments;
+    config.secrets |= args.secrets;
+    config.include_target = args.include_target;
+    config.redact_paths |= args.redact_paths;
+    config.fail_on = args.fail_on.or(config.fail_on);
+    config.min_severity = args.min_severity.clone().or(config.min_severity);
+    config.min_confidence = args.min_confidence.or(config.min_confidence);
+    config
+        .disabled_rules
+        .extend_from_slice(&args.disabled_rules);
+    if let Some(path) = &args.patterns {
+        config.patterns.extend(config::load_patterns(path)?);
+    }
+
+    info!("Scanning sources in {}", args.path.display());
+    let scanner = SecurityScanner::new(&config)?;
+    let mut issues = scanner.scan_path(&args.path)?;
+    for issue in &mut issues {
+        config.override_severity(issue);
+        issue.fingerprint = issue.compute_fingerprint("", dir);
+    }
+    if let Some(min_confidence) = config.min_confidence {
+        issues.retain(|issue| issue.confidence >= min_confidence);
+    }
+    let failing = issues
+        .iter()
+        .filter(|issue| {
+            config
+                .fail_threshold(issue)
+                .is_some_and(|threshold| issue.severity.meets(&threshold))
+        })
+        .count();
+    if let Some(min_severity) = &config.min_severity {
+        issues.retain(|issue| issue.severity.meets(min_severity));
+    }
+
+    let mut path = args.path.display().to_string();
+    if config.redact_paths {
+        let redactor = PathRedactor::new(dir);
+        issues.iter_mut().for_each(|issue| redactor.issue(issue));
+        path = redactor.text(&path);
+    }
+    let report = match output {
+        "json" => report::scan_json(&path, &issues)?,
+        _ => {
+            let color = global
+                .color
+                .enabled(file.is_none() && io::stdout().is_terminal());
+            let style = Style::new(color).quiet(global.quiet);
+            report::scan_text(&path, &issues, style)?
+        }
+    };
+    write_report(file, &report)?;
+
+    if failing > 0 {
+        exit(1);
+    }
+    Ok(())
+}
+
+/// Writes a report to `file`, committing it, or else to stdout.
+fn write_report(file: Option<AtomicFile>, report: &str) -> Result<()> {
+    match file {
+        Some(mut file) => {
+            file.write_all(report.as_bytes())?;
+            let path = file.commit()?;
+            info!("Report written to {}", path.display());
+        }
+        None => {
+            let mut stdout = io::stdout();
+            stdout.write_all(report.as_bytes())?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The manifests to scan: every `--manifest-path`, then those listed in
+/// `--manifests-from`, without repeats; `Cargo.toml` if there are none.
+fn manifest_paths(global: &GlobalArgs, args: &AuditArgs) -> Result<Vec<String>> {
+    let mut manifests = global.manifest_path.clone();
+    if let Some(list) = &args.manifests_from {
+        let content = fs::read_to_string(list)
+            .with_context(|| format!("Failed to read manifest list {}", list.display()))?;
+        let base = list.parent().unwrap_or(Path::new(""));
+        let listed: Vec<&str> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        if listed.is_empty() {
+            anyhow::bail!("Manifest list {} is empty", list.display());
+        }
+        for path in listed {
+            if manifest::is_remote(path) {
+                manifests.push(path.to_string());
+            } else {
+                manifests.push(base.join(path).display().to_string());
             }
+        }
+    }
+    if manifests.is_empty() {
+        manifests.push("Cargo.toml".to_string());
+    }
+    let mut seen = HashSet::new();
+    manifests.retain(|path| seen.insert(path.clone()));
+    Ok(manifests)
+}
 
-            if !analysis.security_issues.is_empty() {
-                println!("\nSecurity Issues Found:");
-                println!("=====================");
-                for (package, issues) in &analysis.security_issues {
-                    println!("\n{} has {} issues:", package, issues.len());
-                    for issue in issues {
-                        println!("  - [{}] {}", issue.severity, issue.description);
-                        if let Some(fix) = &issue.fix_version {
-                            println!("    Fix available in version {}", fix);
-                        }
-                    }
+/// Analyzes every manifest of a batch scan, up to `MAX_CONCURRENT_PROJECTS`
+/// at a time, and writes one report covering all of them. Returns whether
+/// every project passes its `--fail-on` threshold.
+async fn run_batch(global: &GlobalArgs, args: &AuditArgs, manifests: Vec<String>) -> Result<bool> {
+    let unsupported = [
+        (args.watch, "--watch"),
+        (args.tui, "--tui"),
+        (args.sbom.is_some(), "--sbom"),
+        (args.graph.is_some(), "--graph"),
+        (args.baseline.is_some(), "--baseline"),
+        (args.write_baseline.is_some(), "--write-baseline"),
+        (args.check_fixes, "--check-fixes"),
+        (args.apply_fixes.is_some(), "--apply-fixes"),
+        (args.metadata_json.is_some(), "--metadata-json"),
+        (args.package.is_some(), "--package"),
+        (args.profile, "--profile"),
+        (args.scan_timeout.is_some(), "--scan-timeout"),
+    ];
+    if let Some((_, flag)) = unsupported.iter().find(|(set, _)| *set) {
+        anyhow::bail!("{} cannot be used with several manifests", flag);
+    }
+    let output = global.output.as_deref().unwrap_or("text");
+    if !matches!(output, "text" | "json") {
+        anyhow::bail!(
+            "A scan of several manifests only supports text and json output, not {}",
+            output
+        );
+    }
+
+    // Created up front so that an unwritable path fails before the scan
+    let file = global
+        .output_file
+        .as_deref()
+        .map(AtomicFile::create)
+        .transpose()?;
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_PROJECTS));
+    let mut tasks = JoinSet::new();
+    for (index, manifest_path) in manifests.iter().enumerate() {
+        // One progress bar per project would garble the terminal
+        let config = load_config(global, args, manifest_path).map(|config| Config {
+            quiet: true,
+            ..config
+        });
+        let manifest_path = manifest_path.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await;
+            let result = match config {
+                Ok(config) => analyze_project(&manifest_path, config).await,
+                Err(err) => Err(err),
+            };
+            (index, result)
+        });
+    }
+
+    let mut outcomes = BTreeMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, result) = joined?;
+        let outcome = match result {
+            Ok((analysis, failing)) => Outcome::Analyzed {
+                analysis: Box::new(analysis),
+                failing,
+            },
+            Err(err) => {
+                error!("Failed to analyze {}: {:#}", manifests[index], err);
+                Outcome::Failed(format!("{:#}", err))
+            }
+        };
+        outcomes.insert(index, outcome);
+    }
+    let projects: Vec<Project> = manifests
+        .into_iter()
+        .zip(outcomes.into_values())
+        .map(|(manifest_path, outcome)| Project {
+            manifest_path,
+            outcome,
+        })
+        .collect();
+
+    let color = global
+        .color
+        .enabled(file.is_none() && io::stdout().is_terminal());
+    let style = Style::new(color).quiet(global.quiet);
+    let report = match output {
+        "json" => report::batch_json(&projects, args.summary)?,
+        _ => report::batch_text(&projects, args.summary, style, args.group_by)?,
+    };
+    write_report(file, &report)?;
+
+    Ok(projects.iter().all(Project::passed))
+}
+
+/// Analyzes one project of a batch scan, returning the analysis left after
+/// `--min-severity` and the `--fail-on` outcome.
+async fn analyze_project(
+    manifest_path: &str,
+    config: Config,
+) -> Result<(DependencyAnalysis, Option<(Severity, usize)>)> {
+    info!("Starting dependency analysis for: {}", manifest_path);
+    let temp_manifest = TempManifest::materialize(manifest_path, &config.retry_policy()).await?;
+    let analyzer = match &temp_manifest {
+        Some(temp) => Analyzer::new(temp.path().display().to_string(), config)?,
+        None => Analyzer::new(manifest_path.to_string(), config)?,
+    };
+    let mut analysis = analyzer.analyze().await?;
+
+    let config = analyzer.config();
+    let failing = failing_issues(config, &analysis);
+    if let Some(path) = &config.audit_log {
+        audit::append(path, manifest_path, &analysis, failing.as_ref()).await?;
+    }
+    if let Some(min_severity) = &config.min_severity {
+        analysis.retain_min_severity(min_severity);
+    }
+    Ok((analysis, failing))
+}
+
+/// The configuration for `manifest_path`: its config file and manifest
+/// settings, overridden by the command line flags.
+fn load_config(global: &GlobalArgs, args: &AuditArgs, manifest_path: &str) -> Result<Config> {
+    // A manifest from stdin or a URL picks up guardian.toml from the
+    // current directory
+    let config_base = if manifest::is_remote(manifest_path) {
+        Path::new("Cargo.toml")
+    } else {
+        Path::new(manifest_path)
+    };
+
+    // Command line flags take precedence over the config file and the
+    // manifest's [package.metadata.guardian]
+    let mut config = if manifest::is_remote(manifest_path) {
+        Config::load(global.config.as_deref(), config_base)?
+    } else {
+        Config::load_with_manifest(
+            global.config.as_deref(),
+            config_base,
+            args.package.as_deref(),
+        )?
+    };
+    config.output = global.output.clone().or(config.output);
+    config.output_file = global.output_file.clone();
+    config.summary |= args.summary;
+    config.fail_on = args.fail_on.or(config.fail_on);
+    config.fail_on_informational = args.fail_on_informational.or(config.fail_on_informational);
+    config.min_severity = args.min_severity.clone().or(config.min_severity);
+    config.min_confidence = args.min_confidence.or(config.min_confidence);
+    config.advisory_db = args.advisory_db.clone().or(config.advisory_db);
+    config.advisory_db_ttl = args.advisory_db_ttl.unwrap_or(config.advisory_db_ttl);
+    config.plugin_dir = args.plugin_dir.clone().or(config.plugin_dir);
+    config.deny_toml = args.deny_toml.clone().or(config.deny_toml);
+    config.vendor_dir = args.vendor_dir.clone().or(config.vendor_dir);
+    config.audit_log = args.audit_log.clone().or(config.audit_log);
+    config.offline |= args.offline;
+    config.retries = args.retries.unwrap_or(config.retries);
+    config.timeout = args.timeout.unwrap_or(config.timeout);
+    config.skip_dev |= args.skip_dev;
+    config.skip_build |= args.skip_build;
+    config.max_depth = args.max_depth.or(config.max_depth);
+    config.metadata_json = args.metadata_json.clone();
+    config.profile = args.profile;
+    config.features = args.features.clone();
+    config.all_features = args.all_features;
+    config.no_default_features = args.no_default_features;
+    config.active_features_only |= args.active_features_only;
+    config.maintenance |= args.maintenance;
+    config.outdated |= args.outdated;
+    config.include_comments |= args.include_comments;
+    config.secrets |= args.secrets;
+    config.group_by_rule |= args.group_by_rule;
+    config.merge &= !args.no_merge;
+    config.cache &= !args.no_cache;
+    config.package = args.package.clone();
+    config.since = args.since.clone();
+    config.scan_timeout = args.scan_timeout;
+    config.redact_paths |= args.redact_paths;
+    config.watch = args.watch;
+    config.quiet = global.quiet;
+    if args.duplicates_only {
+        config.duplicates_only = true;
+        config.advisories = false;
+        config.yanked = false;
+        config.maintenance = false;
+        config.outdated = false;
+        config.license = Default::default();
+    }
+    config
+        .disabled_rules
+        .extend_from_slice(&args.disabled_rules);
+    if let Some(path) = &args.patterns {
+        config.patterns.extend(config::load_patterns(path)?);
+    }
+    Ok(config)
+}
+
+/// What the exit code of a run depends on.
+struct RunOutcome {
+    /// The `--fail-on` threshold, if any, with the number of issues at or
+    /// above it
+    failing: Option<(Severity, usize)>,
+    /// Whether `--scan-timeout` left packages unscanned
+    incomplete: bool,
+}
+
+/// Runs one analysis and writes the report.
+async fn run(
+    analyzer: &Analyzer,
+    global: &GlobalArgs,
+    args: &AuditArgs,
+    manifest_path: &str,
+    output: &str,
+    min_severity: Option<&Severity>,
+) -> Result<RunOutcome> {
+    let baseline = args.baseline.as_deref().map(baseline::load).transpose()?;
+
+    // Created up front so that an unwritable path fails before the scan
+    let mut file = global
+        .output_file
+        .as_deref()
+        .map(AtomicFile::create)
+        .transpose()?;
+    let mut stdout = io::stdout();
+
+    // NDJSON issue records are written as each package is checked, and a
+    // summary record once the analysis is complete
+    let document = args.sbom.is_some() || args.graph.is_some();
+    let mut stream: Option<&mut dyn Write> = match (document, output) {
+        (false, "ndjson") => match file.as_mut() {
+            Some(file) => Some(file),
+            None => Some(&mut stdout),
+        },
+        _ => None,
+    };
+    let known = baseline
+        .as_ref()
+        .map(baseline::fingerprints)
+        .unwrap_or_default();
+    let mut stream_error = None;
+
+    // Run analysis
+    let started = Instant::now();
+    let mut analysis = analyzer
+        .analyze_with(|package, issue| {
+            let Some(out) = stream.as_mut().filter(|_| stream_error.is_none()) else {
+                return;
+            };
+            if min_severity.is_some_and(|min| !issue.severity.meets(min))
+                || known.contains(&baseline::fingerprint(package, issue))
+            {
+                return;
+            }
+            if let Err(err) = report::ndjson_issue(out, package, issue) {
+                stream_error = Some(err);
+            }
+        })
+        .await?;
+    let elapsed = started.elapsed();
+    if let Some(err) = stream_error {
+        return Err(err).context("Failed to write NDJSON output");
+    }
+
+    // SBOMs and graphs cover every issue, whatever the baseline and filters
+    // hide
+    let document = match (args.sbom, args.graph) {
+        (Some(format), _) => Some(sbom::render(format, &analysis)?),
+        (None, Some(format)) => Some(graph::render(format, &analysis)?),
+        (None, None) => None,
+    };
+
+    if let Some(path) = &args.write_baseline {
+        baseline::write(path, &analysis)?;
+        info!("Baseline written to {}", path.display());
+    }
+    // Every current issue, which --baseline-update-on-pass saves
+    let current = args.baseline_update_on_pass.then(|| analysis.clone());
+    if let Some(baseline) = baseline {
+        baseline::apply(&mut analysis, baseline);
+    }
+
+    // --fail-on looks at every issue, not just the ones left on display
+    let config = analyzer.config();
+    let failing = failing_issues(config, &analysis);
+
+    if let (Some(current), Some(path)) = (current, &args.baseline) {
+        match &failing {
+            Some((threshold, count)) if *count > 0 => info!(
+                "{} new issues at or above the {} threshold, baseline {} left unchanged",
+                count,
+                threshold,
+                path.display()
+            ),
+            _ => baseline::update(path, &current, &analysis)?,
+        }
+    }
+
+    if let Some(path) = &config.audit_log {
+        audit::append(path, manifest_path, &analysis, failing.as_ref()).await?;
+    }
+
+    // Upgrades fix every finding, not only those at the reported severity
+    if args.check_fixes || args.apply_fixes.is_some() {
+        let offline = analyzer.config().offline;
+        let manifest_path = analyzer.manifest_path();
+        let mut checks = fixes::check(manifest_path, &analysis, offline, args.apply_fixes).await?;
+        let applied = checks
+            .iter()
+            .any(|check| check.status == FixStatus::Applied);
+        if applied {
+            info!("Scanning the updated lockfile");
+            fixes::confirm(&mut checks, &analyzer.analyze().await?);
+        } else if args.apply_fixes.is_some() {
+            info!("No upgrade could be applied; Cargo.lock is unchanged");
+        }
+        analysis.fix_checks = Some(checks);
+    }
+
+    if let Some(min_severity) = min_severity {
+        analysis.retain_min_severity(min_severity);
+    }
+
+    #[cfg(feature = "tui")]
+    if args.tui {
+        rustsec_guardian::tui::run(&analysis)?;
+        return Ok(RunOutcome {
+            failing,
+            incomplete: analysis.incomplete.is_some(),
+        });
+    }
+
+    // Output results based on format
+    let summary = analyzer.config().summary;
+    let color = global.color.enabled(file.is_none() && stdout.is_terminal());
+    let style = Style::new(color).quiet(global.quiet);
+    let report = match (document, output) {
+        (Some(document), _) => document,
+        (None, "json") if summary => report::summary_json(&analysis, failing.as_ref())?,
+        (None, _) if summary => report::summary_text(&analysis, failing.as_ref(), style)?,
+        (None, "json") => report::json(
+            &analysis,
+            args.format_version.unwrap_or(report::SCHEMA_VERSION),
+        )?,
+        (None, "html") => report::html(&analysis, manifest_path)?,
+        (None, "junit") => report::junit(&analysis, elapsed)?,
+        (None, "gitlab") => report::gitlab(&analysis, manifest_path)?,
+        (None, "toml") => report::toml(&analysis)?,
+        (None, "ndjson") => report::ndjson_summary(&analysis)?,
+        (None, "markdown") => report::markdown(
+            &analysis,
+            manifest_path,
+            analyzer.config().markdown_max_size,
+        )?,
+        (None, _) => report::text(&analysis, style, args.group_by)?,
+    };
+
+    write_report(file, &report)?;
+    if let Some(timings) = &analysis.timings {
+        eprint!("{}", report::profile(timings)?);
+    }
+
+    if let Some((threshold, count)) = &failing {
+        if *count > 0 {
+            info!(
+                "Failing: {} issues at or above the {} threshold",
+                count, threshold
+            );
+        }
+    }
+
+    Ok(RunOutcome {
+        failing,
+        incomplete: analysis.incomplete.is_some(),
+    })
+}
+
+/// A report file written under a temporary name in the same directory and
+/// renamed over `path` by `commit`, so that readers never see a partial
+/// report. Without a commit, the temporary file is removed.
+struct AtomicFile {
+    path: PathBuf,
+    temp: PathBuf,
+    file: fs::File,
+}
+
+impl AtomicFile {
+    fn create(path: &Path) -> Result<Self> {
+        let Some(name) = path.file_name() else {
+            anyhow::bail!("Output file {} is not a file path", path.display());
+        };
+        if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|err| write_error(parent, err))?;
+        }
+
+        let temp = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            name.to_string_lossy(),
+            std::process::id()
+        ));
+        let file = fs::File::create(&temp).map_err(|err| write_error(path, err))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp,
+            file,
+        })
+    }
+
+    /// Moves the complete report into place, returning its path.
+    fn commit(mut self) -> Result<PathBuf> {
+        self.file.flush()?;
+        self.file
+            .sync_all()
+            .map_err(|err| write_error(&self.path, err))?;
+        fs::rename(&self.temp, &self.path).map_err(|err| write_error(&self.path, err))?;
+        Ok(self.path.clone())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Already gone once committed
+        let _ = fs::remove_file(&self.temp);
+    }
+}
+
+/// The `--fail-on` threshold of `config`, if any, with the number of issues
+/// of `analysis` at or above it. Informational advisories may have a
+/// threshold of their own.
+fn failing_issues(config: &Config, analysis: &DependencyAnalysis) -> Option<(Severity, usize)> {
+    let fail_on = config.fail_on.unwrap_or(FailOn::None);
+    let informational = config.fail_on_informational.unwrap_or(fail_on);
+    let threshold = fail_on.threshold().or(informational.threshold())?;
+    let count = analysis
+        .security_issues
+        .values()
+        .flatten()
+        .filter(|issue| {
+            config
+                .fail_threshold(issue)
+                .is_some_and(|threshold| issue.severity.meets(&threshold))
+        })
+        .count();
+    Some((threshold, count))
+}
+
+/// Logs on stderr, so that stdout carries only the report: warnings and
+/// errors with `--quiet`, our own debug or trace logs with `-v` or `-vv`,
+/// and otherwise what `RUST_LOG` selects.
+fn init_logging(quiet: bool, verbose: u8) {
+    let filter = match (quiet, verbose) {
+        (true, _) => EnvFilter::new("warn"),
+        (false, 0) => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        (false, 1) => EnvFilter::new("info,dep_sense=debug,rustsec_guardian=debug"),
+        (false, _) => EnvFilter::new("info,dep_sense=trace,rustsec_guardian=trace"),
+    };
+    #[cfg(not(feature = "otel"))]
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(filter)
+        .init();
+
+    // The phase spans are exported, and kept out of the log lines
+    #[cfg(feature = "otel")]
+    {
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::util::SubscriberInitExt;
+        use tracing_subscriber::Layer;
+
+        let logs = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+        let filter = filter.add_directive(format!("{}=off", telemetry::TARGET).parse().unwrap());
+        let (export, export_error) = match telemetry::layer() {
+            Ok(layer) => (layer, None),
+            Err(err) => (None, Some(err)),
+        };
+        tracing_subscriber::registry()
+            .with(logs.with_filter(filter))
+            .with(export)
+            .init();
+        if let Some(err) = export_error {
+            tracing::warn!("Telemetry export disabled: {}", err);
+        }
+    }
+}
+
+fn write_error(path: &Path, err: io::Error) -> anyhow::Error {
+    let message = match err.kind() {
+        io::ErrorKind::PermissionDenied => format!("Permission denied writing {}", path.display()),
+        _ => format!("Failed to write {}", path.display()),
+    };
+    anyhow::Error::new(err).context(message)
+}
+
+/// Prints the catalog entry and in-depth explanation of a built-in rule.
+fn explain_rule(id: &str, output: Option<&str>) -> Result<()> {
+    let id = id.to_uppercase();
+    let (Some(rule), Some(explanation)) = (rules::find(&id), rules::explain(&id)) else {
+        anyhow::bail!(
+            "Unknown rule {}; see --list-rules for the built-in rules",
+            id
+        );
+    };
+
+    if output == Some("json") {
+        let mut entry = serde_json::to_value(rule)?;
+        if let (Some(entry), serde_json::Value::Object(details)) =
+            (entry.as_object_mut(), serde_json::to_value(explanation)?)
+        {
+            entry.extend(details);
+        }
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+        return Ok(());
+    }
+
+    let category = serde_json::to_value(rule.category)?;
+    println!(
+        "{}: {} ({}, {} confidence, {})",
+        rule.id,
+        rule.title,
+        rule.severity,
+        rule.confidence,
+        category.as_str().unwrap_or_default()
+    );
+    let sections = [
+        ("What it matches", explanation.matches),
+        ("Why it is risky", explanation.risk),
+        ("Typical false positives", explanation.false_positives),
+        ("Remediation", explanation.remediation),
+    ];
+    for (heading, text) in sections {
+        println!("\n{}:", heading);
+        for line in wrap(text, 76) {
+            println!("  {}", line);
+        }
+    }
+    let examples = [
+        ("Example", explanation.example),
+        ("Safer alternative", explanation.safer),
+    ];
+    for (heading, code) in examples {
+        if let Some(code) = code {
+            println!("\n{}:", heading);
+            for line in code.lines() {
+                match line {
+                    "" => println!(),
+                    _ => println!("    {}", line),
                 }
             }
         }
     }
+    Ok(())
+}
+
+/// Breaks `text` into lines of at most `width` characters at spaces.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+/// Prints the rule catalog.
+fn list_rules(output: Option<&str>) -> Result<()> {
+    if output == Some("json") {
+        println!("{}", serde_json::to_string_pretty(rules::RULES)?);
+        return Ok(());
+    }
 
+    for rule in rules::RULES {
+        let category = serde_json::to_value(rule.category)?;
+        println!(
+            "{}  {:<8}  {:<6}  {:<12}  {}",
+            rule.id,
+            rule.severity.to_string(),
+            rule.confidence.to_string(),
+            category.as_str().unwrap_or_default(),
+            rule.title
+        );
+        println!("{:10}{}", "", rule.description);
+    }
     Ok(())
 }
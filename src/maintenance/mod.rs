@@ -0,0 +1,183 @@
+use cargo_metadata::{Package, PackageId};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::cache::{cache_root, is_fresh, read_cache, write_cache};
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+use crate::retry::{retry, RetryPolicy};
+
+const API_URL: &str = "https://crates.io/api/v1/crates";
+/// How long fetched crate metadata is used before it is fetched again.
+pub const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// crates.io asks API clients for at most one request per second
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+/// Stale crates downloaded more than this in the last 90 days are reported
+/// as Info: widely used crates are often finished rather than abandoned
+const POPULAR_DOWNLOADS: u64 = 100_000;
+const SECONDS_PER_MONTH: u64 = 2_629_746;
+
+/// The parts of a crates.io API crate response used here.
+#[derive(Debug, Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateInfo {
+    max_version: String,
+    updated_at: String,
+    #[serde(default)]
+    recent_downloads: Option<u64>,
+}
+
+/// Client for the crates.io API with a per-crate on-disk cache.
+pub struct CratesIo {
+    client: reqwest::Client,
+    cache_dir: PathBuf,
+    policy: RetryPolicy,
+}
+
+impl CratesIo {
+    pub fn new(policy: RetryPolicy) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!(
+                "dep-sense/",
+                env!("CARGO_PKG_VERSION"),
+                " (https://github.com/Anas-debug/rustsec-guardian)"
+            ))
+            .timeout(policy.timeout)
+            .build()
+            .map_err(|err| GuardianError::network(API_URL, err))?;
+
+        Ok(Self {
+            client,
+            cache_dir: cache_root()?.join("crates-io"),
+            policy,
+        })
+    }
+
+    /// Flags every crates.io package whose crate has not been released for
+    /// more than `max_age_months`. Requests are made one at a time, at most
+    /// one per second; responses are cached for a day.
+    pub async fn stale_issues(
+        &self,
+        packages: &[&Package],
+        max_age_months: u64,
+    ) -> HashMap<PackageId, SecurityIssue> {
+        let names: BTreeSet<&str> = packages
+            .iter()
+            .filter(|package| package.source.as_ref().is_some_and(|s| s.is_crates_io()))
+            .map(|package| package.name.as_str())
+            .collect();
+
+        let mut infos = HashMap::new();
+        let mut next_request = Instant::now();
+        for name in names {
+            let cache_path = self.cache_dir.join(name.to_lowercase());
+            let url = format!("{}/{}", API_URL, name);
+            let body = if is_fresh(&cache_path, CACHE_TTL) {
+                debug!("Using cached crates.io entry for {}", name);
+                read_cache(&cache_path)
+            } else {
+                tokio::time::sleep_until(next_request).await;
+                next_request = Instant::now() + REQUEST_INTERVAL;
+                let what = format!("Fetching crates.io metadata for {}", name);
+                match retry(&self.policy, &what, || self.fetch(&url)).await {
+                    Ok(body) => {
+                        if let Err(err) = write_cache(&cache_path, &body) {
+                            warn!("Failed to cache crates.io entry for {}: {}", name, err);
+                        }
+                        Ok(body)
+                    }
+                    Err(err) if cache_path.exists() => {
+                        warn!("Using stale crates.io entry for {}: {:#}", name, err);
+                        read_cache(&cache_path)
+                    }
+                    Err(err) => Err(err),
+                }
+            };
+
+            match body.and_then(|body| parse_crate(&url, &body)) {
+                Ok(info) => {
+                    infos.insert(name, info);
+                }
+                Err(err) => warn!("Could not check maintenance of {}: {:#}", name, err),
+            }
+        }
+        info!("Checked maintenance of {} crates", infos.len());
+
+        let now = SystemTime::now();
+        let mut issues = HashMap::new();
+        for package in packages {
+            let Some(info) = infos.get(package.name.as_str()) else {
+                continue;
+            };
+            let updated = match parse_timestamp(&info.updated_at) {
+                Ok(updated) => updated,
+                Err(err) => {
+                    debug!("Bad timestamp for {}: {}", package.name, err);
+                    continue;
+                }
+            };
+            let months =
+                now.duration_since(updated).unwrap_or_default().as_secs() / SECONDS_PER_MONTH;
+            if months <= max_age_months {
+                continue;
+            }
+
+            let downloads = info.recent_downloads.unwrap_or(0);
+            issues.insert(
+                package.id.clone(),
+                SecurityIssue {
+                    severity: if downloads > POPULAR_DOWNLOADS {
+                        Severity::Info
+                    } else {
+                        Severity::Low
+                    },
+                    description: format!(
+                        "{} has not been updated in {} months (latest {}, {} downloads in the last 90 days) - https://crates.io/crates/{}",
+                        package.name, months, info.max_version, downloads, package.name
+                    ),
+                    affected_versions: vec![package.version.to_string()],
+                    rule_id: Some("GUARD030".to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+
+        issues
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let response = self.client.get(url).send().await;
+        let response = response
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| GuardianError::network(url, err))?;
+        response
+            .text()
+            .await
+            .map_err(|err| GuardianError::network(url, err))
+    }
+}
+
+/// Parses the response fetched from `url`, or a cached copy of it.
+fn parse_crate(url: &str, body: &str) -> Result<CrateInfo> {
+    let response: CrateResponse =
+        serde_json::from_str(body).map_err(|err| GuardianError::network(url, err))?;
+    Ok(response.krate)
+}
+
+/// Parses the RFC 3339 timestamps of the crates.io API, which use a
+/// `+00:00` offset rather than `Z`.
+fn parse_timestamp(timestamp: &str) -> Result<SystemTime, humantime::TimestampError> {
+    let timestamp = timestamp
+        .strip_suffix("+00:00")
+        .map_or_else(|| timestamp.to_string(), |utc| format!("{}Z", utc));
+    humantime::parse_rfc3339_weak(&timestamp)
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tracing::{debug, info};
+
+use crate::error::{GuardianError, Result};
+use crate::retry::{retry, RetryPolicy};
+
+/// `--manifest-path` value that reads the manifest from stdin.
+pub const STDIN: &str = "-";
+
+/// Whether `spec` names a manifest that is not on disk: stdin or a URL.
+pub fn is_remote(spec: &str) -> bool {
+    spec == STDIN || spec.starts_with("http://") || spec.starts_with("https://")
+}
+
+/// A manifest copied into a temporary directory so that `cargo metadata`
+/// can run on it. The directory, created with an unpredictable name, is
+/// removed when this is dropped.
+///
+/// Only the manifest (and, for URLs, a `Cargo.lock` next to it) is
+/// available: sources are replaced by an empty `src/lib.rs`, so path
+/// dependencies and workspace members cannot be resolved, and without a
+/// lockfile resolution needs access to the registry.
+#[derive(Debug)]
+pub struct TempManifest {
+    dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempManifest {
+    /// Materializes `spec` if it is stdin or a URL; plain paths are left
+    /// alone and give `None`.
+    pub async fn materialize(spec: &str, policy: &RetryPolicy) -> Result<Option<Self>> {
+        if spec == STDIN {
+            let manifest =
+                io::read_to_string(io::stdin()).map_err(|err| GuardianError::io("stdin", err))?;
+            return Self::create(&manifest, None).map(Some);
+        }
+        if !is_remote(spec) {
+            return Ok(None);
+        }
+
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("dep-sense/", env!("CARGO_PKG_VERSION")))
+            .timeout(policy.timeout)
+            .build()
+            .map_err(|err| GuardianError::network(spec, err))?;
+        info!("Fetching manifest from {}", spec);
+        let what = format!("Fetching {}", spec);
+        let manifest = retry(policy, &what, || fetch(&client, spec))
+            .await?
+            .ok_or_else(|| GuardianError::ManifestNotFound { path: spec.into() })?;
+
+        // A lockfile next to the manifest pins the resolution
+        let lockfile = match spec.strip_suffix("Cargo.toml") {
+            Some(base) => {
+                let url = format!("{}Cargo.lock", base);
+                match fetch(&client, &url).await {
+                    Ok(lockfile) => lockfile,
+                    Err(err) => {
+                        debug!("No lockfile at {}: {}", url, err);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        Self::create(&manifest, lockfile.as_deref()).map(Some)
+    }
+
+    fn create(manifest: &str, lockfile: Option<&str>) -> Result<Self> {
+        toml::from_str::<toml::Table>(manifest)
+            .map_err(|err| GuardianError::config("Invalid manifest", err))?;
+
+        let dir = tempfile::Builder::new()
+            .prefix("guardian-manifest-")
+            .tempdir()
+            .map_err(|err| GuardianError::io(std::env::temp_dir(), err))?;
+        let temp = Self {
+            path: dir.path().join("Cargo.toml"),
+            dir,
+        };
+        let write = |relative: &str, content: &str| {
+            let path = temp.dir.path().join(relative);
+            fs::create_dir_all(path.parent().unwrap_or(temp.dir.path()))
+                .and_then(|()| fs::write(&path, content))
+                .map_err(|err| GuardianError::io(path, err))
+        };
+        write("Cargo.toml", manifest)?;
+        write("src/lib.rs", "")?;
+        if let Some(lockfile) = lockfile {
+            write("Cargo.lock", lockfile)?;
+        }
+        Ok(temp)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Fetches `url`, giving `None` when it does not exist.
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Option<String>> {
+    let network_error = |err| GuardianError::network(url, err);
+    let response = client.get(url).send().await.map_err(network_error)?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let response = response.error_for_status().map_err(network_error)?;
+    response.text().await.map(Some).map_err(network_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{Analyzer, DependencyAnalysis};
+    use crate::config::Config;
+
+    /// A manifest of the package `name`, without dependencies.
+    fn manifest(name: &str) -> String {
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            name
+        )
+    }
+
+    async fn analyze(manifest: &TempManifest) -> DependencyAnalysis {
+        let config = Config {
+            offline: true,
+            cache: false,
+            advisories: false,
+            yanked: false,
+            quiet: true,
+            ..Config::default()
+        };
+        Analyzer::new(manifest.path().display().to_string(), config)
+            .unwrap()
+            .analyze()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn batch_scans_analyze_each_materialized_manifest() {
+        let first = TempManifest::create(&manifest("first"), None).unwrap();
+        let second = TempManifest::create(&manifest("second"), None).unwrap();
+        assert_ne!(first.path(), second.path());
+
+        // Concurrently, as in a batch scan
+        let (first_analysis, second_analysis) = tokio::join!(analyze(&first), analyze(&second));
+
+        let packages =
+            |analysis: &DependencyAnalysis| analysis.packages.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(packages(&first_analysis), ["first@0.1.0"]);
+        assert_eq!(packages(&second_analysis), ["second@0.1.0"]);
+
+        let dir = first.path().parent().unwrap().to_path_buf();
+        drop(first);
+        assert!(!dir.exists());
+        assert!(second.path().exists());
+    }
+}
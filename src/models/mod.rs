@@ -1,24 +1,156 @@
-use serde::Serialize;
+use clap::ValueEnum;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-#[derive(Debug, Serialize)]
+use crate::error::GuardianError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyInfo {
+    /// Workspace member that declares this dependency
+    pub member: String,
     pub name: String,
     pub version: String,
     pub is_direct: bool,
     pub features: Vec<String>,
     pub dependencies: Vec<String>,
+    /// Edition of the resolved package
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// `rust-version` (MSRV) declared by the resolved package
+    #[serde(default)]
+    pub rust_version: Option<String>,
+    /// Features enabled through the dependency's default features
+    #[serde(default)]
+    pub default_features: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
 }
 
-#[derive(Debug, Serialize, Clone)]
+impl fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyKind::Normal => write!(f, "normal"),
+            DependencyKind::Dev => write!(f, "dev"),
+            DependencyKind::Build => write!(f, "build"),
+        }
+    }
+}
+
+/// A resolved dependency edge, keyed by `name@version`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyEdge {
+    pub package: String,
+    pub kinds: Vec<DependencyKind>,
+}
+
+/// A package of the resolved graph, as listed in SBOMs.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: String,
+    /// SPDX license expression, as declared
+    pub license: Option<String>,
+    /// Cargo source ID (`registry+…`, `git+…`); `None` for local paths
+    pub source: Option<String>,
+    /// Whether this is a workspace member rather than a dependency
+    pub member: bool,
+}
+
+/// Serialized as the uppercase label that `Display` prints; parsing is
+/// case-insensitive, so config files may write `high`. Ordered from `Info`
+/// up to `Critical`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", rename_all = "UPPERCASE")]
 pub enum Severity {
     Critical,
     High,
     Medium,
     Low,
+    #[default]
     Info,
 }
 
+impl Severity {
+    /// Numeric level, from 0 for `Info` to 4 for `Critical`.
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Critical => 4,
+            Severity::High => 3,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+            Severity::Info => 0,
+        }
+    }
+
+    /// Maps a CVSS base score onto the qualitative severity scale.
+    pub fn from_cvss(score: f32) -> Self {
+        match score {
+            score if score >= 9.0 => Severity::Critical,
+            score if score >= 7.0 => Severity::High,
+            score if score >= 4.0 => Severity::Medium,
+            score if score > 0.0 => Severity::Low,
+            _ => Severity::Info,
+        }
+    }
+
+    /// Returns true when this severity is at or above `threshold`.
+    pub fn meets(&self, threshold: &Severity) -> bool {
+        self >= threshold
+    }
+}
+
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Severity {
+    type Err = GuardianError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "critical" => Ok(Severity::Critical),
+            "high" => Ok(Severity::High),
+            "medium" => Ok(Severity::Medium),
+            "low" => Ok(Severity::Low),
+            "info" => Ok(Severity::Info),
+            _ => Err(GuardianError::Config {
+                message: format!(
+                    "unknown severity `{}` (expected critical, high, medium, low or info)",
+                    s
+                ),
+                source: None,
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for Severity {
+    type Error = GuardianError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl fmt::Display for Severity {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -31,10 +163,297 @@ impl fmt::Display for Severity {
     }
 }
 
-#[derive(Debug, Serialize)]
+/// How reliably a rule's findings are real issues: exact matches of a
+/// dangerous API are `High`, heuristics that often match harmless code
+/// `Low`. Ordered from `Low` up to `High`.
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Position of a finding in a source file; line and column are 1-based and
+/// the column counts characters, not bytes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SecurityIssue {
     pub severity: Severity,
     pub description: String,
     pub affected_versions: Vec<String>,
+    /// Release to upgrade to; for advisories, the lowest patched one the
+    /// dependents' version requirements accept, if any
     pub fix_version: Option<String>,
+    /// Size of the upgrade to `fix_version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_kind: Option<FixKind>,
+    pub advisory_id: Option<String>,
+    /// Class of an informational advisory (`unmaintained`, `unsound`,
+    /// `notice`); `None` for vulnerabilities and non-advisory findings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub informational: Option<String>,
+    pub rule_id: Option<String>,
+    /// How reliable the rule that reported the finding is; `High` for
+    /// advisories
+    #[serde(default)]
+    pub confidence: Confidence,
+    /// Severity before `dependency-severity` escalated the finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalated_from: Option<Severity>,
+    /// Other rules that matched on the same line, merged into this issue;
+    /// see `merge` in the configuration
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_rules: Vec<String>,
+    /// CVSS base score, when the finding comes from a scored advisory
+    pub cvss_score: Option<f32>,
+    pub cvss_vector: Option<String>,
+    pub location: Option<Location>,
+    /// Every location of a finding rolled up by `--group-by-rule`; empty
+    /// when the issue stands for a single match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<Location>,
+    /// Shortest chain of packages from the root to the affected package
+    pub dependency_path: Vec<String>,
+    /// Number of other dependency paths that also reach the package
+    pub other_paths: u64,
+    /// Identity of the finding across runs; see
+    /// [`compute_fingerprint`](Self::compute_fingerprint)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub fingerprint: String,
+}
+
+impl SecurityIssue {
+    /// A SHA-256 digest of the rule or advisory ID, `package` and the
+    /// location's path relative to `root`, the directory of the package's
+    /// sources, and line: stable over runs on the same code, whatever the
+    /// description, severity or checkout path.
+    pub fn compute_fingerprint(&self, package: &str, root: &Path) -> String {
+        let id = self.rule_id.as_deref().or(self.advisory_id.as_deref());
+        let mut hasher = Sha256::new();
+        for part in [id.unwrap_or_default(), package] {
+            hasher.update(part.as_bytes());
+            hasher.update(b"\0");
+        }
+        if let Some(location) = &self.location {
+            let file = location.file.strip_prefix(root).unwrap_or(&location.file);
+            let components: Vec<_> = file
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect();
+            hasher.update(components.join("/").as_bytes());
+            hasher.update(b"\0");
+            hasher.update(location.line.to_string().as_bytes());
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// "Fix available in version …", with the size of the upgrade when known.
+    pub fn fix_description(&self) -> Option<String> {
+        let fix = self.fix_version.as_ref()?;
+        Some(match self.fix_kind {
+            Some(kind) => format!("Fix available in version {} ({} upgrade)", fix, kind),
+            None => format!("Fix available in version {}", fix),
+        })
+    }
+}
+
+/// How far an upgrade goes, by Cargo's semver rules: a `major` upgrade may
+/// break the build, which for `0.x` versions includes a minor bump.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FixKind {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl FixKind {
+    pub fn between(current: &Version, fix: &Version) -> Self {
+        let breaking = match (current.major, current.minor) {
+            (0, 0) => fix.major != 0 || fix.minor != 0 || fix.patch != current.patch,
+            (0, minor) => fix.major != 0 || fix.minor != minor,
+            (major, _) => fix.major != major,
+        };
+        if breaking {
+            FixKind::Major
+        } else if fix.minor != current.minor {
+            FixKind::Minor
+        } else {
+            FixKind::Patch
+        }
+    }
+}
+
+impl fmt::Display for FixKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FixKind::Patch => write!(f, "patch"),
+            FixKind::Minor => write!(f, "minor"),
+            FixKind::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// An advisory finding left out of the reported issues by
+/// `accepted-advisories`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AcceptedIssue {
+    pub issue: SecurityIssue,
+    /// Why the advisory was accepted
+    pub reason: Option<String>,
+    /// Date (`YYYY-MM-DD`) from which the advisory is reported again
+    pub expires: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severities_are_ordered_from_info_to_critical() {
+        let ascending = [
+            Severity::Info,
+            Severity::Low,
+            Severity::Medium,
+            Severity::High,
+            Severity::Critical,
+        ];
+
+        for (rank, pair) in ascending.windows(2).enumerate() {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+            assert_eq!(pair[0].rank() as usize, rank);
+        }
+        assert_eq!(Severity::Critical.rank(), 4);
+    }
+
+    #[test]
+    fn sorting_descending_puts_critical_first() {
+        let mut severities = vec![
+            Severity::Low,
+            Severity::Critical,
+            Severity::Info,
+            Severity::High,
+            Severity::Medium,
+            Severity::Critical,
+        ];
+
+        severities.sort_by(|a, b| b.cmp(a));
+
+        assert_eq!(
+            severities,
+            [
+                Severity::Critical,
+                Severity::Critical,
+                Severity::High,
+                Severity::Medium,
+                Severity::Low,
+                Severity::Info,
+            ]
+        );
+    }
+
+    #[test]
+    fn meets_thresholds_at_or_below() {
+        assert!(Severity::High.meets(&Severity::High));
+        assert!(Severity::High.meets(&Severity::Medium));
+        assert!(!Severity::High.meets(&Severity::Critical));
+    }
+
+    #[test]
+    fn parses_case_insensitively_what_it_displays() {
+        for severity in ["critical", "HIGH", "Medium", "low", "INFO"] {
+            let parsed: Severity = severity.parse().unwrap();
+            assert_eq!(parsed.to_string(), severity.to_ascii_uppercase());
+        }
+        assert!("severe".parse::<Severity>().is_err());
+    }
+
+    #[test]
+    fn sizes_upgrades_by_cargo_semver_rules() {
+        let kind = |current: &str, fix: &str| {
+            FixKind::between(&current.parse().unwrap(), &fix.parse().unwrap())
+        };
+
+        assert_eq!(kind("1.2.3", "1.2.4"), FixKind::Patch);
+        assert_eq!(kind("1.2.3", "1.3.0"), FixKind::Minor);
+        assert_eq!(kind("1.2.3", "2.0.0"), FixKind::Major);
+        // Below 1.0, the minor version is the breaking one
+        assert_eq!(kind("0.4.1", "0.4.2"), FixKind::Patch);
+        assert_eq!(kind("0.4.1", "0.5.0"), FixKind::Major);
+        assert_eq!(kind("0.0.3", "0.0.4"), FixKind::Major);
+    }
+
+    #[test]
+    fn fingerprints_ignore_the_checkout_path_and_wording() {
+        let issue = |root: &str, line: usize, description: &str| SecurityIssue {
+            severity: Severity::High,
+            description: description.to_string(),
+            rule_id: Some("GUARD001".to_string()),
+            location: Some(Location {
+                file: Path::new(root).join("src/lib.rs"),
+                line,
+                column: 5,
+            }),
+            ..Default::default()
+        };
+        let fingerprint = |root: &str, line: usize, description: &str| {
+            issue(root, line, description).compute_fingerprint("log", Path::new(root))
+        };
+
+        let original = fingerprint("/home/ci/a/log-0.4.0", 3, "Unsafe block");
+        assert_eq!(
+            original,
+            fingerprint("/tmp/b/log-0.4.0", 3, "Unsafe code block")
+        );
+        assert_ne!(
+            original,
+            fingerprint("/home/ci/a/log-0.4.0", 4, "Unsafe block")
+        );
+        assert_ne!(
+            original,
+            issue("/home/ci/a/log-0.4.0", 3, "Unsafe block")
+                .compute_fingerprint("serde", Path::new("/home/ci/a/log-0.4.0"))
+        );
+    }
 }
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use crate::models::{SecurityIssue, Severity};
+
+/// Reports the `[patch]` and `[replace]` entries of the workspace manifest
+/// in `workspace_root`, keyed by the name of the overridden crate. Git and
+/// path targets are High; patches that only pin another version, Medium.
+pub fn check(workspace_root: &Path) -> HashMap<String, Vec<SecurityIssue>> {
+    let path = workspace_root.join("Cargo.toml");
+    let manifest: toml::Table = match fs::read_to_string(&path).map(|content| content.parse()) {
+        Ok(Ok(manifest)) => manifest,
+        Ok(Err(err)) => {
+            debug!("Failed to parse {}: {}", path.display(), err);
+            return HashMap::new();
+        }
+        Err(err) => {
+            debug!("Failed to read {}: {}", path.display(), err);
+            return HashMap::new();
+        }
+    };
+
+    let mut issues: HashMap<String, Vec<SecurityIssue>> = HashMap::new();
+    let patches = manifest.get("patch").and_then(toml::Value::as_table);
+    for (registry, crates) in patches.into_iter().flatten() {
+        for (name, target) in crates.as_table().into_iter().flatten() {
+            let issue = issue(
+                format!("{} is patched in [patch.{}]", name, registry),
+                target,
+            );
+            issues.entry(name.clone()).or_default().push(issue);
+        }
+    }
+
+    let replacements = manifest.get("replace").and_then(toml::Value::as_table);
+    for (spec, target) in replacements.into_iter().flatten() {
+        // Keys are package ID specs such as `foo:1.0.0` or `foo@1.0.0`
+        let name = spec.split([':', '@']).next().unwrap_or(spec);
+        let issue = issue(format!("{} is replaced in [replace]", spec), target);
+        issues.entry(name.to_string()).or_default().push(issue);
+    }
+
+    issues
+}
+
+fn issue(overridden: String, target: &toml::Value) -> SecurityIssue {
+    let field = |key: &str| target.get(key).and_then(toml::Value::as_str);
+    let (severity, target) = if let Some(url) = field("git") {
+        let reference = ["rev", "tag", "branch"]
+            .into_iter()
+            .find_map(|key| Some(format!(" ({} {})", key, field(key)?)))
+            .unwrap_or_default();
+        (Severity::High, format!("git {}{}", url, reference))
+    } else if let Some(path) = field("path") {
+        (Severity::High, format!("path {}", path))
+    } else {
+        let version = field("version").or(target.as_str()).unwrap_or("*");
+        (Severity::Medium, format!("version {}", version))
+    };
+    let package = field("package")
+        .map(|package| format!(" of {}", package))
+        .unwrap_or_default();
+
+    SecurityIssue {
+        severity,
+        description: format!("{} with {}{}", overridden, target, package),
+        rule_id: Some("GUARD036".to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use tempfile::TempDir;
+
+    /// The overrides of the workspace manifest `manifest`, as severity and
+    /// description, by crate.
+    fn overrides(manifest: &str) -> Vec<(String, Severity, String)> {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        let mut found: Vec<_> = check(dir.path())
+            .into_iter()
+            .flat_map(|(name, issues)| {
+                issues
+                    .into_iter()
+                    .map(move |issue| (name.clone(), issue.severity, issue.description))
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn patches_are_reported_by_target() {
+        let found = overrides(
+            "[package]\nname = \"app\"\n\n\
+             [patch.crates-io]\n\
+             serde = { git = \"https://github.com/org/serde\", rev = \"0123abc\" }\n\
+             log = { path = \"../log\" }\n\
+             rand = { version = \"=0.8.4\", package = \"rand-fork\" }\n\
+             [patch.'https://github.com/org/repo']\n\
+             util = { git = \"https://github.com/me/util\" }\n",
+        );
+
+        assert_eq!(
+            found,
+            [
+                (
+                    "log".to_string(),
+                    Severity::High,
+                    "log is patched in [patch.crates-io] with path ../log".to_string()
+                ),
+                (
+                    "rand".to_string(),
+                    Severity::Medium,
+                    "rand is patched in [patch.crates-io] with version =0.8.4 of rand-fork"
+                        .to_string()
+                ),
+                (
+                    "serde".to_string(),
+                    Severity::High,
+                    "serde is patched in [patch.crates-io] with git \
+                     https://github.com/org/serde (rev 0123abc)"
+                        .to_string()
+                ),
+                (
+                    "util".to_string(),
+                    Severity::High,
+                    "util is patched in [patch.https://github.com/org/repo] with git \
+                     https://github.com/me/util"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn replacements_are_reported_by_crate_name() {
+        let found = overrides(
+            "[replace]\n\
+             \"foo:1.0.0\" = { git = \"https://github.com/org/foo\", branch = \"fix\" }\n\
+             \"bar@0.2.0\" = { path = \"bar\" }\n",
+        );
+
+        let names: Vec<&str> = found.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, ["bar", "foo"]);
+        assert_eq!(
+            found[1].2,
+            "foo:1.0.0 is replaced in [replace] with git https://github.com/org/foo (branch fix)"
+        );
+    }
+
+    #[test]
+    fn manifests_without_overrides_report_nothing() {
+        assert!(overrides("[package]\nname = \"app\"\n").is_empty());
+        assert!(overrides("not toml [").is_empty());
+        assert!(check(Path::new("/nonexistent")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn patched_dependencies_are_reported_and_counted() {
+        let mut project = Project::new();
+        project
+            .add_from(
+                "serde",
+                "1.0.0",
+                Some("git+https://github.com/org/serde#0123abc"),
+            )
+            .add("log", "0.4.20")
+            .depend("app", "serde", cargo_metadata::DependencyKind::Normal)
+            .depend("app", "log", cargo_metadata::DependencyKind::Normal);
+        project.file(
+            "app",
+            "Cargo.toml",
+            "[package]\nname = \"app\"\n\n\
+             [patch.crates-io]\n\
+             serde = { git = \"https://github.com/org/serde\" }\n",
+        );
+
+        let analysis = project.analyze(project.config()).await;
+
+        assert_eq!(analysis.patched_crates, 1);
+        let rules: Vec<&str> = analysis.security_issues["serde"]
+            .iter()
+            .filter_map(|issue| issue.rule_id.as_deref())
+            .collect();
+        assert!(rules.contains(&"GUARD036"));
+        assert!(!analysis
+            .security_issues
+            .get("log")
+            .is_some_and(|issues| issues
+                .iter()
+                .any(|issue| issue.rule_id.as_deref() == Some("GUARD036"))));
+    }
+}
@@ -0,0 +1,142 @@
+use cargo_metadata::Package;
+use semver::{Op, VersionReq};
+
+use crate::advisory::Advisory;
+use crate::models::{FixKind, SecurityIssue, Severity};
+
+/// Whether `req` pins a single version, as `=1.2.3` does.
+pub fn is_exact(req: &VersionReq) -> bool {
+    matches!(
+        req.comparators.as_slice(),
+        [comparator] if comparator.op == Op::Exact
+            && comparator.minor.is_some()
+            && comparator.patch.is_some()
+    )
+}
+
+/// Flags a dependency that workspace members, named in `pinned_by`, pin to
+/// its exact version. A pin on a version with a vulnerability advisory in
+/// `advisories` keeps cargo from resolving the patched release, so it is
+/// High and recommends the requirement to loosen it to; other pins are
+/// Info.
+pub fn check(
+    package: &Package,
+    pinned_by: &[String],
+    advisories: &[Advisory],
+) -> Option<SecurityIssue> {
+    if pinned_by.is_empty() {
+        return None;
+    }
+
+    let version = &package.version;
+    let pin = format!("{} = \"={}\"", package.name, version);
+    let members = pinned_by.join(", ");
+    let vulnerabilities: Vec<&Advisory> = advisories
+        .iter()
+        .filter(|advisory| advisory.informational.is_none())
+        .collect();
+    if vulnerabilities.is_empty() {
+        return Some(SecurityIssue {
+            severity: Severity::Info,
+            description: format!("{} pins {}", members, pin),
+            affected_versions: vec![version.to_string()],
+            rule_id: Some("GUARD049".to_string()),
+            ..Default::default()
+        });
+    }
+
+    // The pin is what rules the fix out, so it is not a requirement to keep
+    let fix = vulnerabilities
+        .iter()
+        .filter_map(|advisory| advisory.fix_version(version, &[]))
+        .max();
+    let ids: Vec<&str> = vulnerabilities
+        .iter()
+        .map(|advisory| advisory.id.as_str())
+        .collect();
+    let mut description = format!(
+        "{} pins {}, a version affected by {}",
+        members,
+        pin,
+        ids.join(", ")
+    );
+    match &fix {
+        Some(fix) => description.push_str(&format!(
+            "; loosen it to {} = \"{}\" to allow the patched release",
+            package.name, fix
+        )),
+        None => description.push_str("; no patched release is known"),
+    }
+
+    Some(SecurityIssue {
+        severity: Severity::High,
+        description,
+        affected_versions: vec![version.to_string()],
+        fix_version: fix.as_ref().map(ToString::to_string),
+        fix_kind: fix.as_ref().map(|fix| FixKind::between(version, fix)),
+        rule_id: Some("GUARD049".to_string()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use cargo_metadata::DependencyKind;
+
+    #[test]
+    fn only_full_exact_versions_are_pins() {
+        let exact = |req: &str| is_exact(&VersionReq::parse(req).unwrap());
+
+        assert!(exact("=1.2.3"));
+        assert!(exact("=0.4.0-alpha.1"));
+        assert!(!exact("=1.2"));
+        assert!(!exact("1.2.3"));
+        assert!(!exact(">=1.2.3, <1.3"));
+        assert!(!exact("*"));
+    }
+
+    #[tokio::test]
+    async fn pins_on_vulnerable_versions_recommend_the_patched_release() {
+        let mut project = Project::new();
+        project
+            .add("log", "0.4.0")
+            .add("serde", "1.0.0")
+            .depend("app", "log", DependencyKind::Normal)
+            .depend("app", "serde", DependencyKind::Normal)
+            .require("app", "log", "=0.4.0")
+            .require("app", "serde", "=1.0.0");
+        project.advisory("log", "RUSTSEC-2024-0002", &[">= 0.4.1"]);
+
+        let analysis = project.analyze(project.config()).await;
+
+        let pin = |name: &str| {
+            analysis.security_issues[name]
+                .iter()
+                .find(|issue| issue.rule_id.as_deref() == Some("GUARD049"))
+                .cloned()
+                .unwrap()
+        };
+        let log = pin("log");
+        assert_eq!(log.severity, Severity::High);
+        assert_eq!(
+            log.description,
+            "app pins log = \"=0.4.0\", a version affected by RUSTSEC-2024-0002; \
+             loosen it to log = \"0.4.1\" to allow the patched release"
+        );
+        assert_eq!(log.fix_version.as_deref(), Some("0.4.1"));
+        assert_eq!(log.fix_kind, Some(FixKind::Patch));
+        let serde = pin("serde");
+        assert_eq!(serde.severity, Severity::Info);
+        assert_eq!(serde.description, "app pins serde = \"=1.0.0\"");
+    }
+
+    #[test]
+    fn unpinned_packages_are_not_reported() {
+        let mut project = Project::new();
+        project.add("serde", "1.0.0");
+
+        assert!(check(&project.package("serde"), &[], &[]).is_none());
+    }
+}
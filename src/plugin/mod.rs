@@ -0,0 +1,222 @@
+//! Checks loaded at runtime from dynamic libraries.
+//!
+//! A plugin is a `cdylib` crate that depends on `rustsec_guardian`,
+//! implements [`Check`] and declares itself with [`declare_plugin!`]:
+//!
+//! ```ignore
+//! use rustsec_guardian::cargo_metadata::Package;
+//! use rustsec_guardian::plugin::{Check, PluginRegistrar};
+//! use rustsec_guardian::SecurityIssue;
+//!
+//! struct NoLegacyCrypto;
+//!
+//! impl Check for NoLegacyCrypto {
+//!     fn name(&self) -> &str {
+//!         "no-legacy-crypto"
+//!     }
+//!
+//!     fn run(&self, package: &Package) -> Vec<SecurityIssue> {
+//!         Vec::new()
+//!     }
+//! }
+//!
+//! fn register(registrar: &mut PluginRegistrar) {
+//!     registrar.add_check(Box::new(NoLegacyCrypto));
+//! }
+//!
+//! rustsec_guardian::declare_plugin!(register);
+//! ```
+//!
+//! Plugins exchange Rust types with the host, which has no stable ABI: a
+//! plugin must be built with the same compiler and the same version of
+//! this crate as the `dep-sense` binary that loads it. Both versions are
+//! recorded in the declaration and checked before anything else in the
+//! plugin is touched; the compiler cannot be, so it is up to whoever
+//! builds the plugin. Memory allocated by a plugin is freed by the host,
+//! so neither may set a custom global allocator. A check that panics is
+//! skipped for that package. Loading a plugin runs its code with the
+//! permissions of `dep-sense`, so only configure directories you trust.
+
+use cargo_metadata::Package;
+use libloading::Library;
+use std::fmt;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+
+/// Version of the plugin interface: [`Check`], [`PluginRegistrar`] and
+/// [`PluginDeclaration`]. Bumped on any change to them.
+pub const API_VERSION: u32 = 1;
+
+/// Version of this crate, compared against the one a plugin was built with.
+pub const GUARDIAN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the static each plugin exports, see [`declare_plugin!`].
+const DECLARATION_SYMBOL: &[u8] = b"GUARDIAN_PLUGIN\0";
+
+/// A check run over every package of the resolved graph, next to the
+/// built-in scanner.
+pub trait Check: Send + Sync {
+    /// Name used in log messages
+    fn name(&self) -> &str;
+
+    /// Issues found in `package`. Give them a `rule_id` of their own so
+    /// that they can be disabled and suppressed like built-in findings.
+    fn run(&self, package: &Package) -> Vec<SecurityIssue>;
+}
+
+/// Collects the checks a plugin registers.
+#[derive(Default)]
+pub struct PluginRegistrar {
+    checks: Vec<Box<dyn GuardedCheck>>,
+}
+
+impl PluginRegistrar {
+    pub fn add_check(&mut self, check: Box<dyn Check>) {
+        // This runs in the plugin, so the guard's code is compiled into it
+        // and catches its panics with its own copy of std
+        self.checks.push(Box::new(CatchPanic(check)));
+    }
+}
+
+/// A registered check, as the host calls it.
+trait GuardedCheck: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// `None` if the check panicked.
+    fn try_run(&self, package: &Package) -> Option<Vec<SecurityIssue>>;
+}
+
+struct CatchPanic(Box<dyn Check>);
+
+impl GuardedCheck for CatchPanic {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn try_run(&self, package: &Package) -> Option<Vec<SecurityIssue>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.run(package))).ok()
+    }
+}
+
+/// What a plugin exports; written by [`declare_plugin!`].
+#[derive(Debug, Clone, Copy)]
+pub struct PluginDeclaration {
+    pub api_version: u32,
+    pub guardian_version: &'static str,
+    pub register: fn(&mut PluginRegistrar),
+}
+
+/// Declares the plugin entry point; `$register` is a
+/// `fn(&mut PluginRegistrar)` that adds the plugin's checks.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($register:expr) => {
+        #[no_mangle]
+        pub static GUARDIAN_PLUGIN: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                api_version: $crate::plugin::API_VERSION,
+                guardian_version: $crate::plugin::GUARDIAN_VERSION,
+                register: $register,
+            };
+    };
+}
+
+/// The checks of every loaded plugin.
+#[derive(Default)]
+pub struct Plugins {
+    // Dropped before the libraries their code lives in
+    checks: Vec<Box<dyn GuardedCheck>>,
+    libraries: Vec<Library>,
+}
+
+impl Plugins {
+    /// Loads every dynamic library (`.so`, `.dylib` or `.dll`) in `dir`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|err| GuardianError::io(dir, err))? {
+            let path = entry.map_err(|err| GuardianError::io(dir, err))?.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut plugins = Self::default();
+        for path in paths {
+            plugins
+                .load_library(&path)
+                .map_err(|source| GuardianError::Plugin {
+                    path: path.clone(),
+                    source,
+                })?;
+        }
+        info!(
+            "Loaded {} plugin checks from {}",
+            plugins.checks.len(),
+            dir.display()
+        );
+        Ok(plugins)
+    }
+
+    fn load_library(&mut self, path: &Path) -> std::result::Result<(), crate::error::BoxError> {
+        // SAFETY: loading runs the library's initializers; plugins are
+        // trusted by configuration, see the module documentation
+        let library = unsafe { Library::new(path)? };
+        // SAFETY: the symbol is the static written by `declare_plugin!`;
+        // nothing but the two version fields is read before they match
+        let declaration = unsafe { **library.get::<*const PluginDeclaration>(DECLARATION_SYMBOL)? };
+        if declaration.api_version != API_VERSION {
+            return Err(format!(
+                "plugin interface version {} is not supported (expected {})",
+                declaration.api_version, API_VERSION
+            )
+            .into());
+        }
+        if declaration.guardian_version != GUARDIAN_VERSION {
+            return Err(format!(
+                "built against version {}, expected {}",
+                declaration.guardian_version, GUARDIAN_VERSION
+            )
+            .into());
+        }
+
+        let mut registrar = PluginRegistrar::default();
+        (declaration.register)(&mut registrar);
+        self.checks.extend(registrar.checks);
+        self.libraries.push(library);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Runs every check over `package`. A check that panics is reported
+    /// and skipped rather than aborting the analysis.
+    pub fn run(&self, package: &Package) -> Vec<SecurityIssue> {
+        let mut issues = Vec::new();
+        for check in &self.checks {
+            match check.try_run(package) {
+                Some(found) => issues.extend(found),
+                None => warn!("Plugin check {} panicked on {}", check.name(), package.name),
+            }
+        }
+        issues
+    }
+}
+
+impl fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.checks.iter().map(|check| check.name()))
+            .finish()
+    }
+}
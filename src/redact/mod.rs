@@ -0,0 +1,74 @@
+use regex::Regex;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::models::SecurityIssue;
+
+/// Rewrites the absolute paths in findings so that reports do not reveal
+/// user names or machine layout, for `--redact-paths`: registry sources
+/// become `<registry>/crate-1.2.3/...`, git checkouts `<git>/...`, paths
+/// in the workspace relative ones, and other paths under the home
+/// directory `~/...`. Paths elsewhere are kept.
+#[derive(Debug)]
+pub struct PathRedactor {
+    replacements: Vec<(Regex, &'static str)>,
+}
+
+impl PathRedactor {
+    pub fn new(workspace_root: &Path) -> Self {
+        let home = dirs::home_dir();
+        let cargo_home = env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".cargo")));
+
+        let sep = r"[/\\]";
+        // Only whole path components match; the character after the path
+        // is kept as `$end`
+        let prefix = |path: &Path, rest: &str| {
+            let path = regex::escape(path.to_string_lossy().trim_end_matches(['/', '\\']));
+            Regex::new(&format!(r"{}{}(?P<end>[/\\]|[^\w.\-]|$)", path, rest)).unwrap()
+        };
+
+        let mut replacements = Vec::new();
+        if let Some(cargo_home) = &cargo_home {
+            let registry = format!(r"{sep}registry{sep}src{sep}[^/\\]+");
+            let git = format!(r"{sep}git{sep}checkouts");
+            replacements.push((prefix(cargo_home, &registry), "<registry>$end"));
+            replacements.push((prefix(cargo_home, &git), "<git>$end"));
+            replacements.push((prefix(cargo_home, ""), "<cargo-home>$end"));
+        }
+        // Relative paths are left as they are
+        if workspace_root.is_absolute() {
+            let root = workspace_root.to_string_lossy();
+            let root = regex::escape(root.trim_end_matches(['/', '\\']));
+            replacements.push((Regex::new(&format!("{}{}", root, sep)).unwrap(), ""));
+            replacements.push((prefix(workspace_root, ""), ".$end"));
+        }
+        if let Some(home) = &home {
+            replacements.push((prefix(home, ""), "~$end"));
+        }
+        Self { replacements }
+    }
+
+    /// `text` with every absolute path it contains redacted.
+    pub fn text(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (regex, replacement)| {
+                regex.replace_all(&text, *replacement).into_owned()
+            })
+    }
+
+    pub fn path(&self, path: &Path) -> PathBuf {
+        PathBuf::from(self.text(&path.to_string_lossy()))
+    }
+
+    /// Redacts the locations of `issue` and the paths its description
+    /// mentions.
+    pub fn issue(&self, issue: &mut SecurityIssue) {
+        for location in issue.location.iter_mut().chain(&mut issue.locations) {
+            location.file = self.path(&location.file);
+        }
+        issue.description = self.text(&issue.description);
+    }
+}
@@ -0,0 +1,434 @@
+use cargo_metadata::{Package, PackageId};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+use crate::cache::{cache_root, is_fresh, read_cache, write_cache};
+use crate::error::{GuardianError, Result};
+use crate::models::{FixKind, SecurityIssue, Severity};
+use crate::retry::{retry, RetryPolicy};
+
+const INDEX_URL: &str = "https://index.crates.io";
+/// How long a fetched index entry is used before it is fetched again.
+pub const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// One line of a crate's sparse index file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexVersion {
+    pub vers: Version,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+/// Client for the crates.io sparse index with a per-crate on-disk cache.
+#[derive(Clone)]
+pub struct RegistryIndex {
+    client: reqwest::Client,
+    cache_dir: PathBuf,
+    policy: RetryPolicy,
+    cached_only: bool,
+}
+
+impl RegistryIndex {
+    pub fn new(policy: RetryPolicy) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("dep-sense/", env!("CARGO_PKG_VERSION")))
+            .timeout(policy.timeout)
+            .build()
+            .map_err(|err| GuardianError::network(INDEX_URL, err))?;
+
+        Ok(Self {
+            client,
+            cache_dir: cache_root()?.join("index"),
+            policy,
+            cached_only: false,
+        })
+    }
+
+    /// Answers from the on-disk cache alone, however old, for offline runs.
+    pub fn cached_only(mut self) -> Self {
+        self.cached_only = true;
+        self
+    }
+
+    /// Returns every published version of `name`.
+    ///
+    /// Responses are cached for an hour; when the registry cannot be reached
+    /// a stale cached copy is used instead.
+    pub async fn versions(&self, name: &str) -> Result<Vec<IndexVersion>> {
+        let cache_path = self.cache_dir.join(name.to_lowercase());
+        let url = format!("{}/{}", INDEX_URL, index_path(name));
+        if self.cached_only {
+            return parse_index(&url, &read_cache(&cache_path)?);
+        }
+        if is_fresh(&cache_path, CACHE_TTL) {
+            debug!("Using cached index entry for {}", name);
+            return parse_index(&url, &read_cache(&cache_path)?);
+        }
+
+        let what = format!("Fetching the index entry for {}", name);
+        match retry(&self.policy, &what, || self.fetch(&url)).await {
+            Ok(body) => {
+                if let Err(err) = write_cache(&cache_path, &body) {
+                    warn!("Failed to cache index entry for {}: {}", name, err);
+                }
+                parse_index(&url, &body)
+            }
+            Err(err) if cache_path.exists() => {
+                warn!("Using stale index entry for {}: {}", name, err);
+                parse_index(&url, &read_cache(&cache_path)?)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let response = self.client.get(url).send().await;
+        let response = response
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| GuardianError::network(url, err))?;
+        response
+            .text()
+            .await
+            .map_err(|err| GuardianError::network(url, err))
+    }
+
+    /// Fetches the index entries of every crates.io package among
+    /// `packages`, keyed by crate name; `what` names the check in warnings.
+    async fn registry_versions<'a>(
+        &self,
+        packages: &[&'a Package],
+        what: &str,
+    ) -> (Vec<&'a Package>, HashMap<String, Vec<IndexVersion>>) {
+        let registry_packages: Vec<&Package> = packages
+            .iter()
+            .copied()
+            .filter(|package| package.source.as_ref().is_some_and(|s| s.is_crates_io()))
+            .collect();
+
+        let names: HashSet<String> = registry_packages
+            .iter()
+            .map(|package| package.name.clone())
+            .collect();
+
+        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+        let mut tasks = JoinSet::new();
+        for name in names {
+            let index = self.clone();
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire().await;
+                let versions = index.versions(&name).await;
+                (name, versions)
+            });
+        }
+
+        let mut versions_by_name = HashMap::new();
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok((name, Ok(versions))) => {
+                    versions_by_name.insert(name, versions);
+                }
+                Ok((name, Err(err))) if self.cached_only => {
+                    debug!("Could not check {} for {}: {:#}", name, what, err)
+                }
+                Ok((name, Err(err))) => warn!("Could not check {} for {}: {:#}", name, what, err),
+                Err(err) => warn!("Index lookup task failed: {}", err),
+            }
+        }
+        (registry_packages, versions_by_name)
+    }
+
+    /// Reports every crates.io package whose resolved version is yanked,
+    /// suggesting the best non-yanked replacement.
+    pub async fn yanked_issues(&self, packages: &[&Package]) -> HashMap<PackageId, SecurityIssue> {
+        let (registry_packages, versions_by_name) =
+            self.registry_versions(packages, "yanked versions").await;
+
+        let mut issues = HashMap::new();
+        for package in registry_packages {
+            let Some(versions) = versions_by_name.get(&package.name) else {
+                continue;
+            };
+            let yanked = versions
+                .iter()
+                .any(|entry| entry.vers == package.version && entry.yanked);
+
+            if yanked {
+                let upgrade = suggest_upgrade(&package.version, versions);
+                issues.insert(
+                    package.id.clone(),
+                    SecurityIssue {
+                        severity: Severity::High,
+                        description: format!(
+                            "Version {} of {} has been yanked from crates.io",
+                            package.version, package.name
+                        ),
+                        affected_versions: vec![package.version.to_string()],
+                        fix_version: upgrade.as_ref().map(Version::to_string),
+                        fix_kind: upgrade
+                            .as_ref()
+                            .map(|version| FixKind::between(&package.version, version)),
+                        rule_id: Some("GUARD013".to_string()),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        issues
+    }
+
+    /// Finds every crates.io package whose resolved version is behind the
+    /// newest stable release.
+    pub async fn outdated(&self, packages: &[&Package]) -> HashMap<PackageId, Outdated> {
+        let (registry_packages, versions_by_name) =
+            self.registry_versions(packages, "newer releases").await;
+
+        let mut outdated = HashMap::new();
+        for package in registry_packages {
+            let Some(versions) = versions_by_name.get(&package.name) else {
+                continue;
+            };
+            let latest = versions
+                .iter()
+                .filter(|entry| !entry.yanked && entry.vers.pre.is_empty())
+                .map(|entry| &entry.vers)
+                .max();
+            if let Some(latest) = latest.filter(|latest| **latest > package.version) {
+                let outdated_package = Outdated {
+                    gap: FixKind::between(&package.version, latest),
+                    latest: latest.clone(),
+                };
+                outdated.insert(package.id.clone(), outdated_package);
+            }
+        }
+        outdated
+    }
+}
+
+/// A resolved version behind the newest stable release of its crate.
+#[derive(Debug, Clone)]
+pub struct Outdated {
+    pub latest: Version,
+    /// How far behind: a breaking release (`Major`), or only a minor or
+    /// patch one
+    pub gap: FixKind,
+}
+
+impl Outdated {
+    pub fn to_issue(&self, package: &Package) -> SecurityIssue {
+        let gap = match self.gap {
+            FixKind::Patch => "a patch release",
+            FixKind::Minor => "a minor version",
+            FixKind::Major => "a major version",
+        };
+        SecurityIssue {
+            severity: Severity::Info,
+            description: format!(
+                "{} {} is {} behind the latest release {}",
+                package.name, package.version, gap, self.latest
+            ),
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD040".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The number of outdated dependencies by how far they are behind.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutdatedCounts {
+    pub major: usize,
+    pub minor: usize,
+    pub patch: usize,
+}
+
+impl OutdatedCounts {
+    pub fn of<'a>(outdated: impl IntoIterator<Item = &'a Outdated>) -> Self {
+        let mut counts = Self::default();
+        for entry in outdated {
+            match entry.gap {
+                FixKind::Major => counts.major += 1,
+                FixKind::Minor => counts.minor += 1,
+                FixKind::Patch => counts.patch += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.major + self.minor + self.patch
+    }
+}
+
+/// Path of a crate's file within the index, e.g. `se/rd/serde` or `3/s/syn`.
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+/// Parses the index file fetched from `url`, or a cached copy of it.
+fn parse_index(url: &str, body: &str) -> Result<Vec<IndexVersion>> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|err| GuardianError::network(url, err)))
+        .collect()
+}
+
+/// The version cargo would pick instead: the newest non-yanked release that
+/// is semver-compatible with `current`, or else the newest non-yanked one.
+fn suggest_upgrade(current: &Version, versions: &[IndexVersion]) -> Option<Version> {
+    let candidates = versions
+        .iter()
+        .filter(|entry| !entry.yanked && entry.vers.pre.is_empty())
+        .map(|entry| &entry.vers);
+
+    let compatible = VersionReq::parse(&format!("^{}", current)).ok()?;
+    candidates
+        .clone()
+        .filter(|version| compatible.matches(version))
+        .max()
+        .or_else(|| candidates.max())
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use tempfile::TempDir;
+
+    /// An index answering from `entries`, cached by crate name, alone.
+    fn index(entries: &[(&str, &str)]) -> (TempDir, RegistryIndex) {
+        let dir = TempDir::new().unwrap();
+        for (name, body) in entries {
+            std::fs::write(dir.path().join(name), body).unwrap();
+        }
+        let index = RegistryIndex {
+            client: reqwest::Client::new(),
+            cache_dir: dir.path().to_path_buf(),
+            policy: RetryPolicy::default(),
+            cached_only: true,
+        };
+        (dir, index)
+    }
+
+    fn entries(versions: &[(&str, bool)]) -> String {
+        versions
+            .iter()
+            .map(|(vers, yanked)| format!("{{\"vers\":\"{}\",\"yanked\":{}}}\n", vers, yanked))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reports_a_yanked_version_with_a_non_yanked_upgrade() {
+        let mut project = Project::new();
+        project.add("log", "0.4.1").add("serde", "1.0.0");
+        let log = entries(&[
+            ("0.4.0", false),
+            ("0.4.1", true),
+            ("0.4.2", false),
+            ("0.4.3", true),
+            ("0.5.0", false),
+        ]);
+        let serde = entries(&[("1.0.0", false), ("1.0.1", true)]);
+        let (_dir, index) = index(&[("log", &log), ("serde", &serde)]);
+        let (log, serde) = (project.package("log"), project.package("serde"));
+
+        let issues = index.yanked_issues(&[&log, &serde]).await;
+
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[&log.id];
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD013"));
+        assert_eq!(issue.severity, Severity::High);
+        assert_eq!(issue.fix_version.as_deref(), Some("0.4.2"));
+        assert_eq!(issue.fix_kind, Some(FixKind::Patch));
+    }
+
+    #[test]
+    fn suggests_the_newest_release_when_no_compatible_one_is_left() {
+        let versions = parse_index(
+            INDEX_URL,
+            &entries(&[
+                ("1.0.0", true),
+                ("1.0.1", true),
+                ("2.0.0", false),
+                ("3.0.0-rc.1", false),
+            ]),
+        )
+        .unwrap();
+
+        let upgrade = suggest_upgrade(&Version::new(1, 0, 0), &versions);
+
+        assert_eq!(upgrade, Some(Version::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn index_paths_follow_the_name_length() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("cc"), "2/cc");
+        assert_eq!(index_path("syn"), "3/s/syn");
+        assert_eq!(index_path("Serde"), "se/rd/serde");
+    }
+
+    #[tokio::test]
+    async fn reports_how_far_behind_the_latest_release_a_version_is() {
+        let mut project = Project::new();
+        project
+            .add("log", "0.4.1")
+            .add("rand", "0.7.3")
+            .add("serde", "1.0.0")
+            .add("syn", "2.0.0");
+        let log = entries(&[("0.4.1", false), ("0.4.2", false), ("0.4.3", true)]);
+        let rand = entries(&[("0.7.3", false), ("0.8.5", false), ("0.9.0-beta.1", false)]);
+        let serde = entries(&[("1.0.0", false), ("1.2.0", false)]);
+        let syn = entries(&[("1.0.0", false), ("2.0.0", false)]);
+        let (_dir, index) = index(&[
+            ("log", &log),
+            ("rand", &rand),
+            ("serde", &serde),
+            ("syn", &syn),
+        ]);
+        let packages = ["log", "rand", "serde", "syn"].map(|name| project.package(name));
+
+        let outdated = index.outdated(&packages.iter().collect::<Vec<_>>()).await;
+
+        let gaps = packages.each_ref().map(|package| {
+            let outdated = outdated.get(&package.id)?;
+            Some((outdated.latest.to_string(), outdated.gap))
+        });
+        assert_eq!(
+            gaps,
+            [
+                Some(("0.4.2".to_string(), FixKind::Patch)),
+                Some(("0.8.5".to_string(), FixKind::Major)),
+                Some(("1.2.0".to_string(), FixKind::Minor)),
+                None,
+            ]
+        );
+        let issue = outdated[&packages[1].id].to_issue(&packages[1]);
+        assert_eq!(issue.severity, Severity::Info);
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD040"));
+        assert_eq!(
+            issue.description,
+            "rand 0.7.3 is a major version behind the latest release 0.8.5"
+        );
+
+        let counts = OutdatedCounts::of(outdated.values());
+        assert_eq!((counts.major, counts.minor, counts.patch), (1, 1, 1));
+        assert_eq!(counts.total(), 3);
+    }
+}
@@ -0,0 +1,207 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::Severity;
+
+use super::json::Versioned;
+use super::summary::Summary;
+use super::{summary_text, text, GroupBy, SeverityCounts, Style, SCHEMA_VERSION};
+
+/// One project of a batch scan.
+pub struct Project {
+    pub manifest_path: String,
+    pub outcome: Outcome,
+}
+
+pub enum Outcome {
+    /// The analysis, with the project's `--fail-on` threshold, if any, and
+    /// the number of issues at or above it
+    Analyzed {
+        analysis: Box<DependencyAnalysis>,
+        failing: Option<(Severity, usize)>,
+    },
+    /// Why the project could not be analyzed
+    Failed(String),
+}
+
+impl Project {
+    /// Whether the project was analyzed and passes its `--fail-on` threshold.
+    pub fn passed(&self) -> bool {
+        match &self.outcome {
+            Outcome::Analyzed { failing, .. } => failing.as_ref().is_none_or(|(_, n)| *n == 0),
+            Outcome::Failed(_) => false,
+        }
+    }
+}
+
+/// The totals over every analyzed project of a batch scan.
+#[derive(Serialize, Default)]
+struct BatchSummary {
+    projects: usize,
+    /// Projects failing their `--fail-on` threshold or the analysis
+    failing_projects: usize,
+    total_dependencies: usize,
+    issues: usize,
+    by_severity: SeverityCounts,
+    risk_score: u32,
+    failing_issues: usize,
+    passed: bool,
+}
+
+impl BatchSummary {
+    fn new(projects: &[Project]) -> Self {
+        let mut summary = Self {
+            projects: projects.len(),
+            ..Default::default()
+        };
+        for project in projects {
+            if !project.passed() {
+                summary.failing_projects += 1;
+            }
+            let Outcome::Analyzed { analysis, failing } = &project.outcome else {
+                continue;
+            };
+            let counts = SeverityCounts::of(analysis);
+            summary.total_dependencies += analysis.total_dependencies;
+            let issues: usize = analysis.security_issues.values().map(Vec::len).sum();
+            summary.issues += issues;
+            summary.by_severity.critical += counts.critical;
+            summary.by_severity.high += counts.high;
+            summary.by_severity.medium += counts.medium;
+            summary.by_severity.low += counts.low;
+            summary.by_severity.info += counts.info;
+            summary.risk_score += analysis.risk_score;
+            summary.failing_issues += failing.as_ref().map_or(0, |(_, count)| *count);
+        }
+        summary.passed = summary.failing_projects == 0;
+        summary
+    }
+}
+
+/// A project's entry in the JSON report.
+#[derive(Serialize)]
+struct ProjectReport<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<Summary>,
+    /// The project's JSON report, left out with `--summary`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analysis: Option<Versioned<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct BatchReport<'a> {
+    summary: BatchSummary,
+    projects: BTreeMap<&'a str, ProjectReport<'a>>,
+}
+
+/// Renders the text report of a batch scan: each project's report, or
+/// only its totals with `summary`, then the totals over all projects.
+pub fn batch_text(
+    projects: &[Project],
+    summary: bool,
+    style: Style,
+    group_by: GroupBy,
+) -> Result<String> {
+    let mut out = String::new();
+    for project in projects {
+        let heading = format!("Project {}:", project.manifest_path);
+        out.push_str(&style.title(&heading, &"=".repeat(heading.chars().count())));
+        match &project.outcome {
+            Outcome::Analyzed { analysis, failing } if summary => {
+                out.push_str(&summary_text(analysis, failing.as_ref(), style)?)
+            }
+            Outcome::Analyzed { analysis, .. } => out.push_str(&text(analysis, style, group_by)?),
+            Outcome::Failed(error) => writeln!(out, "Analysis failed: {}", error)?,
+        }
+    }
+
+    let totals = BatchSummary::new(projects);
+    let counts = &totals.by_severity;
+    out.push_str(&style.title("Batch Summary:", "=============="));
+    writeln!(
+        out,
+        "Projects: {} ({} failing)",
+        style.count(totals.projects),
+        style.count(totals.failing_projects)
+    )?;
+    writeln!(
+        out,
+        "Total Dependencies: {}",
+        style.count(totals.total_dependencies)
+    )?;
+    let severities = [
+        (Severity::Critical, "critical", counts.critical),
+        (Severity::High, "high", counts.high),
+        (Severity::Medium, "medium", counts.medium),
+        (Severity::Low, "low", counts.low),
+        (Severity::Info, "info", counts.info),
+    ]
+    .map(|(severity, label, count)| style.severity(&severity, format!("{} {}", label, count)));
+    writeln!(
+        out,
+        "Issues: {} ({})",
+        style.count(totals.issues),
+        severities.join(", ")
+    )?;
+    writeln!(out, "Risk Score: {}", style.count(totals.risk_score))?;
+
+    for project in projects {
+        let passed = project.passed();
+        let outcome = style.outcome(passed, if passed { "PASS" } else { "FAIL" });
+        let name = style.package(&project.manifest_path);
+        write!(out, "- {}: {}", name, outcome)?;
+        match &project.outcome {
+            Outcome::Analyzed { analysis, failing } => {
+                if let Some((threshold, count)) = failing.as_ref().filter(|(_, count)| *count > 0) {
+                    write!(out, " ({} issues at or above {})", count, threshold)?;
+                }
+                let issues: usize = analysis.security_issues.values().map(Vec::len).sum();
+                writeln!(out, ", {} issues, risk {}", issues, analysis.risk_score)?;
+            }
+            Outcome::Failed(error) => writeln!(out, ", analysis failed: {}", error)?,
+        }
+    }
+    let outcome = style.outcome(totals.passed, if totals.passed { "PASS" } else { "FAIL" });
+    writeln!(
+        out,
+        "Result: {} ({} of {} projects failing)",
+        outcome, totals.failing_projects, totals.projects
+    )?;
+
+    Ok(out)
+}
+
+/// Renders the JSON report of a batch scan: the totals over all projects,
+/// and each project's summary and analysis keyed by its manifest path.
+pub fn batch_json(projects: &[Project], summary: bool) -> Result<String> {
+    let report = BatchReport {
+        summary: BatchSummary::new(projects),
+        projects: projects
+            .iter()
+            .map(|project| {
+                let report = match &project.outcome {
+                    Outcome::Analyzed { analysis, failing } => ProjectReport {
+                        summary: Some(Summary::new(analysis, failing.as_ref())),
+                        analysis: (!summary).then_some(Versioned {
+                            schema_version: SCHEMA_VERSION,
+                            analysis,
+                        }),
+                        error: None,
+                    },
+                    Outcome::Failed(error) => ProjectReport {
+                        summary: None,
+                        analysis: None,
+                        error: Some(error),
+                    },
+                };
+                (project.manifest_path.as_str(), report)
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&report).map_err(GuardianError::report)? + "\n")
+}
@@ -0,0 +1,235 @@
+use serde::Serialize;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::Severity;
+
+#[derive(Serialize)]
+struct CodeQualityIssue {
+    description: String,
+    check_name: String,
+    fingerprint: String,
+    severity: &'static str,
+    location: CodeQualityLocation,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLocation {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+/// Renders a GitLab Code Quality report: a JSON array with an entry per
+/// issue. GitLab shows entries at paths of the repository, so findings in
+/// files outside the project, and those without a location, are placed on
+/// the lockfile next to `manifest_path`, their location kept in the
+/// description.
+pub fn gitlab(analysis: &DependencyAnalysis, manifest_path: &str) -> Result<String> {
+    let manifest_path = Path::new(manifest_path);
+    let lockfile = manifest_path.with_file_name("Cargo.lock");
+    let fallback = if lockfile.is_file() {
+        lockfile
+    } else {
+        manifest_path.to_path_buf()
+    };
+    let fallback = repository_path(&fallback).unwrap_or_else(|| fallback.display().to_string());
+
+    let issues: Vec<CodeQualityIssue> = analysis
+        .security_issues
+        .iter()
+        .flat_map(|(package, issues)| issues.iter().map(move |issue| (package, issue)))
+        .map(|(package, issue)| {
+            let check_name = issue
+                .rule_id
+                .as_deref()
+                .or(issue.advisory_id.as_deref())
+                .unwrap_or("dep-sense");
+            let mut description = format!("{}: {}", package, issue.description);
+            let in_project = issue.location.as_ref().and_then(|location| {
+                repository_path(&location.file).map(|path| (path, location.line))
+            });
+            let (path, line) = match (in_project, &issue.location) {
+                (Some(position), _) => position,
+                (None, location) => {
+                    if let Some(location) = location {
+                        description.push_str(&format!(" ({})", location));
+                    }
+                    (fallback.clone(), 1)
+                }
+            };
+            CodeQualityIssue {
+                description,
+                check_name: check_name.to_string(),
+                fingerprint: issue.fingerprint.clone(),
+                severity: severity(&issue.severity),
+                location: CodeQualityLocation {
+                    path,
+                    lines: Lines { begin: line },
+                },
+            }
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&issues).map_err(GuardianError::report)? + "\n")
+}
+
+/// GitLab's five levels, one per severity.
+fn severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "blocker",
+        Severity::High => "critical",
+        Severity::Medium => "major",
+        Severity::Low => "minor",
+        Severity::Info => "info",
+    }
+}
+
+/// `file` relative to the working directory, the root of the checkout in a
+/// GitLab job, with `/` separators; `None` when it is not a file there.
+fn repository_path(file: &Path) -> Option<String> {
+    let relative: PathBuf = if file.is_absolute() {
+        file.strip_prefix(env::current_dir().ok()?)
+            .ok()?
+            .to_path_buf()
+    } else {
+        file.to_path_buf()
+    };
+    if !relative.is_file() {
+        return None;
+    }
+    let components: Vec<_> = relative
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    Some(components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Location, SecurityIssue};
+    use crate::testing::Project;
+    use cargo_metadata::DependencyKind;
+    use jsonschema::{Draft, JSONSchema};
+    use serde_json::{json, Value};
+
+    /// The Code Quality report format GitLab documents: the fields its
+    /// widget reads, with their types and the allowed severities.
+    fn schema() -> Value {
+        json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["description", "check_name", "fingerprint", "severity", "location"],
+                "properties": {
+                    "description": { "type": "string" },
+                    "check_name": { "type": "string" },
+                    "fingerprint": { "type": "string", "minLength": 1 },
+                    "severity": { "enum": ["info", "minor", "major", "critical", "blocker"] },
+                    "location": {
+                        "type": "object",
+                        "required": ["path", "lines"],
+                        "properties": {
+                            "path": { "type": "string" },
+                            "lines": {
+                                "type": "object",
+                                "required": ["begin"],
+                                "properties": { "begin": { "type": "integer", "minimum": 1 } },
+                            },
+                        },
+                    },
+                },
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn output_matches_the_code_quality_format() {
+        let mut project = Project::new();
+        project
+            .add("log", "0.4.0")
+            .depend("app", "log", DependencyKind::Normal);
+        project.advisory("log", "RUSTSEC-2024-0002", &[">= 0.4.1"]);
+        let mut analysis = project.analyze(project.config()).await;
+        // A finding in a file of the checkout, the working directory
+        let file = env::current_dir().unwrap().join("src/lib.rs");
+        let mut issue = SecurityIssue {
+            severity: Severity::Medium,
+            description: "Unsafe block".to_string(),
+            rule_id: Some("GUARD001".to_string()),
+            location: Some(Location {
+                file,
+                line: 7,
+                column: 1,
+            }),
+            ..Default::default()
+        };
+        issue.fingerprint = issue.compute_fingerprint("app", &env::current_dir().unwrap());
+        analysis
+            .security_issues
+            .entry("app".to_string())
+            .or_default()
+            .push(issue);
+        let manifest = project.root().join("Cargo.toml");
+
+        let report: Value =
+            serde_json::from_str(&gitlab(&analysis, &manifest.display().to_string()).unwrap())
+                .unwrap();
+
+        let schema = schema();
+        let schema = JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(&schema)
+            .unwrap();
+        if let Err(errors) = schema.validate(&report) {
+            let errors: Vec<String> = errors.map(|err| err.to_string()).collect();
+            panic!("{:?}", errors);
+        }
+
+        let entry = |check_name: &str| {
+            report
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|entry| entry["check_name"] == check_name)
+                .unwrap()
+                .clone()
+        };
+        let unsafe_block = entry("GUARD001");
+        assert_eq!(unsafe_block["severity"], "major");
+        assert_eq!(unsafe_block["description"], "app: Unsafe block");
+        assert_eq!(
+            unsafe_block["location"],
+            json!({ "path": "src/lib.rs", "lines": { "begin": 7 } })
+        );
+        // Without a location in the checkout, on the manifest
+        let advisory = entry("RUSTSEC-2024-0002");
+        assert_eq!(advisory["severity"], "critical");
+        assert_eq!(
+            advisory["location"],
+            json!({ "path": manifest.display().to_string(), "lines": { "begin": 1 } })
+        );
+    }
+
+    #[test]
+    fn severities_map_to_gitlab_levels() {
+        let levels = [
+            Severity::Info,
+            Severity::Low,
+            Severity::Medium,
+            Severity::High,
+            Severity::Critical,
+        ]
+        .map(|level| severity(&level));
+
+        assert_eq!(levels, ["info", "minor", "major", "critical", "blocker"]);
+    }
+}
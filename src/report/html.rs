@@ -0,0 +1,223 @@
+use std::fmt::Write;
+use std::time::SystemTime;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::Result;
+use crate::models::{Confidence, Severity};
+
+use super::escape_xml;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem auto; max-width: 960px; color: #24292f; }
+h1 { margin-bottom: 0.25rem; }
+.meta { color: #57606a; margin-top: 0; }
+table { border-collapse: collapse; margin: 1rem 0; }
+th, td { border: 1px solid #d0d7de; padding: 0.4rem 0.8rem; text-align: left; }
+th { background: #f6f8fa; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.5rem 0; padding: 0.5rem 1rem; }
+summary { cursor: pointer; font-weight: 600; }
+ul.issues { list-style: none; padding-left: 0; }
+ul.issues li { margin: 0.75rem 0; }
+.detail { color: #57606a; font-size: 0.9em; margin-left: 1rem; }
+code { background: #f6f8fa; padding: 0.1rem 0.3rem; border-radius: 4px; }
+.badge { display: inline-block; min-width: 5rem; text-align: center; border-radius: 4px; padding: 0.1rem 0.4rem; font-size: 0.8em; font-weight: 700; color: #fff; }
+.critical { background: #8b0000; }
+.high { background: #cf222e; }
+.medium { background: #bf8700; }
+.low { background: #0969da; }
+.info { background: #6e7781; }
+"#;
+
+/// Renders a self-contained HTML report with inline styles.
+pub fn html(analysis: &DependencyAnalysis, manifest_path: &str) -> Result<String> {
+    let mut out = String::new();
+    let issues: Vec<_> = analysis.security_issues.values().flatten().collect();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(
+        out,
+        "<title>Dependency report: {}</title>",
+        escape_xml(manifest_path)
+    )?;
+    writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+
+    writeln!(out, "<h1>Dependency Analysis Report</h1>")?;
+    writeln!(
+        out,
+        "<p class=\"meta\"><code>{}</code> &middot; scanned {}</p>",
+        escape_xml(manifest_path),
+        humantime::format_rfc3339_seconds(SystemTime::now())
+    )?;
+
+    writeln!(out, "<h2>Summary</h2>\n<table>")?;
+    for (label, value) in [
+        ("Risk score", analysis.risk_score as usize),
+        ("Total dependencies", analysis.total_dependencies),
+        ("Direct dependencies", analysis.direct_dependencies.len()),
+        ("Duplicated crates", analysis.duplicate_crates),
+        ("Proc-macro crates", analysis.proc_macro_crates),
+        ("Patched crates", analysis.patched_crates),
+        ("Packages with issues", analysis.security_issues.len()),
+        ("Issues", issues.len()),
+        ("Suppressed issues", analysis.suppressed_issues),
+        (
+            "Accepted advisories",
+            analysis.accepted_issues.values().map(Vec::len).sum(),
+        ),
+    ] {
+        writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", label, value)?;
+    }
+    if let Some(outdated) = &analysis.outdated_crates {
+        writeln!(
+            out,
+            "<tr><th>Outdated crates</th><td>{} ({} major, {} minor, {} patch)</td></tr>",
+            outdated.total(),
+            outdated.major,
+            outdated.minor,
+            outdated.patch
+        )?;
+    }
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<h2>Severity Breakdown</h2>\n<table>")?;
+    for severity in [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ] {
+        let count = issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count();
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            badge(&severity),
+            count
+        )?;
+    }
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<h2>Issues</h2>")?;
+    if analysis.security_issues.is_empty() {
+        writeln!(out, "<p>No issues found.</p>")?;
+    }
+    for (package, issues) in &analysis.security_issues {
+        writeln!(
+            out,
+            "<details>\n<summary>{} ({} issues)</summary>\n<ul class=\"issues\">",
+            escape_xml(package),
+            issues.len()
+        )?;
+        for issue in issues {
+            write!(
+                out,
+                "<li>{} {}",
+                badge(&issue.severity),
+                escape_xml(&issue.description)
+            )?;
+            if let Some(score) = issue.cvss_score {
+                write!(out, " <span class=\"detail\">CVSS {:.1}</span>", score)?;
+            }
+            if issue.confidence < Confidence::High {
+                write!(
+                    out,
+                    " <span class=\"detail\">{} confidence</span>",
+                    issue.confidence
+                )?;
+            }
+            if let Some(severity) = &issue.escalated_from {
+                write!(
+                    out,
+                    " <span class=\"detail\">escalated from {}</span>",
+                    severity
+                )?;
+            }
+            if let Some(location) = &issue.location {
+                write!(
+                    out,
+                    "<div class=\"detail\"><code>{}</code></div>",
+                    escape_xml(&location.to_string())
+                )?;
+            }
+            if issue.dependency_path.len() > 1 {
+                let mut via = issue.dependency_path.join(" → ");
+                if issue.other_paths > 0 {
+                    via.push_str(&format!(" (+{} other paths)", issue.other_paths));
+                }
+                write!(out, "<div class=\"detail\">via {}</div>", escape_xml(&via))?;
+            }
+            if let Some(fix) = issue.fix_description() {
+                write!(out, "<div class=\"detail\">{}</div>", escape_xml(&fix))?;
+            }
+            writeln!(out, "</li>")?;
+        }
+        writeln!(out, "</ul>\n</details>")?;
+    }
+
+    if !analysis.accepted_issues.is_empty() {
+        writeln!(out, "<h2>Accepted Advisories</h2>\n<ul class=\"issues\">")?;
+        for (package, accepted) in &analysis.accepted_issues {
+            for accepted in accepted {
+                write!(
+                    out,
+                    "<li>{} <code>{}</code> {}",
+                    badge(&accepted.issue.severity),
+                    escape_xml(package),
+                    escape_xml(&accepted.issue.description)
+                )?;
+                if let Some(reason) = &accepted.reason {
+                    write!(out, "<div class=\"detail\">{}</div>", escape_xml(reason))?;
+                }
+                if let Some(expires) = &accepted.expires {
+                    write!(
+                        out,
+                        "<div class=\"detail\">Accepted until {}</div>",
+                        escape_xml(expires)
+                    )?;
+                }
+                writeln!(out, "</li>")?;
+            }
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    writeln!(out, "<h2>Dependency Tree</h2>")?;
+    writeln!(
+        out,
+        "<details>\n<summary>{} resolved packages</summary>\n<ul>",
+        analysis.dependency_tree.len()
+    )?;
+    for (package, edges) in &analysis.dependency_tree {
+        write!(out, "<li><code>{}</code>", escape_xml(package))?;
+        if !edges.is_empty() {
+            write!(out, "<ul>")?;
+            for edge in edges {
+                let kinds: Vec<String> = edge.kinds.iter().map(|kind| kind.to_string()).collect();
+                write!(
+                    out,
+                    "<li>{} <span class=\"detail\">{}</span></li>",
+                    escape_xml(&edge.package),
+                    kinds.join(", ")
+                )?;
+            }
+            write!(out, "</ul>")?;
+        }
+        writeln!(out, "</li>")?;
+    }
+    writeln!(out, "</ul>\n</details>")?;
+
+    writeln!(out, "</body>\n</html>")?;
+    Ok(out)
+}
+
+fn badge(severity: &Severity) -> String {
+    format!(
+        "<span class=\"badge {}\">{}</span>",
+        severity.to_string().to_lowercase(),
+        severity
+    )
+}
@@ -0,0 +1,164 @@
+use serde::Serialize;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+
+/// Version of the JSON report schema, `schema/report.schema.json`. Fields
+/// may be added without a bump; removing, renaming or retyping one bumps
+/// it, and the previous versions stay available through `--format-version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub(super) struct Versioned<'a> {
+    pub(super) schema_version: u32,
+    #[serde(flatten)]
+    pub(super) analysis: &'a DependencyAnalysis,
+}
+
+/// Renders the analysis as JSON in schema `version`, between 1 and
+/// [`SCHEMA_VERSION`].
+pub fn json(analysis: &DependencyAnalysis, version: u32) -> Result<String> {
+    match version {
+        1 => {
+            let report = Versioned {
+                schema_version: 1,
+                analysis,
+            };
+            Ok(serde_json::to_string_pretty(&report).map_err(GuardianError::report)? + "\n")
+        }
+        _ => Err(GuardianError::Config {
+            message: format!(
+                "Unsupported JSON format version {} (supported: 1 to {})",
+                version, SCHEMA_VERSION
+            ),
+            source: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        DependencyInfo, FixKind, Location, ResolvedPackage, SecurityIssue, Severity,
+    };
+    use jsonschema::{Draft, JSONSchema};
+    use serde_json::{json, Value};
+
+    const SCHEMA: &str = include_str!("../../schema/report.schema.json");
+
+    fn validate(report: &Value) -> Vec<String> {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let schema = JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(&schema)
+            .unwrap();
+        let errors = match schema.validate(report) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|err| format!("{} at {}", err, err.instance_path))
+                .collect(),
+        };
+        errors
+    }
+
+    #[test]
+    fn output_validates_against_the_schema() {
+        let package = |name: &str, version: &str, member: bool| ResolvedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            license: Some("MIT".to_string()),
+            source: None,
+            member,
+        };
+        let dependency = |name: &str, version: &str| DependencyInfo {
+            member: "app".to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            is_direct: true,
+            features: vec!["std".to_string()],
+            dependencies: Vec::new(),
+            edition: Some("2021".to_string()),
+            rust_version: None,
+            default_features: vec!["std".to_string()],
+        };
+        let advisory = SecurityIssue {
+            severity: Severity::High,
+            description: "RUSTSEC-2024-0002: log is vulnerable".to_string(),
+            affected_versions: vec!["0.4.0".to_string()],
+            fix_version: Some("0.4.1".to_string()),
+            fix_kind: Some(FixKind::Patch),
+            advisory_id: Some("RUSTSEC-2024-0002".to_string()),
+            cvss_score: Some(7.5),
+            cvss_vector: Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H".to_string()),
+            dependency_path: vec!["app".to_string(), "log".to_string()],
+            ..Default::default()
+        };
+        let finding = SecurityIssue {
+            severity: Severity::Medium,
+            description: "Build script runs external commands".to_string(),
+            rule_id: Some("GUARD009".to_string()),
+            location: Some(Location {
+                file: "build.rs".into(),
+                line: 3,
+                column: 5,
+            }),
+            ..Default::default()
+        };
+        let analysis: DependencyAnalysis = serde_json::from_value(json!({
+            "total_dependencies": 2,
+            "direct_dependencies": [dependency("log", "0.4.0"), dependency("cc", "1.0.0")],
+            "dependency_tree": {
+                "app@0.1.0": [
+                    { "package": "cc@1.0.0", "kinds": ["build"] },
+                    { "package": "log@0.4.0", "kinds": ["normal"] },
+                ],
+            },
+            "packages": {
+                "app@0.1.0": package("app", "0.1.0", true),
+                "cc@1.0.0": package("cc", "1.0.0", false),
+                "log@0.4.0": package("log", "0.4.0", false),
+            },
+            "security_issues": { "cc": [finding], "log": [advisory] },
+            "suppressed_issues": 0,
+            "duplicate_crates": 0,
+            "risk_score": 15,
+            "package_risk": { "cc": 5, "log": 10 },
+        }))
+        .unwrap();
+
+        let report: Value =
+            serde_json::from_str(&json(&analysis, SCHEMA_VERSION).unwrap()).unwrap();
+
+        assert_eq!(report["schema_version"], SCHEMA_VERSION);
+        assert_eq!(validate(&report), Vec::<String>::new());
+    }
+
+    #[test]
+    fn the_schema_rejects_other_shapes() {
+        let report = json!({
+            "schema_version": 2,
+            "total_dependencies": -1,
+            "direct_dependencies": [],
+            "dependency_tree": {},
+            "security_issues": {},
+        });
+
+        assert!(!validate(&report).is_empty());
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let analysis: DependencyAnalysis = serde_json::from_value(json!({
+            "total_dependencies": 0,
+            "direct_dependencies": [],
+            "dependency_tree": {},
+            "security_issues": {},
+            "suppressed_issues": 0,
+        }))
+        .unwrap();
+
+        assert!(json(&analysis, 0).is_err());
+        assert!(json(&analysis, SCHEMA_VERSION + 1).is_err());
+    }
+}
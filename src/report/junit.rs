@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::Result;
+
+use super::escape_xml;
+
+/// Renders JUnit XML: one `<testsuite>` per package, with a failing
+/// `<testcase>` per issue, or a single passing one for a clean package.
+pub fn junit(analysis: &DependencyAnalysis, elapsed: Duration) -> Result<String> {
+    let packages: BTreeSet<&str> = analysis
+        .dependency_tree
+        .keys()
+        .map(|key| key.rsplit_once('@').map_or(key.as_str(), |(name, _)| name))
+        .chain(analysis.security_issues.keys().map(String::as_str))
+        .collect();
+
+    let failures: usize = analysis.security_issues.values().map(Vec::len).sum();
+    let tests: usize = packages
+        .iter()
+        .map(|package| {
+            analysis
+                .security_issues
+                .get(*package)
+                .map_or(1, |issues| issues.len())
+        })
+        .sum();
+
+    let mut out = String::new();
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="dep-sense" tests="{}" failures="{}" time="{:.3}">"#,
+        tests,
+        failures,
+        elapsed.as_secs_f64()
+    )?;
+
+    for name in packages {
+        let package = escape_xml(name);
+        match analysis.security_issues.get(name) {
+            None => {
+                writeln!(
+                    out,
+                    r#"  <testsuite name="{}" tests="1" failures="0">"#,
+                    package
+                )?;
+                writeln!(
+                    out,
+                    r#"    <testcase name="no issues" classname="{}"/>"#,
+                    package
+                )?;
+            }
+            Some(issues) => {
+                writeln!(
+                    out,
+                    r#"  <testsuite name="{0}" tests="{1}" failures="{1}">"#,
+                    package,
+                    issues.len()
+                )?;
+                for issue in issues {
+                    let name = issue
+                        .rule_id
+                        .as_ref()
+                        .or(issue.advisory_id.as_ref())
+                        .map_or("issue", String::as_str);
+                    let name = match &issue.location {
+                        Some(location) => format!("{} at {}", name, location),
+                        None => name.to_string(),
+                    };
+
+                    let mut details = Vec::new();
+                    if issue.dependency_path.len() > 1 {
+                        details.push(format!("via {}", issue.dependency_path.join(" → ")));
+                    }
+                    if let Some(fix) = issue.fix_description() {
+                        details.push(fix);
+                    }
+
+                    writeln!(
+                        out,
+                        r#"    <testcase name="{}" classname="{}">"#,
+                        escape_xml(&name),
+                        package
+                    )?;
+                    writeln!(
+                        out,
+                        r#"      <failure type="{}" message="[{}] {}">{}</failure>"#,
+                        issue.severity,
+                        issue.severity,
+                        escape_xml(&issue.description),
+                        escape_xml(&details.join("\n"))
+                    )?;
+                    writeln!(out, "    </testcase>")?;
+                }
+            }
+        }
+        writeln!(out, "  </testsuite>")?;
+    }
+
+    writeln!(out, "</testsuites>")?;
+    Ok(out)
+}
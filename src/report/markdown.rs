@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::Result;
+use crate::models::{Confidence, SecurityIssue, Severity};
+
+use super::escape_xml;
+
+/// Renders a GitHub-flavored Markdown summary for pull request comments.
+/// Package sections that would take the report past `max_size` bytes are
+/// left out, with a note pointing to the full JSON report.
+pub fn markdown(
+    analysis: &DependencyAnalysis,
+    manifest_path: &str,
+    max_size: usize,
+) -> Result<String> {
+    let mut out = String::new();
+    let issues: Vec<_> = analysis.security_issues.values().flatten().collect();
+
+    writeln!(out, "## Dependency Analysis Report\n")?;
+    writeln!(
+        out,
+        "`{}` · risk score **{}** · {} dependencies ({} direct) · {} duplicated crates\n",
+        manifest_path,
+        analysis.risk_score,
+        analysis.total_dependencies,
+        analysis.direct_dependencies.len(),
+        analysis.duplicate_crates
+    )?;
+
+    writeln!(out, "| Severity | Issues |\n| --- | ---: |")?;
+    for severity in [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ] {
+        let count = issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count();
+        writeln!(out, "| {} | {} |", severity, count)?;
+    }
+    writeln!(out, "| **Total** | **{}** |\n", issues.len())?;
+    if analysis.suppressed_issues > 0 {
+        writeln!(out, "{} issues suppressed.\n", analysis.suppressed_issues)?;
+    }
+    let accepted: Vec<_> = analysis
+        .accepted_issues
+        .values()
+        .flatten()
+        .filter_map(|accepted| accepted.issue.advisory_id.as_deref())
+        .collect();
+    if !accepted.is_empty() {
+        writeln!(out, "Accepted advisories: {}\n", accepted.join(", "))?;
+    }
+
+    if analysis.security_issues.is_empty() {
+        writeln!(out, "No issues found.")?;
+        return Ok(out);
+    }
+
+    let mut sections = Vec::new();
+    for (package, issues) in &analysis.security_issues {
+        sections.push((package, section(analysis, package, issues)?));
+    }
+
+    writeln!(out, "### Issues\n")?;
+    // Room for the truncation note, so that it fits too
+    let budget = max_size.saturating_sub(out.len() + 200);
+    let mut shown = 0;
+    for (_, section) in &sections {
+        if out.len() + section.len() > budget {
+            break;
+        }
+        out.push_str(section);
+        shown += 1;
+    }
+    if shown < sections.len() {
+        let omitted: usize = sections[shown..]
+            .iter()
+            .map(|(package, _)| analysis.security_issues[*package].len())
+            .sum();
+        writeln!(
+            out,
+            "> Truncated: {} more packages with {} issues are not shown. See the full JSON report (`--output json`) for every finding.",
+            sections.len() - shown,
+            omitted
+        )?;
+    }
+    Ok(out)
+}
+
+fn section(
+    analysis: &DependencyAnalysis,
+    package: &str,
+    issues: &[SecurityIssue],
+) -> Result<String> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<details>\n<summary><b>{}</b>: {} issues (risk {})</summary>\n",
+        escape_xml(package),
+        issues.len(),
+        analysis
+            .package_risk
+            .get(package)
+            .copied()
+            .unwrap_or_default()
+    )?;
+    for issue in issues {
+        write!(out, "- **{}**", issue.severity)?;
+        if issue.confidence < Confidence::High {
+            write!(out, " _{} confidence_", issue.confidence)?;
+        }
+        write!(out, " {}", escape_xml(&issue.description))?;
+        let rules: Vec<&str> = issue
+            .rule_id
+            .iter()
+            .chain(&issue.advisory_id)
+            .chain(&issue.related_rules)
+            .map(String::as_str)
+            .collect();
+        if !rules.is_empty() {
+            write!(out, " (`{}`)", rules.join("`, `"))?;
+        }
+        writeln!(out)?;
+        if let Some(severity) = &issue.escalated_from {
+            writeln!(out, "  escalated from {}", severity)?;
+        }
+        if let Some(location) = &issue.location {
+            writeln!(out, "  at `{}`", location)?;
+        }
+        if issue.dependency_path.len() > 1 {
+            writeln!(
+                out,
+                "  via {}",
+                escape_xml(&issue.dependency_path.join(" → "))
+            )?;
+        }
+        if let Some(fix) = issue.fix_description() {
+            writeln!(out, "  {}", escape_xml(&fix))?;
+        }
+    }
+    writeln!(out, "\n</details>\n")?;
+    Ok(out)
+}
@@ -0,0 +1,456 @@
+use clap::ValueEnum;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Write};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::capabilities::CapabilitySet;
+use crate::error::Result;
+use crate::fixes::FixStatus;
+use crate::models::{Confidence, DependencyKind, SecurityIssue};
+use crate::rules;
+
+mod batch;
+mod gitlab;
+mod html;
+mod json;
+mod junit;
+mod markdown;
+mod ndjson;
+mod profile;
+mod scan;
+mod style;
+mod summary;
+mod toml;
+mod tree;
+
+pub use batch::{batch_json, batch_text, Outcome, Project};
+pub use gitlab::gitlab;
+pub use html::html;
+pub use json::{json, SCHEMA_VERSION};
+pub use junit::junit;
+pub use markdown::markdown;
+pub use ndjson::{ndjson_issue, ndjson_summary};
+pub use profile::profile;
+pub use scan::{scan_json, scan_text};
+pub use style::{ColorChoice, Style};
+pub use summary::{summary_json, summary_text, SeverityCounts, UnsafeCounts};
+pub use toml::toml;
+pub use tree::tree;
+
+/// Locations listed for a rolled-up finding; JSON output has all of them.
+const MAX_EXAMPLE_LOCATIONS: usize = 5;
+
+/// How the text report groups findings.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum GroupBy {
+    /// Under each package, in package order
+    #[default]
+    Package,
+    /// From Critical down to Info, each naming its package
+    Severity,
+    /// By rule or advisory ID, with a count for each
+    Rule,
+}
+
+/// The capabilities of the workspace members and their normal direct
+/// dependencies, members first, for the capability matrix.
+fn capability_rows(analysis: &DependencyAnalysis) -> Vec<(&str, &CapabilitySet)> {
+    let members = analysis
+        .packages
+        .iter()
+        .filter(|(_, package)| package.member)
+        .map(|(key, _)| key);
+    let mut dependencies = BTreeSet::new();
+    for member in members.clone() {
+        let edges = analysis.dependency_tree.get(member).into_iter().flatten();
+        dependencies.extend(
+            edges
+                .filter(|edge| edge.kinds.contains(&DependencyKind::Normal))
+                .map(|edge| &edge.package),
+        );
+    }
+    members
+        .chain(dependencies)
+        .filter_map(|key| Some((key.as_str(), analysis.capabilities.get(key)?)))
+        .collect()
+}
+
+/// Renders the human-readable console report, colored by `style`.
+pub fn text(analysis: &DependencyAnalysis, style: Style, group_by: GroupBy) -> Result<String> {
+    let mut out = String::new();
+
+    out.push_str(&style.banner("Dependency Analysis Results:", "=========================="));
+    writeln!(out, "Risk Score: {}", style.count(analysis.risk_score))?;
+    writeln!(
+        out,
+        "Total Dependencies: {}",
+        style.count(analysis.total_dependencies)
+    )?;
+    writeln!(
+        out,
+        "Duplicated Crates: {}",
+        style.count(analysis.duplicate_crates)
+    )?;
+    let unsafe_code = UnsafeCounts::of(analysis);
+    writeln!(
+        out,
+        "Unsafe Code: {} ({})",
+        style.count(unsafe_code.total()),
+        unsafe_code
+    )?;
+    writeln!(
+        out,
+        "Proc-macro Crates: {}",
+        style.count(analysis.proc_macro_crates)
+    )?;
+    writeln!(
+        out,
+        "Patched Crates: {}",
+        style.count(analysis.patched_crates)
+    )?;
+    if let Some(outdated) = &analysis.outdated_crates {
+        writeln!(
+            out,
+            "Outdated Crates: {} ({} a major version behind, {} minor, {} patch)",
+            style.count(outdated.total()),
+            outdated.major,
+            outdated.minor,
+            outdated.patch
+        )?;
+    }
+
+    writeln!(out, "\n{}", style.heading("Direct Dependencies:"))?;
+    let workspace = analysis
+        .direct_dependencies
+        .iter()
+        .any(|dep| dep.member != analysis.direct_dependencies[0].member);
+    for dep in &analysis.direct_dependencies {
+        let mut details = dep.version.clone();
+        if let Some(edition) = &dep.edition {
+            write!(details, ", edition {}", edition)?;
+        }
+        if let Some(rust_version) = &dep.rust_version {
+            write!(details, ", rust {}", rust_version)?;
+        }
+        if workspace {
+            let name = style.package(&dep.name);
+            writeln!(out, "- {} ({}) [{}]", name, details, dep.member)?;
+        } else {
+            writeln!(out, "- {} ({})", style.package(&dep.name), details)?;
+        }
+    }
+
+    let capabilities = capability_rows(analysis);
+    if !capabilities.is_empty() {
+        writeln!(out, "\n{}", style.heading("Capabilities:"))?;
+        let width = capabilities.iter().map(|(key, _)| key.len()).max();
+        let width = width.unwrap_or_default();
+        let columns = CapabilitySet::default().flags().map(|(name, _)| name);
+        writeln!(out, "  {:<width$}  {}", "Package", columns.join("  "))?;
+        for (key, set) in capabilities {
+            let mut row = format!("  {:<width$}", key);
+            for (name, enabled) in set.flags() {
+                let mark = if enabled { "yes" } else { "-" };
+                write!(row, "  {:<1$}", mark, name.len())?;
+            }
+            writeln!(out, "{}", row.trim_end())?;
+        }
+    }
+
+    if !analysis.security_issues.is_empty() {
+        if analysis.baseline.is_some() {
+            out.push_str(&style.title("New Security Issues:", "==================="));
+        } else {
+            out.push_str(&style.title("Security Issues Found:", "====================="));
+        }
+        match group_by {
+            GroupBy::Package => {
+                for (package, issues) in &analysis.security_issues {
+                    let risk = analysis
+                        .package_risk
+                        .get(package)
+                        .copied()
+                        .unwrap_or_default();
+                    writeln!(
+                        out,
+                        "\n{} has {} issues (risk {}):",
+                        style.package(package),
+                        style.count(issues.len()),
+                        style.count(risk)
+                    )?;
+                    for issue in issues {
+                        write_issue(&mut out, None, issue, style)?;
+                    }
+                }
+            }
+            GroupBy::Severity => {
+                let mut all: Vec<_> = analysis
+                    .security_issues
+                    .iter()
+                    .flat_map(|(package, issues)| issues.iter().map(move |issue| (package, issue)))
+                    .collect();
+                // Stable, so packages stay in order within a severity
+                all.sort_by(|(_, a), (_, b)| b.severity.cmp(&a.severity));
+                for group in all.chunk_by(|(_, a), (_, b)| a.severity == b.severity) {
+                    let severity = &group[0].1.severity;
+                    writeln!(
+                        out,
+                        "\n{} ({} issues):",
+                        style.severity(severity, severity),
+                        style.count(group.len())
+                    )?;
+                    for (package, issue) in group {
+                        write_issue(&mut out, Some(package), issue, style)?;
+                    }
+                }
+            }
+            GroupBy::Rule => {
+                let mut by_rule: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+                for (package, issues) in &analysis.security_issues {
+                    for issue in issues {
+                        let id = issue.rule_id.as_ref().or(issue.advisory_id.as_ref());
+                        let id = id.map_or("other", String::as_str);
+                        by_rule.entry(id).or_default().push((package, issue));
+                    }
+                }
+                for (id, group) in by_rule {
+                    let title = match rules::find(id) {
+                        Some(rule) => format!("{} {}", id, rule.title),
+                        None => id.to_string(),
+                    };
+                    writeln!(
+                        out,
+                        "\n{} ({} issues):",
+                        style.heading(title),
+                        style.count(group.len())
+                    )?;
+                    for (package, issue) in group {
+                        write_issue(&mut out, Some(package), issue, style)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if !analysis.accepted_issues.is_empty() {
+        out.push_str(&style.title("Accepted Advisories:", "===================="));
+        for (package, accepted) in &analysis.accepted_issues {
+            for accepted in accepted {
+                let package = style.package(package);
+                writeln!(out, "- {}: {}", package, accepted.issue.description)?;
+                if let Some(reason) = &accepted.reason {
+                    writeln!(out, "    reason: {}", reason)?;
+                }
+                if let Some(expires) = &accepted.expires {
+                    writeln!(out, "    accepted until {}", expires)?;
+                }
+            }
+        }
+    }
+
+    if let Some(checks) = &analysis.fix_checks {
+        out.push_str(&style.title("Upgrade Checks:", "==============="));
+        if checks.is_empty() {
+            writeln!(out, "No findings with a fix version; nothing to upgrade")?;
+        }
+        for (statuses, passed, heading) in [
+            (&[FixStatus::Applied][..], true, "Applied:"),
+            (&[FixStatus::Safe], true, "Safe to apply:"),
+            (
+                &[FixStatus::Breaking, FixStatus::Conflict],
+                false,
+                "Needs manual resolution:",
+            ),
+        ] {
+            let group: Vec<_> = checks
+                .iter()
+                .filter(|check| statuses.contains(&check.status))
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+            writeln!(out, "{}", style.outcome(passed, heading))?;
+            for check in group {
+                write!(
+                    out,
+                    "- {} {} → {} ({} upgrade, {} issues)",
+                    style.package(&check.package),
+                    check.version,
+                    check.fix_version,
+                    check.fix_kind,
+                    style.count(check.issues)
+                )?;
+                match check.cleared {
+                    Some(true) => write!(out, ", cleared")?,
+                    Some(false) => write!(out, ", still reported after the update")?,
+                    None => {}
+                }
+                if check.status == FixStatus::Breaking {
+                    write!(out, ": breaking, apply with --apply-fixes=major")?;
+                }
+                if let Some(conflict) = &check.conflict {
+                    write!(out, ": {}", conflict)?;
+                }
+                writeln!(out)?;
+            }
+        }
+    }
+
+    if let Some(diff) = &analysis.baseline {
+        if !diff.fixed.is_empty() {
+            out.push_str(&style.title("Fixed Since Baseline:", "===================="));
+            for (package, issues) in &diff.fixed {
+                for issue in issues {
+                    writeln!(
+                        out,
+                        "- {}: [{}] {}",
+                        style.package(package),
+                        style.severity(&issue.severity, &issue.severity),
+                        issue.description
+                    )?;
+                }
+            }
+        }
+
+        let fixed: usize = diff.fixed.values().map(Vec::len).sum();
+        let unchanged: usize = diff.unchanged.values().map(Vec::len).sum();
+        writeln!(
+            out,
+            "\nCompared to baseline: {} new, {} fixed, {} unchanged",
+            diff.new, fixed, unchanged
+        )?;
+    }
+
+    if let Some(unchanged) = &analysis.unchanged_packages {
+        writeln!(
+            out,
+            "\n{} dependencies unchanged since {}, sources not scanned",
+            unchanged.packages.len(),
+            unchanged.since
+        )?;
+        for package in &unchanged.packages {
+            writeln!(out, "  {}", style.package(package))?;
+        }
+    }
+
+    if let Some(incomplete) = &analysis.incomplete {
+        writeln!(
+            out,
+            "\nIncomplete: the {} scan timeout was reached, {} packages not scanned",
+            incomplete.timeout,
+            incomplete.unscanned_packages.len()
+        )?;
+        for package in &incomplete.unscanned_packages {
+            writeln!(out, "  {}", style.package(package))?;
+        }
+    }
+
+    if analysis.filtered_issues > 0 {
+        writeln!(
+            out,
+            "\n{} issues below the minimum severity or confidence not shown",
+            analysis.filtered_issues
+        )?;
+    }
+
+    if analysis.suppressed_issues > 0 {
+        writeln!(
+            out,
+            "\n{} issues suppressed by ignore rules",
+            analysis.suppressed_issues
+        )?;
+    }
+
+    Ok(out)
+}
+
+/// Escapes text for XML and HTML content and attribute values. Markdown
+/// decodes the entities too, so Markdown text shows the original characters.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Writes one finding of the text report with its details, prefixed with
+/// `package` when the findings are not grouped by package.
+fn write_issue(
+    out: &mut String,
+    package: Option<&str>,
+    issue: &SecurityIssue,
+    style: Style,
+) -> fmt::Result {
+    let mut label = match issue.cvss_score {
+        Some(score) => format!("{} {:.1}", issue.severity, score),
+        None => issue.severity.to_string(),
+    };
+    if issue.confidence < Confidence::High {
+        label = format!("{}, {} confidence", label, issue.confidence);
+    }
+    let label = style.severity(&issue.severity, label);
+    match package {
+        Some(package) => writeln!(
+            out,
+            "  - [{}] {}: {}",
+            label,
+            style.package(package),
+            issue.description
+        )?,
+        None => writeln!(out, "  - [{}] {}", label, issue.description)?,
+    }
+    if !issue.related_rules.is_empty() {
+        let rules: Vec<&str> = issue
+            .rule_id
+            .iter()
+            .chain(&issue.related_rules)
+            .map(String::as_str)
+            .collect();
+        writeln!(out, "    rules: {}", rules.join(", "))?;
+    }
+    if let Some(informational) = &issue.informational {
+        writeln!(out, "    informational advisory: {}", informational)?;
+    }
+    if let Some(severity) = &issue.escalated_from {
+        writeln!(out, "    escalated from {}", severity)?;
+    }
+    if issue.locations.is_empty() {
+        if let Some(location) = &issue.location {
+            writeln!(out, "    --> {}", location)?;
+        }
+    } else {
+        let files: BTreeSet<_> = issue
+            .locations
+            .iter()
+            .map(|location| &location.file)
+            .collect();
+        writeln!(
+            out,
+            "    found {} times in {} files",
+            issue.locations.len(),
+            files.len()
+        )?;
+        for location in issue.locations.iter().take(MAX_EXAMPLE_LOCATIONS) {
+            writeln!(out, "    --> {}", location)?;
+        }
+        if issue.locations.len() > MAX_EXAMPLE_LOCATIONS {
+            writeln!(
+                out,
+                "    ... and {} more",
+                issue.locations.len() - MAX_EXAMPLE_LOCATIONS
+            )?;
+        }
+    }
+    if issue.dependency_path.len() > 1 {
+        let mut via = issue.dependency_path.join(" → ");
+        if issue.other_paths > 0 {
+            via.push_str(&format!(" (+{} other paths)", issue.other_paths));
+        }
+        writeln!(out, "    via {}", via)?;
+    }
+    if let Some(fix) = issue.fix_description() {
+        writeln!(out, "    {}", fix)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+use crate::registry::OutdatedCounts;
+
+/// One line of NDJSON output, tagged with its `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record<'a> {
+    Issue {
+        package: &'a str,
+        #[serde(flatten)]
+        issue: &'a SecurityIssue,
+    },
+    Summary {
+        total_dependencies: usize,
+        direct_dependencies: usize,
+        issues: usize,
+        risk_score: u32,
+        duplicate_crates: usize,
+        proc_macro_crates: usize,
+        patched_crates: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        outdated_crates: Option<&'a OutdatedCounts>,
+        suppressed_issues: usize,
+        filtered_issues: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fixed_since_baseline: Option<usize>,
+    },
+}
+
+/// Writes an issue record and flushes, so that consumers see it at once.
+pub fn ndjson_issue(out: &mut dyn Write, package: &str, issue: &SecurityIssue) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, &Record::Issue { package, issue })?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+/// Renders the record that ends an NDJSON stream, with the totals of the
+/// finished analysis.
+pub fn ndjson_summary(analysis: &DependencyAnalysis) -> Result<String> {
+    let record = Record::Summary {
+        total_dependencies: analysis.total_dependencies,
+        direct_dependencies: analysis.direct_dependencies.len(),
+        issues: analysis.security_issues.values().map(Vec::len).sum(),
+        risk_score: analysis.risk_score,
+        duplicate_crates: analysis.duplicate_crates,
+        proc_macro_crates: analysis.proc_macro_crates,
+        patched_crates: analysis.patched_crates,
+        outdated_crates: analysis.outdated_crates.as_ref(),
+        suppressed_issues: analysis.suppressed_issues,
+        filtered_issues: analysis.filtered_issues,
+        fixed_since_baseline: analysis
+            .baseline
+            .as_ref()
+            .map(|diff| diff.fixed.values().map(Vec::len).sum()),
+    };
+    Ok(serde_json::to_string(&record).map_err(GuardianError::report)? + "\n")
+}
@@ -0,0 +1,57 @@
+use std::fmt::Write;
+
+use crate::analyzer::Timings;
+use crate::error::Result;
+
+/// Packages listed in the profile, slowest first.
+const SLOWEST_PACKAGES: usize = 10;
+
+/// Renders the `--profile` breakdown: the stages and the packages that took
+/// the most scan time.
+pub fn profile(timings: &Timings) -> Result<String> {
+    let mut out = String::new();
+    let cpu_ms = timings.cpu_ms();
+
+    writeln!(out, "\nScan Profile:")?;
+    writeln!(out, "=============")?;
+    writeln!(
+        out,
+        "Scanned {} packages in {:.1} ms ({:.1} ms summed over packages)",
+        timings.packages.len(),
+        timings.wall_ms,
+        cpu_ms
+    )?;
+
+    writeln!(out, "\nStages:")?;
+    let mut stages: Vec<_> = timings.stages.iter().collect();
+    stages.sort_by(|a, b| b.1.total_cmp(a.1));
+    for (stage, ms) in stages {
+        let share = if cpu_ms > 0.0 {
+            ms / cpu_ms * 100.0
+        } else {
+            0.0
+        };
+        writeln!(out, "  {:<20} {:>10.1} ms {:>5.1}%", stage, ms, share)?;
+    }
+
+    writeln!(out, "\nSlowest Packages:")?;
+    let mut packages: Vec<_> = timings.packages.iter().collect();
+    packages.sort_by(|a, b| b.1.total_ms.total_cmp(&a.1.total_ms));
+    for (package, package_timings) in packages.into_iter().take(SLOWEST_PACKAGES) {
+        let slowest = package_timings
+            .stages
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1));
+        write!(
+            out,
+            "  {:<40} {:>10.1} ms",
+            package, package_timings.total_ms
+        )?;
+        match slowest {
+            Some((stage, ms)) => writeln!(out, " (mostly {}, {:.1} ms)", stage, ms)?,
+            None => writeln!(out)?,
+        }
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,56 @@
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+
+use super::{write_issue, Style};
+
+#[derive(Serialize)]
+struct ScanReport<'a> {
+    path: &'a str,
+    issues: &'a [SecurityIssue],
+}
+
+/// Renders the findings of a source scan of `path`, in file order.
+pub fn scan_text(path: &str, issues: &[SecurityIssue], style: Style) -> Result<String> {
+    let mut out = String::new();
+    out.push_str(&style.banner("Source Scan Results:", "===================="));
+    writeln!(out, "Path: {}", style.package(path))?;
+
+    let severities = [
+        (Severity::Critical, "critical"),
+        (Severity::High, "high"),
+        (Severity::Medium, "medium"),
+        (Severity::Low, "low"),
+        (Severity::Info, "info"),
+    ]
+    .map(|(severity, label)| {
+        let count = issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count();
+        style.severity(&severity, format!("{} {}", label, count))
+    });
+    writeln!(
+        out,
+        "Issues: {} ({})",
+        style.count(issues.len()),
+        severities.join(", ")
+    )?;
+
+    if !issues.is_empty() {
+        out.push_str(&style.title("Security Issues Found:", "====================="));
+        writeln!(out)?;
+        for issue in issues {
+            write_issue(&mut out, None, issue, style)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Renders the findings of a source scan of `path` as JSON.
+pub fn scan_json(path: &str, issues: &[SecurityIssue]) -> Result<String> {
+    let report = ScanReport { path, issues };
+    Ok(serde_json::to_string_pretty(&report).map_err(GuardianError::report)? + "\n")
+}
@@ -0,0 +1,157 @@
+use clap::ValueEnum;
+use std::env;
+use std::fmt::Display;
+
+use crate::models::Severity;
+
+/// When to color the text reports, for `--color`.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Color output to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output that goes to a terminal if `terminal`.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The color scheme of the text reports, and whether they carry decorative
+/// headers. A disabled style returns every text unchanged, so uncolored
+/// reports are the same as without styling.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Style {
+    enabled: bool,
+    quiet: bool,
+}
+
+impl Style {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            quiet: false,
+        }
+    }
+
+    /// Leaves out the decorative headers, for `--quiet`.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// The title of a report with its underline, or nothing when quiet.
+    pub fn banner(&self, text: &str, underline: &str) -> String {
+        if self.quiet {
+            String::new()
+        } else {
+            format!("\n{}\n{}\n", self.heading(text), underline)
+        }
+    }
+
+    /// A section heading after a blank line, underlined unless quiet.
+    pub fn title(&self, text: &str, underline: &str) -> String {
+        if self.quiet {
+            format!("\n{}\n", self.heading(text))
+        } else {
+            format!("\n{}\n{}\n", self.heading(text), underline)
+        }
+    }
+
+    /// A severity label, colored by how severe it is.
+    pub fn severity(&self, severity: &Severity, text: impl Display) -> String {
+        let code = match severity {
+            Severity::Critical => "1;31",
+            Severity::High => "31",
+            Severity::Medium => "33",
+            Severity::Low => "34",
+            Severity::Info => "2",
+        };
+        self.paint(code, text)
+    }
+
+    pub fn package(&self, text: impl Display) -> String {
+        self.paint("1;35", text)
+    }
+
+    pub fn count(&self, text: impl Display) -> String {
+        self.paint("36", text)
+    }
+
+    pub fn heading(&self, text: impl Display) -> String {
+        self.paint("1", text)
+    }
+
+    /// The outcome of a `--fail-on` check, green if it passed.
+    pub fn outcome(&self, passed: bool, text: impl Display) -> String {
+        self.paint(if passed { "1;32" } else { "1;31" }, text)
+    }
+
+    fn paint(&self, code: &str, text: impl Display) -> String {
+        if self.enabled {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_color_turns_off_automatic_color_only() {
+        // One test, since the environment is shared between threads
+        env::set_var("NO_COLOR", "1");
+        assert!(!ColorChoice::Auto.enabled(true));
+        assert!(ColorChoice::Always.enabled(true));
+
+        // An empty value does not count
+        env::set_var("NO_COLOR", "");
+        assert!(ColorChoice::Auto.enabled(true));
+
+        env::remove_var("NO_COLOR");
+        assert!(ColorChoice::Auto.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+    }
+
+    #[test]
+    fn disabled_styles_return_text_unchanged() {
+        let style = Style::new(false);
+
+        assert_eq!(style.severity(&Severity::Critical, "CRITICAL"), "CRITICAL");
+        assert_eq!(style.package("log"), "log");
+        assert_eq!(style.count(3), "3");
+        assert_eq!(style.heading("Summary"), "Summary");
+        assert_eq!(style.outcome(false, "FAIL"), "FAIL");
+    }
+
+    #[test]
+    fn severities_are_colored_by_how_severe_they_are() {
+        let style = Style::new(true);
+        let codes = [
+            (Severity::Critical, "1;31"),
+            (Severity::High, "31"),
+            (Severity::Medium, "33"),
+            (Severity::Low, "34"),
+            (Severity::Info, "2"),
+        ];
+
+        for (severity, code) in codes {
+            assert_eq!(
+                style.severity(&severity, &severity),
+                format!("\x1b[{}m{}\x1b[0m", code, severity)
+            );
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use serde::Serialize;
+use std::fmt::{self, Write};
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::Severity;
+use crate::registry::OutdatedCounts;
+
+use super::Style;
+
+/// The totals of an analysis, without per-package detail.
+#[derive(Serialize)]
+pub(super) struct Summary {
+    total_dependencies: usize,
+    issues: usize,
+    by_severity: SeverityCounts,
+    /// Advisory findings, split into vulnerabilities and informational
+    /// advisories such as unmaintained crates
+    advisories: AdvisoryCounts,
+    /// Findings of each kind of unsafe code
+    unsafe_code: UnsafeCounts,
+    /// Dependencies providing a procedural macro
+    proc_macro_crates: usize,
+    /// Dependencies overridden by `[patch]` or `[replace]`
+    patched_crates: usize,
+    /// Dependencies behind their newest release, with `--outdated`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outdated_crates: Option<OutdatedCounts>,
+    risk_score: u32,
+    /// `--fail-on` threshold, if any
+    fail_on: Option<Severity>,
+    /// Issues at or above `fail_on`, whatever `--min-severity` hides
+    failing_issues: usize,
+    passed: bool,
+    #[serde(skip_serializing_if = "is_zero")]
+    filtered_issues: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    suppressed_issues: usize,
+    /// Dependencies left unscanned by `--scan-timeout`
+    #[serde(skip_serializing_if = "is_zero")]
+    unscanned_packages: usize,
+}
+
+/// The number of issues of each severity.
+#[derive(Serialize, Default)]
+pub struct SeverityCounts {
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    pub fn of(analysis: &DependencyAnalysis) -> Self {
+        let mut counts = Self::default();
+        for issue in analysis.security_issues.values().flatten() {
+            let count = match issue.severity {
+                Severity::Critical => &mut counts.critical,
+                Severity::High => &mut counts.high,
+                Severity::Medium => &mut counts.medium,
+                Severity::Low => &mut counts.low,
+                Severity::Info => &mut counts.info,
+            };
+            *count += 1;
+        }
+        counts
+    }
+}
+
+/// The number of findings of each unsafe code rule, counting the
+/// locations of grouped findings and the rules merged into another
+/// finding.
+#[derive(Serialize, Default)]
+pub struct UnsafeCounts {
+    /// `unsafe { ... }` blocks (GUARD001)
+    pub blocks: usize,
+    /// `unsafe impl Send` (GUARD052)
+    pub send_impls: usize,
+    /// `unsafe impl Sync` (GUARD053)
+    pub sync_impls: usize,
+    /// `unsafe fn` declarations (GUARD054)
+    pub functions: usize,
+    /// `unsafe trait` declarations (GUARD055)
+    pub traits: usize,
+    /// Raw pointer dereferences (GUARD056)
+    pub pointer_derefs: usize,
+}
+
+impl UnsafeCounts {
+    pub fn of(analysis: &DependencyAnalysis) -> Self {
+        let mut counts = Self::default();
+        for issue in analysis.security_issues.values().flatten() {
+            let locations = issue.locations.len().max(1);
+            let related = issue.related_rules.iter().map(|id| (id, 1));
+            for (id, found) in issue
+                .rule_id
+                .iter()
+                .map(|id| (id, locations))
+                .chain(related)
+            {
+                let count = match id.as_str() {
+                    "GUARD001" => &mut counts.blocks,
+                    "GUARD052" => &mut counts.send_impls,
+                    "GUARD053" => &mut counts.sync_impls,
+                    "GUARD054" => &mut counts.functions,
+                    "GUARD055" => &mut counts.traits,
+                    "GUARD056" => &mut counts.pointer_derefs,
+                    _ => continue,
+                };
+                *count += found;
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.blocks
+            + self.send_impls
+            + self.sync_impls
+            + self.functions
+            + self.traits
+            + self.pointer_derefs
+    }
+}
+
+impl fmt::Display for UnsafeCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} blocks, {} Send impls, {} Sync impls, {} functions, {} traits, {} pointer dereferences",
+            self.blocks,
+            self.send_impls,
+            self.sync_impls,
+            self.functions,
+            self.traits,
+            self.pointer_derefs
+        )
+    }
+}
+
+#[derive(Serialize, Default)]
+struct AdvisoryCounts {
+    vulnerabilities: usize,
+    informational: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl Summary {
+    pub(super) fn new(analysis: &DependencyAnalysis, failing: Option<&(Severity, usize)>) -> Self {
+        let mut advisories = AdvisoryCounts::default();
+        for issue in analysis.security_issues.values().flatten() {
+            if issue.informational.is_some() {
+                advisories.informational += 1;
+            } else if issue.advisory_id.is_some() {
+                advisories.vulnerabilities += 1;
+            }
+        }
+        let failing_issues = failing.map_or(0, |(_, count)| *count);
+
+        Self {
+            total_dependencies: analysis.total_dependencies,
+            issues: analysis.security_issues.values().map(Vec::len).sum(),
+            by_severity: SeverityCounts::of(analysis),
+            advisories,
+            unsafe_code: UnsafeCounts::of(analysis),
+            proc_macro_crates: analysis.proc_macro_crates,
+            patched_crates: analysis.patched_crates,
+            outdated_crates: analysis.outdated_crates.clone(),
+            risk_score: analysis.risk_score,
+            fail_on: failing.map(|(threshold, _)| threshold.clone()),
+            failing_issues,
+            passed: failing_issues == 0,
+            filtered_issues: analysis.filtered_issues,
+            suppressed_issues: analysis.suppressed_issues,
+            unscanned_packages: analysis
+                .incomplete
+                .as_ref()
+                .map_or(0, |incomplete| incomplete.unscanned_packages.len()),
+        }
+    }
+}
+
+/// Renders only the totals of the analysis and whether it passes the
+/// `--fail-on` threshold in `failing`, for `--summary`.
+pub fn summary_text(
+    analysis: &DependencyAnalysis,
+    failing: Option<&(Severity, usize)>,
+    style: Style,
+) -> Result<String> {
+    let summary = Summary::new(analysis, failing);
+    let counts = &summary.by_severity;
+    let mut out = String::new();
+
+    out.push_str(&style.banner(
+        "Dependency Analysis Summary:",
+        "===========================",
+    ));
+    writeln!(
+        out,
+        "Total Dependencies: {}",
+        style.count(summary.total_dependencies)
+    )?;
+    let severities = [
+        (Severity::Critical, "critical", counts.critical),
+        (Severity::High, "high", counts.high),
+        (Severity::Medium, "medium", counts.medium),
+        (Severity::Low, "low", counts.low),
+        (Severity::Info, "info", counts.info),
+    ]
+    .map(|(severity, label, count)| style.severity(&severity, format!("{} {}", label, count)));
+    writeln!(
+        out,
+        "Issues: {} ({})",
+        style.count(summary.issues),
+        severities.join(", ")
+    )?;
+    writeln!(
+        out,
+        "Advisories: {} vulnerabilities, {} informational",
+        style.count(summary.advisories.vulnerabilities),
+        style.count(summary.advisories.informational)
+    )?;
+    writeln!(
+        out,
+        "Unsafe Code: {} ({})",
+        style.count(summary.unsafe_code.total()),
+        summary.unsafe_code
+    )?;
+    writeln!(
+        out,
+        "Proc-macro Crates: {}",
+        style.count(summary.proc_macro_crates)
+    )?;
+    writeln!(
+        out,
+        "Patched Crates: {}",
+        style.count(summary.patched_crates)
+    )?;
+    if let Some(outdated) = &summary.outdated_crates {
+        writeln!(
+            out,
+            "Outdated Crates: {} ({} a major version behind, {} minor, {} patch)",
+            style.count(outdated.total()),
+            outdated.major,
+            outdated.minor,
+            outdated.patch
+        )?;
+    }
+    writeln!(out, "Risk Score: {}", style.count(summary.risk_score))?;
+    if summary.filtered_issues > 0 {
+        writeln!(
+            out,
+            "{} issues below the minimum severity not counted",
+            summary.filtered_issues
+        )?;
+    }
+    if summary.suppressed_issues > 0 {
+        writeln!(
+            out,
+            "{} issues suppressed by ignore rules",
+            summary.suppressed_issues
+        )?;
+    }
+    if summary.unscanned_packages > 0 {
+        writeln!(
+            out,
+            "Incomplete: {} packages not scanned before the scan timeout",
+            summary.unscanned_packages
+        )?;
+    }
+    let outcome = style.outcome(summary.passed, if summary.passed { "PASS" } else { "FAIL" });
+    match &summary.fail_on {
+        Some(threshold) if summary.passed => writeln!(
+            out,
+            "Result: {} (no issues at or above {})",
+            outcome, threshold
+        )?,
+        Some(threshold) => writeln!(
+            out,
+            "Result: {} ({} issues at or above {})",
+            outcome, summary.failing_issues, threshold
+        )?,
+        None => writeln!(out, "Result: {} (no --fail-on threshold)", outcome)?,
+    }
+
+    Ok(out)
+}
+
+/// Renders the summary object that `--summary` prints in JSON mode.
+pub fn summary_json(
+    analysis: &DependencyAnalysis,
+    failing: Option<&(Severity, usize)>,
+) -> Result<String> {
+    let json = serde_json::to_string_pretty(&Summary::new(analysis, failing))
+        .map_err(GuardianError::report)?;
+    Ok(json + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use cargo_metadata::DependencyKind;
+
+    #[tokio::test]
+    async fn counts_each_kind_of_unsafe_separately() {
+        let mut project = Project::new();
+        project
+            .add("ffi", "1.0.0")
+            .depend("app", "ffi", DependencyKind::Normal);
+        project.file(
+            "ffi",
+            "src/lib.rs",
+            "pub struct Handle(*mut u8);\n\
+             unsafe impl Send for Handle {}\n\
+             unsafe impl Sync for Handle {}\n\
+             pub unsafe trait Zeroable {}\n\
+             pub unsafe fn first(p: *const u8) -> u8 { *p.add(0) }\n\
+             pub unsafe fn second(p: *const u8) -> u8 { *p.add(1) }\n\
+             pub fn read(p: *const u8) -> u8 { unsafe { first(p) } }\n",
+        );
+        let analysis = project.analyze(project.config()).await;
+
+        let counts = UnsafeCounts::of(&analysis);
+
+        assert_eq!(
+            [
+                counts.blocks,
+                counts.send_impls,
+                counts.sync_impls,
+                counts.functions,
+                counts.traits,
+                counts.pointer_derefs,
+            ],
+            [1, 1, 1, 2, 1, 2]
+        );
+        assert_eq!(counts.total(), 8);
+        assert_eq!(
+            counts.to_string(),
+            "1 blocks, 1 Send impls, 1 Sync impls, 2 functions, 1 traits, 2 pointer dereferences"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::SecurityIssue;
+
+#[derive(Serialize)]
+struct Issue<'a> {
+    package: &'a str,
+    #[serde(flatten)]
+    issue: &'a SecurityIssue,
+}
+
+/// Renders the analysis as TOML. The per-package issue map becomes an
+/// `[[issue]]` array of tables, each entry naming its package.
+pub fn toml(analysis: &DependencyAnalysis) -> Result<String> {
+    let issues: Vec<Issue> = analysis
+        .security_issues
+        .iter()
+        .flat_map(|(package, issues)| issues.iter().map(move |issue| Issue { package, issue }))
+        .collect();
+
+    let mut report = ::toml::Table::try_from(analysis).map_err(GuardianError::report)?;
+    report.remove("security_issues");
+    report.insert(
+        "issue".to_string(),
+        ::toml::Value::try_from(issues).map_err(GuardianError::report)?,
+    );
+    ::toml::to_string(&report).map_err(GuardianError::report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Location, Severity};
+    use serde_json::json;
+
+    fn analysis() -> DependencyAnalysis {
+        let issue = |severity: Severity, rule: &str| SecurityIssue {
+            severity,
+            description: format!("Finding of {}", rule),
+            rule_id: Some(rule.to_string()),
+            location: Some(Location {
+                file: "src/lib.rs".into(),
+                line: 3,
+                column: 5,
+            }),
+            ..Default::default()
+        };
+        serde_json::from_value(json!({
+            "total_dependencies": 2,
+            "direct_dependencies": [],
+            "dependency_tree": {},
+            "security_issues": {
+                "log": [issue(Severity::Low, "GUARD009"), issue(Severity::High, "GUARD001")],
+                "smallvec": [issue(Severity::Critical, "GUARD005")],
+            },
+            "suppressed_issues": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_back_into_the_analysis() {
+        let analysis = analysis();
+
+        let mut report: ::toml::Table = toml(&analysis).unwrap().parse().unwrap();
+
+        // Regroup the issues by package
+        let mut security_issues = ::toml::Table::new();
+        let Some(::toml::Value::Array(issues)) = report.remove("issue") else {
+            panic!("no [[issue]] tables");
+        };
+        for mut issue in issues {
+            let table = issue.as_table_mut().unwrap();
+            let Some(::toml::Value::String(package)) = table.remove("package") else {
+                panic!("issue without a package");
+            };
+            // As displayed, like the other formats
+            let severity = table["severity"].as_str().unwrap();
+            assert_eq!(severity, severity.parse::<Severity>().unwrap().to_string());
+            security_issues
+                .entry(package)
+                .or_insert_with(|| ::toml::Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap()
+                .push(issue);
+        }
+        report.insert("security_issues".to_string(), security_issues.into());
+        let parsed: DependencyAnalysis = ::toml::Value::Table(report).try_into().unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&analysis).unwrap()
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::analyzer::DependencyTree;
+use crate::error::Result;
+use crate::models::DependencyKind;
+
+use super::Style;
+
+/// Renders the resolved dependency graph as an indented tree under each
+/// workspace member, like `cargo tree`. A package is expanded the first
+/// time it appears and marked `(*)` after that.
+pub fn tree(tree: &DependencyTree, style: Style) -> Result<String> {
+    let mut out = String::new();
+    let mut expanded = HashSet::new();
+    for (index, root) in tree.roots.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", style.package(root))?;
+        expanded.insert(root.as_str());
+        write_children(&mut out, tree, root, "", &mut expanded, style)?;
+    }
+    Ok(out)
+}
+
+fn write_children<'a>(
+    out: &mut String,
+    tree: &'a DependencyTree,
+    package: &str,
+    prefix: &str,
+    expanded: &mut HashSet<&'a str>,
+    style: Style,
+) -> Result<()> {
+    let edges = tree
+        .edges
+        .get(package)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (index, edge) in edges.iter().enumerate() {
+        let last = index + 1 == edges.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        write!(out, "{}{}{}", prefix, branch, style.package(&edge.package))?;
+        // Dev- and build-only edges are called out
+        if !edge.kinds.is_empty() && !edge.kinds.contains(&DependencyKind::Normal) {
+            let kinds: Vec<String> = edge.kinds.iter().map(ToString::to_string).collect();
+            write!(out, " [{}]", kinds.join(", "))?;
+        }
+
+        if expanded.insert(edge.package.as_str()) {
+            writeln!(out)?;
+            let prefix = format!("{}{}", prefix, indent);
+            write_children(out, tree, &edge.package, &prefix, expanded, style)?;
+        } else if tree
+            .edges
+            .get(&edge.package)
+            .is_some_and(|edges| !edges.is_empty())
+        {
+            writeln!(out, " (*)")?;
+        } else {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
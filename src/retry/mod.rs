@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+use crate::error::Result;
+
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// How often and how long network operations are attempted.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Limit for a single attempt
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Marks a failure as worth retrying when its type alone can't tell, such
+/// as a failed `git` invocation.
+#[derive(Debug)]
+pub struct Transient(pub String);
+
+impl fmt::Display for Transient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Transient {}
+
+/// Runs `operation` until it succeeds, fails with a non-retryable error, or
+/// the policy's retries are used up, backing off exponentially with jitter
+/// between attempts.
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, what: &str, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.retries && is_retryable(&err) => {
+                let delay = backoff(attempt);
+                attempt += 1;
+                debug!(
+                    "{} failed (attempt {} of {}), retrying in {:?}: {}",
+                    what,
+                    attempt,
+                    policy.retries + 1,
+                    delay,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => {
+                if attempt > 0 {
+                    debug!("{} failed after {} attempts", what, attempt + 1);
+                }
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Timeouts, connection failures, 5xx and 429 responses are retried;
+/// anything else (404s, malformed data) would fail the same way again.
+fn is_retryable(err: &(dyn Error + 'static)) -> bool {
+    let mut causes = std::iter::successors(Some(err), |&cause| cause.source());
+    causes.any(|cause| {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout()
+                || err.is_connect()
+                || err.status().is_some_and(|status| {
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                });
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::Interrupted
+            );
+        }
+        cause.is::<tokio::time::error::Elapsed>() || cause.is::<Transient>()
+    })
+}
+
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_DELAY);
+
+    // Up to 50% jitter so parallel lookups don't retry in lockstep
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.subsec_nanos());
+    let jitter = delay.as_millis() as u64 / 2 * u64::from(nanos % 1000) / 1000;
+    delay + Duration::from_millis(jitter)
+}
@@ -0,0 +1,574 @@
+use serde::Serialize;
+
+use crate::models::{Confidence, Severity};
+
+mod explain;
+
+pub use explain::{Explanation, EXPLANATIONS};
+
+/// What a rule looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// Regex over a package's Rust sources
+    Source,
+    /// Regex or path tracking over a package's build script
+    BuildScript,
+    /// Hard-coded secrets in sources (`--secrets`)
+    Secrets,
+    /// Package metadata and dependency declarations
+    Package,
+    /// Resolved graph and registry data
+    Dependencies,
+}
+
+/// A built-in check. Source and most build script rules carry the regex
+/// the scanner runs, so the catalog is the one place they are defined.
+#[derive(Debug, Serialize)]
+pub struct Rule {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// For pattern rules, this is also the description of each finding
+    pub description: &'static str,
+    pub severity: Severity,
+    /// How reliably a finding of the rule is a real issue
+    pub confidence: Confidence,
+    pub category: Category,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<&'static str>,
+    /// Matches whose text also matches this are not reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unless: Option<&'static str>,
+    /// Also reported, but only inside `unsafe` blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_unsafe: Option<&'static str>,
+}
+
+const fn rule(
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
+    severity: Severity,
+    category: Category,
+) -> Rule {
+    Rule {
+        id,
+        title,
+        description,
+        severity,
+        confidence: Confidence::High,
+        category,
+        pattern: None,
+        unless: None,
+        in_unsafe: None,
+    }
+}
+
+const fn pattern(
+    id: &'static str,
+    title: &'static str,
+    pattern: &'static str,
+    description: &'static str,
+    severity: Severity,
+    confidence: Confidence,
+    category: Category,
+) -> Rule {
+    Rule {
+        pattern: Some(pattern),
+        confidence,
+        ..rule(id, title, description, severity, category)
+    }
+}
+
+impl Rule {
+    /// The rule with a lower confidence than the `High` that checks without
+    /// a pattern get by default, for heuristics.
+    const fn with_confidence(self, confidence: Confidence) -> Rule {
+        Rule { confidence, ..self }
+    }
+
+    /// The pattern rule, not reporting matches whose text matches `unless`.
+    const fn unless(self, unless: &'static str) -> Rule {
+        Rule {
+            unless: Some(unless),
+            ..self
+        }
+    }
+
+    /// The pattern rule, also reporting matches of `pattern` within
+    /// `unsafe` blocks, for code that is only a risk there.
+    const fn in_unsafe(self, pattern: &'static str) -> Rule {
+        Rule {
+            in_unsafe: Some(pattern),
+            ..self
+        }
+    }
+}
+
+/// Every built-in rule, ordered by ID. Severities are the defaults; some
+/// checks (advisories, maintenance, editions) adjust them per finding.
+pub static RULES: &[Rule] = &[
+    // Memory safety patterns
+    pattern(
+        "GUARD001",
+        "Unsafe block",
+        r"unsafe\s*\{",
+        "Contains unsafe blocks - review for memory safety",
+        Severity::High,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD002",
+        "Transmute",
+        r"std::mem::transmute",
+        "Uses memory transmutation - potential type safety issues",
+        Severity::High,
+        Confidence::High,
+        Category::Source,
+    ),
+    // FFI patterns
+    pattern(
+        "GUARD003",
+        "no_std crate",
+        r"#!\[no_std\]",
+        "No standard library usage - verify safety implementations",
+        Severity::Medium,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD004",
+        "FFI",
+        r"extern\s*C",
+        "FFI usage detected - validate memory safety",
+        Severity::Medium,
+        Confidence::Medium,
+        Category::Source,
+    ),
+    // Common vulnerability patterns
+    pattern(
+        "GUARD005",
+        "Code evaluation",
+        // Rust has no eval builtin: only calls of a free function named
+        // `eval`, not methods, definitions or longer identifiers
+        r"(?:\.\s*|\bfn\s+)?\beval\s*\(",
+        "Code evaluation detected - potential security risk",
+        Severity::Critical,
+        Confidence::Low,
+        Category::Source,
+    )
+    .unless(r"^(?:\.|fn\b)"),
+    pattern(
+        "GUARD006",
+        "Process execution",
+        r"std::process::Command",
+        "Process execution capabilities - review for command injection",
+        Severity::High,
+        Confidence::High,
+        Category::Source,
+    ),
+    // File operation patterns
+    pattern(
+        "GUARD007",
+        "File system modification",
+        r"std::fs::(write|create|remove)",
+        "File system modification - review for proper permissions",
+        Severity::Medium,
+        Confidence::High,
+        Category::Source,
+    ),
+    // Network related patterns
+    pattern(
+        "GUARD008",
+        "Network listener",
+        r"TcpListener::bind",
+        "Network listener - verify proper security controls",
+        Severity::Medium,
+        Confidence::High,
+        Category::Source,
+    ),
+    rule(
+        "GUARD009",
+        "Pre-1.0 version",
+        "The package is pre-1.0, so its API may be unstable",
+        Severity::Low,
+        Category::Package,
+    ),
+    rule(
+        "GUARD010",
+        "Many dependencies",
+        "The package declares more than 20 dependencies, increasing the attack surface",
+        Severity::Low,
+        Category::Package,
+    ),
+    rule(
+        "GUARD011",
+        "Wildcard dependency",
+        "A dependency is declared with a wildcard version requirement",
+        Severity::High,
+        Category::Package,
+    ),
+    rule(
+        "GUARD012",
+        "Build script",
+        "The package has a build script; reported when no specific build script rule matched",
+        Severity::Medium,
+        Category::Package,
+    ),
+    rule(
+        "GUARD013",
+        "Yanked version",
+        "The resolved version has been yanked from crates.io",
+        Severity::High,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD014",
+        "Denied license",
+        "The license is denied by, or not allowed by, the license policy, or cannot be parsed",
+        Severity::High,
+        Category::Package,
+    ),
+    rule(
+        "GUARD015",
+        "Missing license",
+        "The package declares no license while a license policy is configured",
+        Severity::Medium,
+        Category::Package,
+    ),
+    rule(
+        "GUARD016",
+        "Duplicate versions",
+        "The crate is resolved at more than one version",
+        Severity::Low,
+        Category::Dependencies,
+    ),
+    // Operations that a build script has no business performing
+    pattern(
+        "GUARD017",
+        "Build script runs commands",
+        r"Command::new\s*\(",
+        "Build script runs external commands",
+        Severity::High,
+        Confidence::High,
+        Category::BuildScript,
+    ),
+    pattern(
+        "GUARD018",
+        "Build script uses the network",
+        r"\b(reqwest|ureq|curl|attohttpc)::|TcpStream::connect",
+        "Build script accesses the network",
+        Severity::High,
+        Confidence::Medium,
+        Category::BuildScript,
+    ),
+    rule(
+        "GUARD019",
+        "Build script writes outside OUT_DIR",
+        "Build script writes, creates or removes files at a path not derived from OUT_DIR",
+        Severity::High,
+        Category::BuildScript,
+    )
+    .with_confidence(Confidence::Medium),
+    pattern(
+        "GUARD020",
+        "Build script uses include!",
+        r"include!\s*\(",
+        "Build script includes generated code with include!",
+        Severity::High,
+        Confidence::Medium,
+        Category::BuildScript,
+    ),
+    rule(
+        "GUARD021",
+        "AWS access key",
+        "An AWS access key ID appears in the sources",
+        Severity::Critical,
+        Category::Secrets,
+    ),
+    rule(
+        "GUARD022",
+        "Hard-coded credential",
+        "A password, API key or token is assigned a literal value",
+        Severity::Critical,
+        Category::Secrets,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD023",
+        "Private key",
+        "A PEM private key block appears in the sources",
+        Severity::Critical,
+        Category::Secrets,
+    ),
+    rule(
+        "GUARD024",
+        "High-entropy string",
+        "A long string literal with high Shannon entropy, possibly a secret",
+        Severity::Critical,
+        Category::Secrets,
+    )
+    .with_confidence(Confidence::Low),
+    rule(
+        "GUARD025",
+        "Git dependency",
+        "The dependency is fetched from a git repository not listed in allow-git",
+        Severity::Medium,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD026",
+        "Path dependency",
+        "A publishable package depends on a local path",
+        Severity::High,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD027",
+        "2015 edition",
+        "The package still uses the 2015 edition, which may indicate it is unmaintained",
+        Severity::Info,
+        Category::Package,
+    ),
+    rule(
+        "GUARD028",
+        "No rust-version",
+        "The package does not declare a rust-version (MSRV)",
+        Severity::Info,
+        Category::Package,
+    ),
+    rule(
+        "GUARD029",
+        "Edition below minimum",
+        "The package uses an edition older than min-edition",
+        Severity::Medium,
+        Category::Package,
+    ),
+    rule(
+        "GUARD030",
+        "Unmaintained crate",
+        "The crate has had no release on crates.io for more than max-age-months (--maintenance)",
+        Severity::Low,
+        Category::Dependencies,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD031",
+        "Default features enabled",
+        "A direct dependency is used with its default features; Medium when they enable a sensitive-features entry",
+        Severity::Info,
+        Category::Package,
+    ),
+    rule(
+        "GUARD032",
+        "Possible typosquat",
+        "The crate's name is one or two edits from a popular crate, or a popular name with an added affix",
+        Severity::High,
+        Category::Dependencies,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD033",
+        "Build script reads outside the package",
+        "Build script reads files from an absolute or home directory path",
+        Severity::High,
+        Category::BuildScript,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD034",
+        "Procedural macro dependency",
+        "Dependency provides a procedural macro, which runs code at compile time",
+        Severity::Info,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD035",
+        "Banned crate",
+        "The crate is banned by the deny list of the cargo-deny configuration",
+        Severity::High,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD036",
+        "Patched dependency",
+        "The crate is overridden by a [patch] or [replace] entry of the workspace manifest",
+        Severity::Medium,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD037",
+        "Overflow checks disabled",
+        "A [profile] of the package's manifest sets overflow-checks = false",
+        Severity::Medium,
+        Category::Package,
+    ),
+    pattern(
+        "GUARD038",
+        "Unsafe code allowed",
+        r"#!\[\s*allow\s*\([^)]*\bunsafe_code\b",
+        "Allows unsafe code, overriding any deny(unsafe_code) lint",
+        Severity::Low,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD039",
+        "Exported symbol",
+        r"#\[\s*(?:unsafe\s*\(\s*)?(?:no_mangle|export_name\s*=)",
+        "Exports an unmangled symbol - may collide with or override other symbols",
+        Severity::Medium,
+        Confidence::High,
+        Category::Source,
+    ),
+    rule(
+        "GUARD040",
+        "Outdated dependency",
+        "The resolved version is behind the newest release on crates.io (--outdated)",
+        Severity::Info,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD041",
+        "Insecure registry URL",
+        "A registry or source in the cargo configuration, or the registry of a dependency, is fetched over plain http:// or git://",
+        Severity::High,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD042",
+        "Unauthenticated registry",
+        "An alternate registry of the cargo configuration has no token or credential provider",
+        Severity::Medium,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD043",
+        "Git fetched with the CLI",
+        "The cargo configuration sets net.git-fetch-with-cli = true",
+        Severity::Low,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD044",
+        "Incompatible license",
+        "A linked dependency's license cannot be distributed under the project's license",
+        Severity::High,
+        Category::Dependencies,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD045",
+        "Dependency confusion",
+        "A crate named in internal-crates resolves from crates.io or another registry than internal-registry",
+        Severity::High,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD046",
+        "GitHub token",
+        "A GitHub personal access, OAuth or app token appears in the sources",
+        Severity::Critical,
+        Category::Secrets,
+    ),
+    rule(
+        "GUARD047",
+        "Slack token",
+        "A Slack bot, user or app token appears in the sources",
+        Severity::Critical,
+        Category::Secrets,
+    ),
+    rule(
+        "GUARD048",
+        "JSON Web Token",
+        "A signed JSON Web Token appears in the sources",
+        Severity::Critical,
+        Category::Secrets,
+    ),
+    rule(
+        "GUARD049",
+        "Pinned dependency version",
+        "A workspace member pins a dependency with `=x.y.z`; High when an advisory affects the pinned version",
+        Severity::Info,
+        Category::Dependencies,
+    ),
+    rule(
+        "GUARD050",
+        "Transitive process execution",
+        "A dependency can spawn processes through one of its own dependencies",
+        Severity::Low,
+        Category::Dependencies,
+    )
+    .with_confidence(Confidence::Medium),
+    rule(
+        "GUARD051",
+        "Copied source of another crate",
+        "A source file is a copy of a well-known file of another crate, which that crate's advisories do not cover",
+        Severity::Medium,
+        Category::Source,
+    )
+    .with_confidence(Confidence::Medium),
+    pattern(
+        "GUARD052",
+        "Unsafe Send implementation",
+        r"\bunsafe\s+impl\b[^{;]*?\bSend\s+for\b",
+        "Implements Send unsafely - a wrong impl lets values cross threads and race",
+        Severity::High,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD053",
+        "Unsafe Sync implementation",
+        r"\bunsafe\s+impl\b[^{;]*?\bSync\s+for\b",
+        "Implements Sync unsafely - a wrong impl allows unsynchronized shared access",
+        Severity::High,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD054",
+        "Unsafe function",
+        r#"\bunsafe\s+(?:extern\s*(?:"[^"]*"\s*)?)?fn\s+\w"#,
+        "Declares an unsafe function - callers must uphold its safety contract",
+        Severity::Medium,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD055",
+        "Unsafe trait",
+        r"\bunsafe\s+(?:auto\s+)?trait\s+\w",
+        "Declares an unsafe trait - its implementations must uphold invariants the compiler cannot check",
+        Severity::Low,
+        Confidence::High,
+        Category::Source,
+    ),
+    pattern(
+        "GUARD056",
+        "Raw pointer dereference",
+        // A `*` after an operand is a multiplication. Anywhere, pointer
+        // arithmetic and casts make the operand a raw pointer, and the
+        // `ptr` read and write functions take one
+        r"(?m)(?:^|[^\w)\]\s])[ \t]*\*[ \t]*(?:\(?[ \t]*[\w.]+\s+as\s+\*(?:const|mut)\b|[\w.]+\.(?:add|sub|offset|byte_add|byte_sub|byte_offset|wrapping_add|wrapping_sub|wrapping_offset)\s*\()|\bptr::(?:read|write)(?:_volatile|_unaligned)?\s*(?:::<|\()|\.(?:read|write)_(?:volatile|unaligned)\s*\(",
+        "Dereferences a raw pointer - out-of-bounds or dangling pointers are undefined behavior",
+        Severity::High,
+        Confidence::Medium,
+        Category::Source,
+    )
+    // Within `unsafe` blocks, a plain variable is likely a raw pointer;
+    // `*const T` and `*mut T` are pointer types, not dereferences
+    .in_unsafe(r"(?m)(?:^|[^\w)\]\s])[ \t]*\*[ \t]*[A-Za-z_]\w*")
+    .unless(r"^[^*]*\*\s*(?:const|mut)\b"),
+];
+
+/// Looks up a built-in rule by ID.
+pub fn find(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+/// Looks up the in-depth explanation of a built-in rule by ID.
+pub fn explain(id: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS.iter().find(|explanation| explanation.id == id)
+}
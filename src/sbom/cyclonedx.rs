@@ -0,0 +1,238 @@
+use serde_json::{json, Value};
+
+use super::{dependency_graph, purl, timestamp, tool_version};
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+
+/// Renders a CycloneDX 1.5 JSON document: a component per resolved
+/// package, the dependency graph, and advisories as vulnerabilities.
+pub fn cyclonedx(analysis: &DependencyAnalysis) -> Result<String> {
+    let components: Vec<Value> = analysis
+        .packages
+        .values()
+        .map(|package| {
+            let mut component = json!({
+                "type": if package.member { "application" } else { "library" },
+                "bom-ref": purl(package),
+                "name": package.name,
+                "version": package.version,
+                "purl": purl(package),
+            });
+            if let Some(license) = &package.license {
+                component["licenses"] = json!([{ "expression": license }]);
+            }
+            component
+        })
+        .collect();
+
+    let dependencies: Vec<Value> = dependency_graph(analysis)
+        .map(|(package, dependencies)| {
+            let depends_on: Vec<String> = dependencies.into_iter().map(purl).collect();
+            json!({ "ref": purl(package), "dependsOn": depends_on })
+        })
+        .collect();
+
+    let bom_ref = |key: &str| analysis.packages.get(key).map(purl);
+    let vulnerabilities: Vec<Value> = analysis
+        .security_issues
+        .iter()
+        .flat_map(|(name, issues)| issues.iter().map(move |issue| (name, issue)))
+        .filter_map(|(name, issue)| {
+            let id = issue.advisory_id.as_ref()?;
+            let affected = issue
+                .affected_versions
+                .first()
+                .and_then(|version| bom_ref(&format!("{}@{}", name, version)))?;
+
+            let mut rating = json!({ "severity": issue.severity.to_string().to_lowercase() });
+            if let Some(score) = issue.cvss_score {
+                rating["score"] = json!(score);
+            }
+            if let Some(vector) = &issue.cvss_vector {
+                rating["method"] = json!(cvss_method(vector));
+                rating["vector"] = json!(vector);
+            }
+
+            let mut vulnerability = json!({
+                "id": id,
+                "source": {
+                    "name": "RustSec",
+                    "url": format!("https://rustsec.org/advisories/{}.html", id),
+                },
+                "ratings": [rating],
+                "description": issue.description,
+                "affects": [{ "ref": affected }],
+            });
+            if let Some(fix) = &issue.fix_version {
+                vulnerability["recommendation"] = json!(format!("Upgrade to {}", fix));
+            }
+            Some(vulnerability)
+        })
+        .collect();
+
+    let mut bom = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp(),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "dep-sense",
+                    "version": tool_version(),
+                }],
+            },
+        },
+        "components": components,
+        "dependencies": dependencies,
+    });
+    if !vulnerabilities.is_empty() {
+        bom["vulnerabilities"] = json!(vulnerabilities);
+    }
+
+    Ok(serde_json::to_string_pretty(&bom).map_err(GuardianError::report)? + "\n")
+}
+
+/// CycloneDX rating method for a CVSS vector string.
+fn cvss_method(vector: &str) -> &'static str {
+    if vector.starts_with("CVSS:3.1/") {
+        "CVSSv31"
+    } else if vector.starts_with("CVSS:3.0/") {
+        "CVSSv3"
+    } else if vector.starts_with("CVSS:4.0/") {
+        "CVSSv4"
+    } else if vector.starts_with("AV:") {
+        "CVSSv2"
+    } else {
+        "other"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ResolvedPackage, SecurityIssue, Severity};
+    use std::collections::HashSet;
+
+    /// `app` depends on `log`, which depends on `cfg-if`; `log` is
+    /// vulnerable.
+    fn analysis() -> DependencyAnalysis {
+        let package = |name: &str, version: &str, member: bool| ResolvedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            license: Some("MIT".to_string()),
+            source: None,
+            member,
+        };
+        let advisory = SecurityIssue {
+            severity: Severity::High,
+            description: "log is vulnerable".to_string(),
+            affected_versions: vec!["0.4.0".to_string()],
+            fix_version: Some("0.4.1".to_string()),
+            advisory_id: Some("RUSTSEC-2024-0002".to_string()),
+            ..Default::default()
+        };
+        serde_json::from_value(json!({
+            "total_dependencies": 2,
+            "direct_dependencies": [],
+            "dependency_tree": {
+                "app@0.1.0": [{ "package": "log@0.4.0", "kinds": ["normal"] }],
+                "log@0.4.0": [{ "package": "cfg-if@1.0.0", "kinds": ["normal"] }],
+                "cfg-if@1.0.0": [],
+            },
+            "packages": {
+                "app@0.1.0": package("app", "0.1.0", true),
+                "log@0.4.0": package("log", "0.4.0", false),
+                "cfg-if@1.0.0": package("cfg-if", "1.0.0", false),
+            },
+            "security_issues": { "log": [advisory] },
+            "suppressed_issues": 0,
+        }))
+        .unwrap()
+    }
+
+    fn strings(values: &Value) -> Vec<&str> {
+        values
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn renders_a_cyclonedx_1_5_document() {
+        let analysis = analysis();
+
+        let bom: Value = serde_json::from_str(&cyclonedx(&analysis).unwrap()).unwrap();
+
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["specVersion"], "1.5");
+        assert_eq!(bom["version"], 1);
+        assert!(humantime::parse_rfc3339(bom["metadata"]["timestamp"].as_str().unwrap()).is_ok());
+        assert_eq!(
+            bom["metadata"]["tools"]["components"][0]["name"],
+            "dep-sense"
+        );
+
+        let components = bom["components"].as_array().unwrap();
+        assert_eq!(components.len(), analysis.packages.len());
+        for component in components {
+            for field in ["type", "bom-ref", "name", "version", "purl"] {
+                assert!(component[field].is_string(), "{} in {}", field, component);
+            }
+            assert_eq!(component["licenses"][0]["expression"], "MIT");
+        }
+        let log = components
+            .iter()
+            .find(|component| component["name"] == "log")
+            .unwrap();
+        assert_eq!(log["type"], "library");
+        assert_eq!(log["purl"], "pkg:cargo/log@0.4.0");
+    }
+
+    #[test]
+    fn references_resolve_to_components() {
+        let analysis = analysis();
+
+        let bom: Value = serde_json::from_str(&cyclonedx(&analysis).unwrap()).unwrap();
+
+        let components = bom["components"].as_array().unwrap();
+        let refs: HashSet<&str> = components
+            .iter()
+            .map(|component| component["bom-ref"].as_str().unwrap())
+            .collect();
+        assert_eq!(refs.len(), components.len(), "bom-refs are unique");
+
+        let dependencies = bom["dependencies"].as_array().unwrap();
+        for dependency in dependencies {
+            assert!(refs.contains(dependency["ref"].as_str().unwrap()));
+            assert!(strings(&dependency["dependsOn"])
+                .iter()
+                .all(|depends_on| refs.contains(depends_on)));
+        }
+        let log = dependencies
+            .iter()
+            .find(|dependency| dependency["ref"] == "pkg:cargo/log@0.4.0")
+            .unwrap();
+        assert_eq!(strings(&log["dependsOn"]), ["pkg:cargo/cfg-if@1.0.0"]);
+
+        let vulnerabilities = bom["vulnerabilities"].as_array().unwrap();
+        assert_eq!(vulnerabilities.len(), 1);
+        let vulnerability = &vulnerabilities[0];
+        assert_eq!(vulnerability["id"], "RUSTSEC-2024-0002");
+        assert_eq!(vulnerability["affects"][0]["ref"], "pkg:cargo/log@0.4.0");
+        assert_eq!(vulnerability["recommendation"], "Upgrade to 0.4.1");
+        assert!(vulnerability["ratings"][0]["severity"].is_string());
+    }
+
+    #[test]
+    fn names_cvss_methods_by_vector_version() {
+        assert_eq!(cvss_method("CVSS:3.1/AV:N/AC:L"), "CVSSv31");
+        assert_eq!(cvss_method("CVSS:3.0/AV:N/AC:L"), "CVSSv3");
+        assert_eq!(cvss_method("CVSS:4.0/AV:N/AC:L"), "CVSSv4");
+        assert_eq!(cvss_method("AV:N/AC:L/Au:N"), "CVSSv2");
+        assert_eq!(cvss_method("unknown"), "other");
+    }
+}
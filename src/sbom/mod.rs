@@ -0,0 +1,55 @@
+use clap::ValueEnum;
+use std::time::SystemTime;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::Result;
+use crate::models::ResolvedPackage;
+
+mod cyclonedx;
+mod spdx;
+
+pub use cyclonedx::cyclonedx;
+pub use spdx::spdx;
+
+/// Software bill of materials formats for `--sbom`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    Cyclonedx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+/// Renders the resolved graph of `analysis` as an SBOM document.
+pub fn render(format: SbomFormat, analysis: &DependencyAnalysis) -> Result<String> {
+    match format {
+        SbomFormat::Cyclonedx => cyclonedx(analysis),
+        SbomFormat::Spdx => spdx(analysis),
+    }
+}
+
+/// Package URL of a crate, e.g. `pkg:cargo/serde@1.0.0`.
+fn purl(package: &ResolvedPackage) -> String {
+    format!("pkg:cargo/{}@{}", package.name, package.version)
+}
+
+fn tool_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+fn timestamp() -> String {
+    humantime::format_rfc3339_seconds(SystemTime::now()).to_string()
+}
+
+/// Each resolved package with the packages it depends on.
+fn dependency_graph(
+    analysis: &DependencyAnalysis,
+) -> impl Iterator<Item = (&ResolvedPackage, Vec<&ResolvedPackage>)> {
+    analysis.dependency_tree.iter().filter_map(|(key, edges)| {
+        let dependencies = edges
+            .iter()
+            .filter_map(|edge| analysis.packages.get(&edge.package))
+            .collect();
+        Some((analysis.packages.get(key)?, dependencies))
+    })
+}
@@ -0,0 +1,237 @@
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{dependency_graph, purl, timestamp, tool_version};
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::ResolvedPackage;
+
+/// Renders an SPDX 2.3 JSON document: a package per resolved package and
+/// `DEPENDS_ON` relationships for the dependency graph.
+pub fn spdx(analysis: &DependencyAnalysis) -> Result<String> {
+    let members: Vec<&ResolvedPackage> = analysis
+        .packages
+        .values()
+        .filter(|package| package.member)
+        .collect();
+    let name = match members.as_slice() {
+        [member] => member.name.clone(),
+        _ => "workspace".to_string(),
+    };
+
+    let packages: Vec<Value> = analysis
+        .packages
+        .values()
+        .map(|package| {
+            json!({
+                "name": package.name,
+                "SPDXID": spdx_id(package),
+                "versionInfo": package.version,
+                "downloadLocation": download_location(package),
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": package.license.as_deref().unwrap_or("NOASSERTION"),
+                "copyrightText": "NOASSERTION",
+                "filesAnalyzed": false,
+                "externalRefs": [{
+                    "referenceCategory": "PACKAGE-MANAGER",
+                    "referenceType": "purl",
+                    "referenceLocator": purl(package),
+                }],
+            })
+        })
+        .collect();
+
+    let describes = members.iter().map(|member| {
+        json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": spdx_id(member),
+        })
+    });
+    let depends_on = dependency_graph(analysis).flat_map(|(package, dependencies)| {
+        dependencies.into_iter().map(move |dependency| {
+            json!({
+                "spdxElementId": spdx_id(package),
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": spdx_id(dependency),
+            })
+        })
+    });
+    let relationships: Vec<Value> = describes.chain(depends_on).collect();
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let document = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": name,
+        "documentNamespace": format!("https://spdx.org/spdxdocs/dep-sense/{}-{:x}", name, nanos),
+        "creationInfo": {
+            "created": timestamp(),
+            "creators": [format!("Tool: dep-sense-{}", tool_version())],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    });
+
+    Ok(serde_json::to_string_pretty(&document).map_err(GuardianError::report)? + "\n")
+}
+
+/// SPDX identifiers may only contain letters, digits, `.` and `-`.
+fn spdx_id(package: &ResolvedPackage) -> String {
+    let id: String = format!("{}-{}", package.name, package.version)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{}", id)
+}
+
+/// Where the package was fetched from: the crates.io download URL, the
+/// git repository at its revision, or `NOASSERTION` for local paths and
+/// other registries.
+fn download_location(package: &ResolvedPackage) -> String {
+    match package.source.as_deref() {
+        Some(source) if source.contains("crates.io") => format!(
+            "https://crates.io/api/v1/crates/{}/{}/download",
+            package.name, package.version
+        ),
+        Some(source) if source.starts_with("git+") => {
+            let (url, revision) = source.split_once('#').unwrap_or((source, ""));
+            let url = url.split('?').next().unwrap_or(url);
+            if revision.is_empty() {
+                url.to_string()
+            } else {
+                format!("{}@{}", url, revision)
+            }
+        }
+        _ => "NOASSERTION".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    const CRATES_IO: &str = "registry+https://github.com/rust-lang/crates.io-index";
+    const GIT: &str = "git+https://github.com/org/fork?branch=main#0123abc";
+
+    /// `app` depends on `log` and on `fork` from git; both depend on
+    /// `cfg-if`.
+    fn analysis() -> DependencyAnalysis {
+        let package = |name: &str, version: &str, source: Option<&str>| ResolvedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            license: Some("MIT".to_string()),
+            source: source.map(str::to_string),
+            member: source.is_none(),
+        };
+        let edges = |packages: &[&str]| -> Value {
+            packages
+                .iter()
+                .map(|package| json!({ "package": package, "kinds": ["normal"] }))
+                .collect()
+        };
+        serde_json::from_value(json!({
+            "total_dependencies": 3,
+            "direct_dependencies": [],
+            "dependency_tree": {
+                "app@0.1.0": edges(&["log@0.4.0", "fork@0.2.0"]),
+                "log@0.4.0": edges(&["cfg-if@1.0.0"]),
+                "fork@0.2.0": edges(&["cfg-if@1.0.0"]),
+                "cfg-if@1.0.0": edges(&[]),
+            },
+            "packages": {
+                "app@0.1.0": package("app", "0.1.0", None),
+                "log@0.4.0": package("log", "0.4.0", Some(CRATES_IO)),
+                "cfg-if@1.0.0": package("cfg-if", "1.0.0", Some(CRATES_IO)),
+                "fork@0.2.0": package("fork", "0.2.0", Some(GIT)),
+            },
+            "security_issues": {},
+            "suppressed_issues": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn every_package_appears() {
+        let analysis = analysis();
+
+        let document: Value = serde_json::from_str(&spdx(&analysis).unwrap()).unwrap();
+
+        assert_eq!(document["spdxVersion"], "SPDX-2.3");
+        assert_eq!(document["name"], "app");
+        assert!(
+            humantime::parse_rfc3339(document["creationInfo"]["created"].as_str().unwrap()).is_ok()
+        );
+        let packages = document["packages"].as_array().unwrap();
+        let names: BTreeSet<&str> = packages
+            .iter()
+            .map(|package| package["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, BTreeSet::from(["app", "cfg-if", "fork", "log"]));
+
+        let location = |name: &str| {
+            packages
+                .iter()
+                .find(|package| package["name"] == name)
+                .map(|package| package["downloadLocation"].as_str().unwrap())
+        };
+        assert_eq!(
+            location("log"),
+            Some("https://crates.io/api/v1/crates/log/0.4.0/download")
+        );
+        assert_eq!(
+            location("fork"),
+            Some("git+https://github.com/org/fork@0123abc")
+        );
+        assert_eq!(location("app"), Some("NOASSERTION"));
+    }
+
+    #[test]
+    fn relationships_are_consistent_with_the_graph() {
+        let analysis = analysis();
+
+        let document: Value = serde_json::from_str(&spdx(&analysis).unwrap()).unwrap();
+
+        let ids: HashSet<&str> = document["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|package| package["SPDXID"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids.len(), analysis.packages.len(), "SPDXIDs are unique");
+
+        let mut relationships = BTreeSet::new();
+        for relationship in document["relationships"].as_array().unwrap() {
+            let from = relationship["spdxElementId"].as_str().unwrap();
+            let to = relationship["relatedSpdxElement"].as_str().unwrap();
+            assert!(from == "SPDXRef-DOCUMENT" || ids.contains(from), "{}", from);
+            assert!(ids.contains(to), "{}", to);
+            relationships.insert((
+                from.trim_start_matches("SPDXRef-Package-"),
+                relationship["relationshipType"].as_str().unwrap(),
+                to.trim_start_matches("SPDXRef-Package-"),
+            ));
+        }
+        assert_eq!(
+            relationships,
+            BTreeSet::from([
+                ("SPDXRef-DOCUMENT", "DESCRIBES", "app-0.1.0"),
+                ("app-0.1.0", "DEPENDS_ON", "fork-0.2.0"),
+                ("app-0.1.0", "DEPENDS_ON", "log-0.4.0"),
+                ("fork-0.2.0", "DEPENDS_ON", "cfg-if-1.0.0"),
+                ("log-0.4.0", "DEPENDS_ON", "cfg-if-1.0.0"),
+            ])
+        );
+    }
+}
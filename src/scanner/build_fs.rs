@@ -0,0 +1,399 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::models::{SecurityIssue, Severity};
+
+use super::{lexer, locate};
+
+/// Longest source line quoted in a finding.
+const MAX_QUOTED_LINE: usize = 120;
+
+/// Calls that write, create or remove the path in the given argument.
+const WRITES: &[(&str, usize)] = &[
+    ("fs::write", 0),
+    ("fs::copy", 1),
+    ("fs::rename", 1),
+    ("fs::create_dir", 0),
+    ("fs::create_dir_all", 0),
+    ("fs::remove_file", 0),
+    ("fs::remove_dir", 0),
+    ("fs::remove_dir_all", 0),
+    ("fs::set_permissions", 0),
+    ("File::create", 0),
+    ("OpenOptions::write", 0),
+];
+
+/// Calls that read the path in the given argument.
+const READS: &[(&str, usize)] = &[
+    ("fs::read", 0),
+    ("fs::read_to_string", 0),
+    ("fs::read_dir", 0),
+    ("fs::copy", 0),
+    ("File::open", 0),
+    ("OpenOptions::read", 0),
+];
+
+/// Flags the filesystem calls of a build script that reach outside the
+/// build: writes whose target is not derived from `OUT_DIR` (GUARD019) and
+/// reads from absolute or home-relative paths (GUARD033).
+///
+/// Derivation is tracked through `let` bindings in source order: a binding
+/// whose value mentions `OUT_DIR`, or an earlier such binding, points into
+/// the output directory, and one built from an absolute path, `~` or the
+/// home directory points outside the package. Names bound elsewhere, such
+/// as function parameters, are trusted when they are called `out` or
+/// `dest`-something, as the output directory usually is.
+#[derive(Debug)]
+pub struct BuildFsCheck {
+    writes: bool,
+    reads: bool,
+    call: Regex,
+    writable: Regex,
+    binding: Regex,
+    ident: Regex,
+    out_name: Regex,
+}
+
+impl BuildFsCheck {
+    /// `None` when both rules are disabled.
+    pub fn new(config: &Config) -> Option<Self> {
+        let writes = config.is_rule_enabled("GUARD019");
+        let reads = config.is_rule_enabled("GUARD033");
+        if !writes && !reads {
+            return None;
+        }
+
+        Some(Self {
+            writes,
+            reads,
+            call: Regex::new(
+                r"\b(fs::(?:write|copy|rename|create_dir(?:_all)?|remove_(?:file|dir(?:_all)?)|set_permissions|read(?:_to_string|_dir)?)|File::(?:create|open))\s*\(|\bOpenOptions::new\(\)([^;]*?)\.open\s*\(",
+            )
+            .unwrap(),
+            writable: Regex::new(r"\.(write|append|create|create_new|truncate)\s*\(\s*true").unwrap(),
+            // `let name =`, `let mut name: T =` and `let Some(name) =`
+            binding: Regex::new(
+                r"\blet\s+(?:mut\s+)?(?:[\w:]+\(\s*(?:mut\s+|ref\s+)?)?([A-Za-z_]\w*)\)?[^=;]*=([^;{]*)",
+            )
+            .unwrap(),
+            ident: Regex::new(r"[A-Za-z_]\w*").unwrap(),
+            out_name: Regex::new(r"(?i)out_dir|^out$|^out_|^dest").unwrap(),
+        })
+    }
+
+    pub fn scan(
+        &self,
+        file: &Path,
+        content: &str,
+        line_starts: &[usize],
+        ignored: &[Range<usize>],
+        issues: &mut Vec<SecurityIssue>,
+    ) {
+        let strings = lexer::ignored_spans(content);
+        let mut events: Vec<(usize, Event)> = Vec::new();
+        for found in self.binding.captures_iter(content) {
+            let start = found.get(0).unwrap().start();
+            if !lexer::is_ignored(ignored, start) {
+                let binding = Event::Binding(found[1].to_string(), found[2].to_string());
+                events.push((start, binding));
+            }
+        }
+        for found in self.call.captures_iter(content) {
+            let whole = found.get(0).unwrap();
+            if lexer::is_ignored(ignored, whole.start()) {
+                continue;
+            }
+            let name = match (found.get(1), found.get(2)) {
+                (Some(call), _) => call.as_str(),
+                (None, Some(options)) if self.writable.is_match(options.as_str()) => {
+                    "OpenOptions::write"
+                }
+                _ => "OpenOptions::read",
+            };
+            let args = arguments(content, whole.end(), &strings);
+            events.push((whole.start(), Event::Call(name.to_string(), args)));
+        }
+        events.sort_by_key(|(offset, _)| *offset);
+
+        let mut scope = Scope::default();
+        for (offset, event) in events {
+            match event {
+                Event::Binding(name, value) => {
+                    let inside = self.inside_out_dir(&value, &scope);
+                    let outside = outside_package(&value, &scope);
+                    scope.bind(name, inside, outside);
+                }
+                Event::Call(name, args) => {
+                    let target = |table: &[(&str, usize)]| {
+                        table
+                            .iter()
+                            .find(|(call, _)| *call == name)
+                            .and_then(|(_, index)| args.get(*index))
+                    };
+                    if self.writes {
+                        if let Some(path) = target(WRITES) {
+                            if !self.inside_out_dir(path, &scope) {
+                                issues.push(finding(
+                                    file,
+                                    content,
+                                    line_starts,
+                                    offset,
+                                    "GUARD019",
+                                    "Build script writes outside OUT_DIR",
+                                ));
+                            }
+                        }
+                    }
+                    if self.reads {
+                        if let Some(path) = target(READS) {
+                            if outside_package(path, &scope) {
+                                issues.push(finding(
+                                    file,
+                                    content,
+                                    line_starts,
+                                    offset,
+                                    "GUARD033",
+                                    "Build script reads from an absolute or home directory path",
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn inside_out_dir(&self, expr: &str, scope: &Scope) -> bool {
+        // Names in the code, not in string literals such as "out/gen.rs"
+        let mut idents = expr
+            .split('"')
+            .step_by(2)
+            .flat_map(|code| self.ident.find_iter(code));
+        expr.contains("OUT_DIR")
+            || idents.any(|ident| {
+                let ident = ident.as_str();
+                if scope.bound.contains(ident) {
+                    scope.inside.contains(ident)
+                } else {
+                    self.out_name.is_match(ident)
+                }
+            })
+    }
+}
+
+enum Event {
+    /// A `let` binding and its value
+    Binding(String, String),
+    /// A filesystem call and its arguments
+    Call(String, Vec<String>),
+}
+
+/// What the `let` bindings seen so far point to.
+#[derive(Default)]
+struct Scope {
+    bound: HashSet<String>,
+    inside: HashSet<String>,
+    outside: HashSet<String>,
+}
+
+impl Scope {
+    fn bind(&mut self, name: String, inside: bool, outside: bool) {
+        // A later binding shadows an earlier one of the same name
+        for (set, flag) in [(&mut self.inside, inside), (&mut self.outside, outside)] {
+            if flag {
+                set.insert(name.clone());
+            } else {
+                set.remove(&name);
+            }
+        }
+        self.bound.insert(name);
+    }
+}
+
+/// Whether `expr` names an absolute or home-relative path, or a binding
+/// that does. Paths joined onto the package or output directory are not.
+fn outside_package(expr: &str, scope: &Scope) -> bool {
+    if expr.contains("CARGO_MANIFEST_DIR") || expr.contains("OUT_DIR") {
+        return false;
+    }
+    let absolute = expr.split('"').skip(1).step_by(2).any(|literal| {
+        literal.starts_with('/')
+            || literal.starts_with('~')
+            || matches!(literal.get(1..3), Some(":\\" | ":/"))
+    });
+    absolute
+        || expr.contains("home_dir(")
+        || expr.contains("\"HOME\"")
+        || expr.contains("\"USERPROFILE\"")
+        || scope.outside.iter().any(|name| contains_ident(expr, name))
+}
+
+fn contains_ident(expr: &str, name: &str) -> bool {
+    expr.match_indices(name).any(|(start, _)| {
+        let before = expr[..start].chars().next_back();
+        let after = expr[start + name.len()..].chars().next();
+        !before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)
+    })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// The top-level arguments of the call whose opening parenthesis ends at
+/// `start`, skipping parentheses and commas inside string literals.
+fn arguments(content: &str, start: usize, strings: &[Range<usize>]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = start;
+    for (offset, c) in content[start..].char_indices() {
+        let offset = start + offset;
+        if lexer::is_ignored(strings, offset) {
+            continue;
+        }
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth > 0 => depth -= 1,
+            ')' => {
+                args.push(content[current..offset].trim().to_string());
+                break;
+            }
+            ',' if depth == 0 => {
+                args.push(content[current..offset].trim().to_string());
+                current = offset + 1;
+            }
+            _ => {}
+        }
+    }
+    args
+}
+
+fn finding(
+    file: &Path,
+    content: &str,
+    line_starts: &[usize],
+    offset: usize,
+    rule: &str,
+    description: &str,
+) -> SecurityIssue {
+    let location = locate(file, content, line_starts, offset);
+    let line = content[line_starts[location.line - 1]..]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let quoted: String = line.chars().take(MAX_QUOTED_LINE).collect();
+
+    SecurityIssue {
+        severity: Severity::High,
+        description: format!("{}: `{}`", description, quoted),
+        rule_id: Some(rule.to_string()),
+        location: Some(location),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::line_starts;
+
+    /// The rule and line of each finding in the build script `source`.
+    fn scan(source: &str) -> Vec<(String, usize)> {
+        let check = BuildFsCheck::new(&Config::default()).unwrap();
+        let mut issues = Vec::new();
+        let line_starts = line_starts(source);
+        let ignored = lexer::ignored_spans(source);
+        check.scan(
+            Path::new("build.rs"),
+            source,
+            &line_starts,
+            &ignored,
+            &mut issues,
+        );
+        issues
+            .into_iter()
+            .map(|issue| (issue.rule_id.unwrap(), issue.location.unwrap().line))
+            .collect()
+    }
+
+    #[test]
+    fn well_behaved_build_scripts_are_clean() {
+        let source = r#"
+use std::{env, fs, path::PathBuf};
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let dest = out_dir.join("generated.rs");
+    fs::write(&dest, "pub const N: u32 = 1;").unwrap();
+    fs::create_dir_all(out_dir.join("include")).unwrap();
+    let manifest = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let template = fs::read_to_string(manifest.join("template.rs")).unwrap();
+    fs::copy("src/schema.json", Path::new(&env::var("OUT_DIR").unwrap()).join("schema.json")).unwrap();
+    write(&dest, &template);
+}
+
+fn write(out: &Path, template: &str) {
+    fs::write(out, template).unwrap();
+}
+"#;
+
+        assert_eq!(scan(source), []);
+    }
+
+    #[test]
+    fn flags_writes_outside_out_dir_and_reads_outside_the_package() {
+        let source = r#"
+use std::{env, fs, fs::File, fs::OpenOptions};
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(format!("{}/ok.rs", out_dir), "").unwrap();
+    let home = env::var("HOME").unwrap();
+    let key = fs::read_to_string(format!("{}/.ssh/id_rsa", home)).unwrap();
+    File::create("/tmp/exfil").unwrap();
+    let out_dir = "src/generated";
+    fs::write(out_dir, key).unwrap();
+    let log = OpenOptions::new().append(true).open("/var/log/build.log").unwrap();
+    let passwd = File::open("/etc/passwd").unwrap();
+    // fs::write("/etc/hosts", "") in a comment
+}
+"#;
+
+        assert_eq!(
+            scan(source),
+            [
+                ("GUARD033".to_string(), 8),
+                ("GUARD019".to_string(), 9),
+                ("GUARD019".to_string(), 11),
+                ("GUARD019".to_string(), 12),
+                ("GUARD033".to_string(), 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_the_offending_line() {
+        let check = BuildFsCheck::new(&Config::default()).unwrap();
+        let source = "fn main() {\n    std::fs::remove_dir_all(\"/\").ok();\n}\n";
+        let mut issues = Vec::new();
+        let line_starts = line_starts(source);
+        check.scan(
+            Path::new("build.rs"),
+            source,
+            &line_starts,
+            &[],
+            &mut issues,
+        );
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::High);
+        assert_eq!(
+            issues[0].description,
+            "Build script writes outside OUT_DIR: `std::fs::remove_dir_all(\"/\").ok();`"
+        );
+    }
+}
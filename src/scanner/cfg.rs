@@ -0,0 +1,356 @@
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::lexer;
+
+/// Finds the code that `#[cfg(...)]` attributes leave out of a build with
+/// a given feature set.
+///
+/// Only `feature = "..."` and `test` are decided (`test` is never built
+/// into a dependency); other predicates, such as `unix` or
+/// `target_os = "..."`, may hold and keep their items.
+#[derive(Debug)]
+pub struct CfgGates {
+    attribute: Regex,
+    module: Regex,
+}
+
+impl CfgGates {
+    pub fn new() -> Self {
+        Self {
+            attribute: Regex::new(r"#\s*(!?)\s*\[\s*cfg\s*\(").unwrap(),
+            module: Regex::new(r"\bmod\s+([A-Za-z_]\w*)\s*;").unwrap(),
+        }
+    }
+
+    /// Byte ranges of the items turned off for `features`, in order, or
+    /// the whole file for a disabled `#![cfg(...)]`.
+    pub fn disabled_spans(&self, content: &str, features: &BTreeSet<String>) -> Vec<Range<usize>> {
+        if !self.attribute.is_match(content) {
+            return Vec::new();
+        }
+        let ignored = lexer::ignored_spans(content);
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        for found in self.attribute.captures_iter(content) {
+            let start = found.get(0).unwrap().start();
+            // Attributes inside an item already turned off need no look
+            if lexer::is_ignored(&ignored, start) || lexer::is_ignored(&spans, start) {
+                continue;
+            }
+            let predicate_start = found.get(0).unwrap().end();
+            let Some(predicate_end) = closing(content, predicate_start, &ignored) else {
+                continue;
+            };
+            let predicate = &content[predicate_start..predicate_end];
+            if eval(&mut tokens(predicate).into_iter().peekable(), features) != Some(false) {
+                continue;
+            }
+
+            if !found[1].is_empty() {
+                // An inner attribute turns off the module it is in
+                return std::iter::once(0..content.len()).collect();
+            }
+            let Some(attribute_end) = content[predicate_end..]
+                .find(']')
+                .map(|offset| predicate_end + offset + 1)
+            else {
+                continue;
+            };
+            spans.push(start..item_end(content, attribute_end, &ignored));
+        }
+        spans
+    }
+
+    /// Files of the modules declared with `mod name;` in `file` under a
+    /// `#[cfg(...)]` that `features` turn off, which are left out with
+    /// their declaration.
+    pub fn disabled_modules(
+        &self,
+        file: &Path,
+        content: &str,
+        features: &BTreeSet<String>,
+    ) -> Vec<PathBuf> {
+        let Some(parent) = file.parent() else {
+            return Vec::new();
+        };
+        if !self.module.is_match(content) {
+            return Vec::new();
+        }
+        let spans = self.disabled_spans(content, features);
+        // Submodules of `foo.rs` live in `foo/`, those of `mod.rs`, `lib.rs`
+        // and `main.rs` next to it
+        let dir = match file.file_stem().and_then(|stem| stem.to_str()) {
+            Some("mod" | "lib" | "main") | None => parent.to_path_buf(),
+            Some(stem) => parent.join(stem),
+        };
+
+        self.module
+            .captures_iter(content)
+            .filter(|found| lexer::is_ignored(&spans, found.get(0).unwrap().start()))
+            .flat_map(|found| {
+                let name = &found[1];
+                [dir.join(format!("{}.rs", name)), dir.join(name)]
+            })
+            .collect()
+    }
+}
+
+/// Offset of the parenthesis closing the one that ends at `start`.
+fn closing(content: &str, start: usize, ignored: &[Range<usize>]) -> Option<usize> {
+    let mut depth = 0;
+    for (offset, c) in code(content, start, ignored) {
+        match c {
+            b'(' => depth += 1,
+            b')' if depth == 0 => return Some(offset),
+            b')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// End of the item, statement, field or match arm starting after an
+/// attribute at `start`: its closing brace, or the `;` or `,` ending it.
+fn item_end(content: &str, start: usize, ignored: &[Range<usize>]) -> usize {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    // Generic arguments, whose commas do not end the item
+    let mut angle = 0usize;
+    for (offset, c) in code(content, start, ignored) {
+        let previous = offset.checked_sub(1).map(|i| bytes[i]);
+        match c {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' if depth == 0 => return offset,
+            b'}' if depth == 1 => return offset + 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'<' if depth == 0
+                && previous.is_some_and(|b| b.is_ascii_alphanumeric() || b == b':') =>
+            {
+                angle += 1
+            }
+            b'>' if depth == 0 && angle > 0 && !matches!(previous, Some(b'-' | b'=')) => angle -= 1,
+            b';' if depth == 0 => return offset + 1,
+            b',' if depth == 0 && angle == 0 => return offset + 1,
+            _ => {}
+        }
+    }
+    content.len()
+}
+
+/// The bytes of `content` from `start` on with their offsets, leaving out
+/// those in `ignored`. Delimiters are ASCII, so bytes suffice.
+fn code<'a>(
+    content: &'a str,
+    start: usize,
+    ignored: &'a [Range<usize>],
+) -> impl Iterator<Item = (usize, u8)> + 'a {
+    // Spans are ordered, so the next one to skip only moves forward
+    let mut next = ignored.partition_point(|span| span.end <= start);
+    content.as_bytes()[start..]
+        .iter()
+        .enumerate()
+        .map(move |(i, &c)| (start + i, c))
+        .filter(move |(offset, _)| {
+            while ignored.get(next).is_some_and(|span| span.end <= *offset) {
+                next += 1;
+            }
+            !ignored.get(next).is_some_and(|span| span.contains(offset))
+        })
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+fn tokens(predicate: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = predicate.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut value = String::new();
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    tokens
+}
+
+/// Evaluates one predicate; `None` when it depends on more than features.
+fn eval<I>(tokens: &mut std::iter::Peekable<I>, features: &BTreeSet<String>) -> Option<bool>
+where
+    I: Iterator<Item = Token>,
+{
+    let Some(Token::Ident(name)) = tokens.next() else {
+        return None;
+    };
+
+    match tokens.peek() {
+        Some(Token::Punct('(')) => {
+            tokens.next();
+            let mut values = Vec::new();
+            loop {
+                match tokens.peek() {
+                    Some(Token::Punct(')')) | None => {
+                        tokens.next();
+                        break;
+                    }
+                    Some(Token::Punct(',')) => {
+                        tokens.next();
+                    }
+                    _ => values.push(eval(tokens, features)),
+                }
+            }
+            match name.as_str() {
+                "all" if values.contains(&Some(false)) => Some(false),
+                "all" => values
+                    .iter()
+                    .all(|value| *value == Some(true))
+                    .then_some(true),
+                "any" if values.contains(&Some(true)) => Some(true),
+                "any" => values
+                    .iter()
+                    .all(|value| *value == Some(false))
+                    .then_some(false),
+                "not" => values.first().copied().flatten().map(|value| !value),
+                _ => None,
+            }
+        }
+        Some(Token::Punct('=')) => {
+            tokens.next();
+            let value = match tokens.next() {
+                Some(Token::Str(value)) => value,
+                _ => return None,
+            };
+            (name == "feature").then(|| features.contains(&value))
+        }
+        _ => (name == "test").then_some(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// The source text of each item turned off for `enabled`.
+    fn disabled<'a>(content: &'a str, enabled: &[&str]) -> Vec<&'a str> {
+        CfgGates::new()
+            .disabled_spans(content, &features(enabled))
+            .into_iter()
+            .map(|span| &content[span])
+            .collect()
+    }
+
+    fn evaluate(predicate: &str, enabled: &[&str]) -> Option<bool> {
+        eval(
+            &mut tokens(predicate).into_iter().peekable(),
+            &features(enabled),
+        )
+    }
+
+    #[test]
+    fn evaluates_feature_predicates() {
+        assert_eq!(evaluate(r#"feature = "ffi""#, &["ffi"]), Some(true));
+        assert_eq!(evaluate(r#"feature = "ffi""#, &[]), Some(false));
+        assert_eq!(evaluate(r#"not(feature = "ffi")"#, &[]), Some(true));
+        assert_eq!(
+            evaluate(r#"all(feature = "a", feature = "b")"#, &["a"]),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate(r#"any(feature = "a", feature = "b")"#, &["b"]),
+            Some(true)
+        );
+        assert_eq!(evaluate("test", &[]), Some(false));
+        // Other predicates may hold, unless features alone decide
+        assert_eq!(evaluate("unix", &[]), None);
+        assert_eq!(evaluate(r#"all(unix, feature = "ffi")"#, &["ffi"]), None);
+        assert_eq!(evaluate(r#"all(unix, feature = "ffi")"#, &[]), Some(false));
+    }
+
+    #[test]
+    fn disables_gated_items_statements_and_fields() {
+        let content = r#"
+#[cfg(feature = "ffi")]
+fn ffi() { unsafe { call() } }
+
+#[cfg(not(feature = "ffi"))]
+fn fallback() {}
+
+struct S {
+    #[cfg(feature = "ffi")]
+    raw: *mut u8,
+    len: usize,
+}
+
+fn f() {
+    #[cfg(feature = "ffi")]
+    let shell = std::process::Command::new("sh");
+}
+"#;
+
+        assert_eq!(
+            disabled(content, &[]),
+            [
+                "#[cfg(feature = \"ffi\")]\nfn ffi() { unsafe { call() } }",
+                "#[cfg(feature = \"ffi\")]\n    raw: *mut u8,",
+                "#[cfg(feature = \"ffi\")]\n    let shell = std::process::Command::new(\"sh\");",
+            ]
+        );
+        assert_eq!(
+            disabled(content, &["ffi"]),
+            ["#[cfg(not(feature = \"ffi\"))]\nfn fallback() {}"]
+        );
+    }
+
+    #[test]
+    fn inner_attributes_disable_the_whole_file() {
+        let content = "#![cfg(feature = \"ffi\")]\nfn ffi() {}\n";
+
+        assert_eq!(disabled(content, &[]), [content]);
+        assert!(disabled(content, &["ffi"]).is_empty());
+    }
+
+    #[test]
+    fn finds_the_files_of_disabled_modules() {
+        let content = "#[cfg(feature = \"ffi\")]\nmod ffi;\nmod safe;\n#[cfg(test)]\nmod tests;\n";
+
+        let gates = CfgGates::new();
+        let modules = gates.disabled_modules(Path::new("src/net.rs"), content, &features(&[]));
+
+        assert_eq!(
+            modules,
+            [
+                PathBuf::from("src/net/ffi.rs"),
+                PathBuf::from("src/net/ffi"),
+                PathBuf::from("src/net/tests.rs"),
+                PathBuf::from("src/net/tests"),
+            ]
+        );
+    }
+}
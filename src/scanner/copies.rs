@@ -0,0 +1,224 @@
+use cargo_metadata::Package;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::{lexer, read_source};
+use crate::models::{Location, SecurityIssue, Severity};
+
+/// Digests of well-known source files that crates copy instead of
+/// depending on their crate, bundled so that the check works offline: one
+/// `<digest> <crate> <version> <path>` per line.
+const KNOWN: &str = include_str!("known_sources.txt");
+
+/// Below this many bytes of normalized code, files are too generic to tell
+/// where they come from.
+const MIN_CODE_LEN: usize = 512;
+
+/// Where a known file comes from.
+#[derive(Debug)]
+struct Origin {
+    krate: &'static str,
+    version: &'static str,
+    path: &'static str,
+}
+
+/// Finds copies of the bundled well-known files in a package's sources.
+#[derive(Debug)]
+pub struct KnownSources {
+    origins: HashMap<&'static str, Origin>,
+}
+
+impl KnownSources {
+    pub fn new() -> Self {
+        let origins = KNOWN
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let digest = fields.next()?;
+                let origin = Origin {
+                    krate: fields.next()?,
+                    version: fields.next()?,
+                    path: fields.next()?,
+                };
+                Some((digest, origin))
+            })
+            .collect();
+        Self { origins }
+    }
+
+    /// Flags the Rust files under `src_dir` of `package` whose code, with
+    /// comments and whitespace left out, is that of a known file of another
+    /// crate.
+    pub fn check(&self, package: &Package, src_dir: &Path) -> Vec<SecurityIssue> {
+        let own_name = normalize_name(&package.name);
+        let files = WalkDir::new(src_dir)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"));
+
+        let mut issues = Vec::new();
+        for entry in files {
+            let Ok(content) = read_source(entry.path()) else {
+                continue;
+            };
+            let Some(origin) =
+                source_digest(&content).and_then(|digest| self.origins.get(digest.as_str()))
+            else {
+                continue;
+            };
+            // A crate shipping its own file, or a renamed release of itself
+            if normalize_name(origin.krate) == own_name {
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(src_dir).unwrap_or(entry.path());
+            issues.push(SecurityIssue {
+                severity: Severity::Medium,
+                description: format!(
+                    "src/{} is a copy of {} from {} {}; advisories for {} do not cover it",
+                    relative.display(),
+                    origin.path,
+                    origin.krate,
+                    origin.version,
+                    origin.krate
+                ),
+                affected_versions: vec![package.version.to_string()],
+                rule_id: Some("GUARD051".to_string()),
+                location: Some(Location {
+                    file: entry.path().to_path_buf(),
+                    line: 1,
+                    column: 1,
+                }),
+                ..Default::default()
+            });
+        }
+        issues
+    }
+}
+
+/// SHA-256 of `source` without comments and whitespace, so that copies
+/// survive reformatting and stripped license headers; `None` for files with
+/// too little code to be told apart.
+fn source_digest(source: &str) -> Option<String> {
+    let comments = lexer::ignored_spans(source)
+        .into_iter()
+        .filter(|span| source[span.clone()].starts_with('/'));
+
+    let mut code = String::with_capacity(source.len());
+    let mut start = 0;
+    for comment in comments {
+        code.push_str(&source[start..comment.start]);
+        start = comment.end;
+    }
+    code.push_str(&source[start..]);
+    code.retain(|c| !c.is_whitespace());
+
+    if code.len() < MIN_CODE_LEN {
+        return None;
+    }
+    Some(
+        Sha256::digest(code.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+/// Crate names compare like crates.io compares them.
+fn normalize_name(name: &str) -> String {
+    name.to_ascii_lowercase().replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// `path` in the sources of `krate` `version` fetched into the cargo
+    /// registry, if they are there.
+    fn registry_source(krate: &str, version: &str, path: &str) -> Option<String> {
+        let cargo_home = env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))?;
+        fs::read_dir(cargo_home.join("registry/src"))
+            .ok()?
+            .flatten()
+            .find_map(|index| {
+                let dir = index.path().join(format!("{}-{}", krate, version));
+                read_source(&dir.join(path)).ok()
+            })
+    }
+
+    /// `source` without its line comments and with every line reindented.
+    fn reformat(source: &str) -> String {
+        source
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("//"))
+            .map(|line| format!("\t{}\n", line.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn bundled_digests_match_the_published_files() {
+        let known = KnownSources::new();
+        assert_eq!(known.origins.len(), 25);
+
+        // Checked against the crates fetched on this machine
+        for (digest, origin) in &known.origins {
+            if let Some(source) = registry_source(origin.krate, origin.version, origin.path) {
+                assert_eq!(
+                    source_digest(&source).as_deref(),
+                    Some(*digest),
+                    "{} {} {}",
+                    origin.krate,
+                    origin.version,
+                    origin.path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reformatted_copies_are_flagged_in_other_crates_only() {
+        // sha2 is a dependency, so its sources have been fetched
+        let source = registry_source("sha2", "0.10.9", "src/sha256/soft.rs")
+            .expect("sha2 0.10.9 in the cargo registry");
+        let mut project = Project::new();
+        project.add("hasher", "1.0.0").add("sha2", "0.10.8");
+        for name in ["hasher", "sha2"] {
+            project.file(name, "src/soft.rs", &reformat(&source));
+        }
+        let check = |name: &str| {
+            KnownSources::new().check(
+                &project.package(name),
+                &project.package_dir(name).join("src"),
+            )
+        };
+
+        let issues = check("hasher");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule_id.as_deref(), Some("GUARD051"));
+        assert_eq!(
+            issues[0].description,
+            "src/soft.rs is a copy of src/sha256/soft.rs from sha2 0.10.9; \
+             advisories for sha2 do not cover it"
+        );
+        // Another release of sha2 itself
+        assert!(check("sha2").is_empty());
+    }
+
+    #[test]
+    fn files_with_little_code_are_not_told_apart() {
+        assert!(source_digest(&"a".repeat(MIN_CODE_LEN - 1)).is_none());
+        assert!(source_digest(&"a".repeat(MIN_CODE_LEN)).is_some());
+        // Comments and whitespace do not count
+        let padded = format!("// {}\n{}\n", "a".repeat(MIN_CODE_LEN), " b".repeat(100));
+        assert!(source_digest(&padded).is_none());
+    }
+}
@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+/// Byte ranges of comments and string literals in Rust source, in order.
+///
+/// This is a lightweight lexer rather than a full tokenizer: it tracks just
+/// enough (char literals, lifetimes, raw string hashes, nested block
+/// comments) to tell code apart from text that merely mentions it.
+pub fn ignored_spans(source: &str) -> Vec<Range<usize>> {
+    let bytes = source.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+                spans.push(start..i);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = block_comment_end(bytes, i);
+                spans.push(start..i);
+            }
+            b'"' => {
+                i = string_end(bytes, i + 1);
+                spans.push(start..i);
+            }
+            b'\'' => i = char_end(source, i),
+            b'b' | b'c' | b'r' if !continues_ident(bytes, i) => match raw_string_end(bytes, i) {
+                Some(end) => {
+                    i = end;
+                    spans.push(start..i);
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+
+    spans
+}
+
+/// Whether `offset` falls inside one of `spans`, which must be sorted.
+pub fn is_ignored(spans: &[Range<usize>], offset: usize) -> bool {
+    let index = spans.partition_point(|span| span.end <= offset);
+    spans.get(index).is_some_and(|span| span.contains(&offset))
+}
+
+/// Byte ranges of the `unsafe` blocks in Rust source, in order, from their
+/// opening to their closing brace. Blocks nested in another one are part of
+/// it; `ignored` are the spans of [`ignored_spans`], which are skipped.
+pub fn unsafe_blocks(source: &str, ignored: &[Range<usize>]) -> Vec<Range<usize>> {
+    let bytes = source.as_bytes();
+    let mut blocks = Vec::new();
+    let mut from = 0;
+
+    while let Some(found) = source[from..].find("unsafe") {
+        let start = from + found;
+        from = start + "unsafe".len();
+        if continues_ident(bytes, start)
+            || bytes
+                .get(from)
+                .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_')
+            || is_ignored(ignored, start)
+        {
+            continue;
+        }
+        let open = from + source[from..].len() - source[from..].trim_start().len();
+        if bytes.get(open) != Some(&b'{') {
+            continue;
+        }
+        let end = block_end(bytes, open, ignored);
+        blocks.push(open..end);
+        from = end;
+    }
+
+    blocks
+}
+
+/// End of the block whose opening brace is at `open`, just past its closing
+/// brace, or the end of `bytes` if it is not closed.
+fn block_end(bytes: &[u8], open: usize, ignored: &[Range<usize>]) -> usize {
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        let index = ignored.partition_point(|span| span.end <= i);
+        if let Some(span) = ignored.get(index).filter(|span| span.contains(&i)) {
+            i = span.end;
+            continue;
+        }
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' if depth == 1 => return i + 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn continues_ident(bytes: &[u8], i: usize) -> bool {
+    i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_')
+}
+
+/// End of a (possibly nested) block comment starting at `i`.
+fn block_comment_end(bytes: &[u8], mut i: usize) -> usize {
+    let mut depth = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// End of a double-quoted string whose contents start at `i`.
+fn string_end(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Skips a char literal, or just the quote of a lifetime or label.
+fn char_end(source: &str, i: usize) -> usize {
+    let bytes = source.as_bytes();
+    if bytes.get(i + 1) == Some(&b'\\') {
+        let mut end = i + 3;
+        while end < bytes.len() && bytes[end] != b'\'' && bytes[end] != b'\n' {
+            end += 1;
+        }
+        return (end + 1).min(bytes.len());
+    }
+
+    match source[i + 1..].chars().next() {
+        Some(c) if bytes.get(i + 1 + c.len_utf8()) == Some(&b'\'') => i + 2 + c.len_utf8(),
+        _ => i + 1,
+    }
+}
+
+/// End of a byte, C or raw string literal (`b"…"`, `c"…"`, `r#"…"#`,
+/// `br"…"`) starting at `i`, if there is one. Byte char literals are left
+/// to the char literal handling of their quote.
+fn raw_string_end(bytes: &[u8], i: usize) -> Option<usize> {
+    let mut j = i;
+    if matches!(bytes[j], b'b' | b'c') {
+        j += 1;
+        match bytes.get(j) {
+            Some(b'"') => return Some(string_end(bytes, j + 1)),
+            Some(b'r') => {}
+            _ => return None,
+        }
+    }
+
+    // Raw string: r, then any number of #, then a quote
+    j += 1;
+    let hashes = bytes[j..].iter().take_while(|&&b| b == b'#').count();
+    j += hashes;
+    if bytes.get(j) != Some(&b'"') {
+        return None;
+    }
+
+    let closing = [b"\"".as_slice(), &bytes[j - hashes..j]].concat();
+    let end = bytes[j + 1..]
+        .windows(closing.len())
+        .position(|window| window == closing)
+        .map_or(bytes.len(), |offset| j + 1 + offset + closing.len());
+    Some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The source text of each ignored span.
+    fn ignored(source: &str) -> Vec<&str> {
+        ignored_spans(source)
+            .into_iter()
+            .map(|span| &source[span])
+            .collect()
+    }
+
+    #[test]
+    fn skips_line_doc_and_nested_block_comments() {
+        let source = "/// unsafe {\nlet a = 1; // unsafe {\n/* outer /* inner */ still */ b";
+
+        assert_eq!(
+            ignored(source),
+            [
+                "/// unsafe {",
+                "// unsafe {",
+                "/* outer /* inner */ still */"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_strings_with_escapes_and_raw_strings() {
+        let source = r###"f("a \"quoted\" unsafe {", r#"raw "unsafe {" "#, br"bytes", c"c");"###;
+
+        assert_eq!(
+            ignored(source),
+            [
+                r#""a \"quoted\" unsafe {""#,
+                r###"r#"raw "unsafe {" "#"###,
+                r#"br"bytes""#,
+                r#"c"c""#
+            ]
+        );
+    }
+
+    #[test]
+    fn char_literals_and_lifetimes_do_not_open_strings() {
+        let source = "fn f<'a>(s: &'a str) -> char { let q = '\"'; let e = '\\''; 'x' }";
+
+        assert!(ignored(source).is_empty());
+    }
+
+    #[test]
+    fn identifiers_ending_in_r_or_b_are_not_string_prefixes() {
+        let source = "let ptr = br; let s = ptr\"x\";";
+
+        assert_eq!(ignored(source), ["\"x\""]);
+    }
+
+    #[test]
+    fn finds_offsets_inside_spans() {
+        let spans = [2..5, 8..10];
+
+        let ignored: Vec<usize> = (0..12).filter(|&i| is_ignored(&spans, i)).collect();
+
+        assert_eq!(ignored, [2, 3, 4, 8, 9]);
+    }
+
+    #[test]
+    fn finds_unsafe_blocks_with_their_nested_blocks() {
+        let source = "unsafe fn f() { g() }
+                      let a = unsafe { if b { \"}\" } else { unsafe { *p } } };
+                      // unsafe { c }
+                      let not_unsafe = { d };";
+
+        let blocks: Vec<&str> = unsafe_blocks(source, &ignored_spans(source))
+            .into_iter()
+            .map(|span| &source[span])
+            .collect();
+
+        assert_eq!(blocks, ["{ if b { \"}\" } else { unsafe { *p } } }"]);
+    }
+}
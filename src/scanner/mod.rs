@@ -1,135 +1,459 @@
-use anyhow::Result;
 use cargo_metadata::Package;
+use rayon::prelude::*;
 use regex::Regex;
-use serde::Serialize;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use walkdir::WalkDir;
 
-use crate::models::{SecurityIssue, Severity};
+use crate::cache::FileCache;
+use crate::config::{Config, PatternConfig};
+use crate::error::{GuardianError, Result};
+use crate::models::{Confidence, Location, SecurityIssue, Severity};
+use crate::rules::{self, Category, Rule};
+use crate::secrets::SecretScanner;
 
-#[derive(Debug, Serialize)]
-pub struct SecurityScan {
-    pub issues: Vec<SecurityIssue>,
-    pub dependencies: HashMap<String, Vec<SecurityIssue>>,
+mod build_fs;
+mod cfg;
+mod copies;
+mod lexer;
+mod profile;
+
+use build_fs::BuildFsCheck;
+use cfg::CfgGates;
+use copies::KnownSources;
+
+/// Directories never descended into: build output and version control.
+/// `target` is scanned again with `include-target`.
+const SKIPPED_DIRS: &[&str] = &["target", ".git"];
+
+/// Version of the built-in checks; bump it whenever they change so that
+/// cached analyses are invalidated.
+pub const PATTERN_SET_VERSION: u32 = 17;
+
+/// Time spent in each stage of scanning one package.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StageTimings {
+    pub version_check: Duration,
+    pub dependency_check: Duration,
+    pub build_script_check: Duration,
+    pub source_scan: Duration,
 }
 
+impl StageTimings {
+    /// The stages by name, in the order they run.
+    pub fn stages(&self) -> [(&'static str, Duration); 4] {
+        [
+            ("version_check", self.version_check),
+            ("dependency_check", self.dependency_check),
+            ("build_script_check", self.build_script_check),
+            ("source_scan", self.source_scan),
+        ]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stages().iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+}
+
+#[derive(Debug)]
+struct Pattern {
+    id: String,
+    regex: Regex,
+    /// Matches whose text also matches this are not reported
+    unless: Option<Regex>,
+    /// Also matched, within `unsafe` blocks only
+    in_unsafe: Option<Regex>,
+    description: String,
+    severity: Severity,
+    confidence: Confidence,
+}
+
+impl Pattern {
+    fn from_rule(rule: &Rule) -> Self {
+        Self {
+            id: rule.id.to_string(),
+            regex: Regex::new(rule.pattern.expect("pattern rule")).unwrap(),
+            unless: rule.unless.map(|unless| Regex::new(unless).unwrap()),
+            in_unsafe: rule.in_unsafe.map(|pattern| Regex::new(pattern).unwrap()),
+            description: rule.description.to_string(),
+            severity: rule.severity.clone(),
+            confidence: rule.confidence,
+        }
+    }
+
+    fn from_config(config: &PatternConfig) -> Result<Self> {
+        Ok(Self {
+            id: config.id.clone(),
+            regex: Regex::new(&config.regex).map_err(|err| {
+                GuardianError::config(format!("Invalid regex for pattern {}", config.id), err)
+            })?,
+            unless: None,
+            in_unsafe: None,
+            description: config.description.clone(),
+            severity: config.severity.clone(),
+            confidence: config.confidence.unwrap_or(Confidence::Medium),
+        })
+    }
+}
+
+#[derive(Debug)]
 pub struct SecurityScanner {
-    patterns: Vec<(Regex, String, Severity)>,
+    patterns: Vec<Pattern>,
+    build_patterns: Vec<Pattern>,
+    build_fs: Option<BuildFsCheck>,
+    cfg_gates: CfgGates,
+    secrets: Option<SecretScanner>,
+    known_sources: Option<KnownSources>,
+    include_comments: bool,
+    /// Names of the directories a directory scan does not enter
+    skipped_dirs: Vec<String>,
+    file_cache: Option<FileCache>,
+    /// Digest of the checks above, part of every file cache key
+    cache_salt: Vec<u8>,
+    /// `cargo vendor` output holding the sources of non-local packages
+    vendor_dir: Option<PathBuf>,
 }
 
 impl SecurityScanner {
-    pub fn new() -> Result<Self> {
-        let patterns = vec![
-            // Memory safety patterns
-            (
-                Regex::new(r"unsafe\s*\{").unwrap(),
-                "Contains unsafe blocks - review for memory safety".to_string(),
-                Severity::High,
-            ),
-            (
-                Regex::new(r"std::mem::transmute").unwrap(),
-                "Uses memory transmutation - potential type safety issues".to_string(),
-                Severity::High,
-            ),
-            // FFI patterns
-            (
-                Regex::new(r"#!\[no_std\]").unwrap(),
-                "No standard library usage - verify safety implementations".to_string(),
-                Severity::Medium,
-            ),
-            (
-                Regex::new(r"extern\s*C").unwrap(),
-                "FFI usage detected - validate memory safety".to_string(),
-                Severity::Medium,
-            ),
-            // Common vulnerability patterns
-            (
-                Regex::new(r"eval\s*\(").unwrap(),
-                "Code evaluation detected - potential security risk".to_string(),
-                Severity::Critical,
-            ),
-            (
-                Regex::new(r"std::process::Command").unwrap(),
-                "Process execution capabilities - review for command injection".to_string(),
-                Severity::High,
-            ),
-            // File operation patterns
-            (
-                Regex::new(r"std::fs::(write|create|remove)").unwrap(),
-                "File system modification - review for proper permissions".to_string(),
-                Severity::Medium,
-            ),
-            // Network related patterns
-            (
-                Regex::new(r"TcpListener::bind").unwrap(),
-                "Network listener - verify proper security controls".to_string(),
-                Severity::Medium,
-            ),
-        ];
+    pub fn new(config: &Config) -> Result<Self> {
+        let builtin = |category| {
+            rules::RULES
+                .iter()
+                .filter(move |rule| rule.category == category)
+                .filter(|rule| rule.pattern.is_some() && config.is_rule_enabled(rule.id))
+                .map(Pattern::from_rule)
+        };
 
-        Ok(Self { patterns })
+        let mut patterns: Vec<Pattern> = builtin(Category::Source).collect();
+        for custom in &config.patterns {
+            patterns.push(Pattern::from_config(custom)?);
+        }
+        // Operations that a build script has no business performing
+        let build_patterns = builtin(Category::BuildScript).collect();
+
+        let mut scanner = Self {
+            patterns,
+            build_patterns,
+            build_fs: BuildFsCheck::new(config),
+            cfg_gates: CfgGates::new(),
+            secrets: config.secrets.then(|| SecretScanner::new(config)),
+            known_sources: config.is_rule_enabled("GUARD051").then(KnownSources::new),
+            include_comments: config.include_comments,
+            skipped_dirs: SKIPPED_DIRS
+                .iter()
+                .filter(|&&name| !(name == "target" && config.include_target))
+                .map(|name| name.to_string())
+                .chain(config.exclude_dirs.iter().cloned())
+                .collect(),
+            file_cache: None,
+            cache_salt: Vec::new(),
+            vendor_dir: config.vendor_dir.clone(),
+        };
+        if config.cache {
+            match FileCache::new() {
+                Ok(cache) => scanner.file_cache = Some(cache),
+                Err(err) => debug!("File scan cache unavailable: {}", err),
+            }
+            scanner.cache_salt = Sha256::digest(format!(
+                "{} {:?} {:?} {:?} {}",
+                PATTERN_SET_VERSION,
+                scanner.patterns,
+                scanner.build_patterns,
+                scanner.secrets,
+                scanner.include_comments
+            ))
+            .to_vec();
+        }
+        Ok(scanner)
+    }
+
+    /// File cache hits and misses so far, when the cache is enabled.
+    pub fn cache_stats(&self) -> Option<(usize, usize)> {
+        self.file_cache.as_ref().map(FileCache::stats)
     }
 
     pub fn scan_package(&self, package: &Package) -> Result<Vec<SecurityIssue>> {
+        self.scan_package_with(package, None)
+    }
+
+    /// Like [`scan_package`](Self::scan_package), leaving out source code
+    /// that `#[cfg(feature = "...")]` (or `#[cfg(test)]`) excludes from a
+    /// build with `features` enabled: the findings inside gated items, and
+    /// module files declared behind a gate.
+    pub fn scan_package_with(
+        &self,
+        package: &Package,
+        features: Option<&BTreeSet<String>>,
+    ) -> Result<Vec<SecurityIssue>> {
+        self.scan_package_timed(package, features)
+            .map(|(issues, _)| issues)
+    }
+
+    /// Like [`scan_package_with`](Self::scan_package_with), also returning
+    /// the time each stage of the scan took.
+    #[cfg_attr(
+        feature = "otel",
+        tracing::instrument(
+            name = "scan_package",
+            target = "telemetry",
+            skip_all,
+            fields(package = %package.name, version = %package.version, issues)
+        )
+    )]
+    pub fn scan_package_timed(
+        &self,
+        package: &Package,
+        features: Option<&BTreeSet<String>>,
+    ) -> Result<(Vec<SecurityIssue>, StageTimings)> {
         let mut issues = Vec::new();
+        let mut timings = StageTimings::default();
 
         // Version checks
+        let started = Instant::now();
         self.check_version(package, &mut issues);
+        timings.version_check = started.elapsed();
         
         // Dependency checks
+        let started = Instant::now();
         self.check_dependencies(package, &mut issues);
+        self.check_profiles(package, &mut issues);
+        timings.dependency_check = started.elapsed();
         
         // Build script checks
+        let started = Instant::now();
         self.check_build_scripts(package, &mut issues);
+        timings.build_script_check = started.elapsed();
 
         // Source code analysis
-        if let Some(manifest_path) = package.manifest_path.parent() {
-            let src_dir = PathBuf::from(manifest_path.as_str()).join("src");
+        let started = Instant::now();
+        if let Some(package_dir) = self.package_dir(package) {
+            let src_dir = package_dir.join("src");
             if src_dir.exists() {
-                self.scan_directory(&src_dir, &mut issues)?;
+                let gated = features.map(|features| Gated {
+                    features,
+                    excluded: self.gated_modules(&src_dir, features),
+                });
+                let source_issues = Mutex::new(Vec::new());
+                let visited = Mutex::new(HashSet::new());
+                self.scan_directory(&src_dir, gated.as_ref(), &visited, &source_issues)
+                    .map_err(|err| GuardianError::Scanner {
+                        package: package.name.clone(),
+                        source: err.into(),
+                    })?;
+                issues.extend(source_issues.into_inner().unwrap());
+                if let Some(known_sources) = &self.known_sources {
+                    issues.extend(known_sources.check(package, &src_dir));
+                }
             }
         }
+        timings.source_scan = started.elapsed();
+
+        sort_issues(&mut issues);
+        #[cfg(feature = "otel")]
+        tracing::Span::current().record("issues", issues.len());
+        Ok((issues, timings))
+    }
 
+    /// Runs the source patterns, and the secret checks when enabled, over
+    /// the Rust files at `path`: a single file, or a directory scanned
+    /// recursively. `path` need not belong to a cargo project.
+    pub fn scan_path(&self, path: &Path) -> Result<Vec<SecurityIssue>> {
+        let scanner_error = |err: GuardianError| GuardianError::Scanner {
+            package: path.display().to_string(),
+            source: err.into(),
+        };
+        let mut issues = Vec::new();
+        if path.is_dir() {
+            let found = Mutex::new(Vec::new());
+            let visited = Mutex::new(HashSet::new());
+            self.scan_directory(path, None, &visited, &found)
+                .map_err(scanner_error)?;
+            issues = found.into_inner().unwrap();
+        } else {
+            self.scan_file(path, &self.patterns, None, &mut issues)
+                .map_err(scanner_error)?;
+        }
+        sort_issues(&mut issues);
         Ok(issues)
     }
 
-    fn scan_directory(&self, dir: &Path, issues: &mut Vec<SecurityIssue>) -> Result<()> {
+    /// Module files under `src_dir` declared behind a `cfg` that `features`
+    /// turn off, with the directories of their submodules.
+    fn gated_modules(&self, src_dir: &Path, features: &BTreeSet<String>) -> HashSet<PathBuf> {
+        let files: Vec<_> = WalkDir::new(src_dir)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+            .collect();
+        files
+            .par_iter()
+            .flat_map_iter(|entry| {
+                let Ok(content) = read_source(entry.path()) else {
+                    return Vec::new();
+                };
+                self.cfg_gates
+                    .disabled_modules(entry.path(), &content, features)
+            })
+            .collect()
+    }
+
+    /// Scans the Rust files under `dir`, skipping `skipped_dirs` below it.
+    /// Symbolic links are followed, but no directory is scanned twice, so
+    /// links back up the tree do not loop.
+    fn scan_directory(
+        &self,
+        dir: &Path,
+        gated: Option<&Gated>,
+        visited: &Mutex<HashSet<PathBuf>>,
+        issues: &Mutex<Vec<SecurityIssue>>,
+    ) -> Result<()> {
         if dir.is_dir() {
-            for entry in fs::read_dir(dir)? {
-                let entry = entry?;
+            let canonical = fs::canonicalize(dir).map_err(|err| GuardianError::io(dir, err))?;
+            if !visited.lock().unwrap().insert(canonical) {
+                debug!("Skipping {}, already scanned", dir.display());
+                return Ok(());
+            }
+            let entries = fs::read_dir(dir)
+                .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+                .map_err(|err| GuardianError::io(dir, err))?;
+
+            entries.par_iter().try_for_each(|entry| -> Result<()> {
                 let path = entry.path();
-                
+                if gated.is_some_and(|gated| gated.excludes(&path)) {
+                    return Ok(());
+                }
+
                 if path.is_dir() {
-                    self.scan_directory(&path, issues)?;
-                } else if let Some(ext) = path.extension() {
-                    if ext == "rs" {
-                        self.scan_file(&path, issues)?;
+                    let name = entry.file_name();
+                    if self.skipped_dirs.iter().any(|dir| name == dir.as_str()) {
+                        return Ok(());
                     }
+                    self.scan_directory(&path, gated, visited, issues)?;
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let mut file_issues = Vec::new();
+                    let features = gated.map(|gated| gated.features);
+                    self.scan_file(&path, &self.patterns, features, &mut file_issues)?;
+                    issues.lock().unwrap().extend(file_issues);
                 }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn scan_file(
+        &self,
+        file: &Path,
+        patterns: &[Pattern],
+        features: Option<&BTreeSet<String>>,
+        issues: &mut Vec<SecurityIssue>,
+    ) -> Result<()> {
+        let content = read_source(file).map_err(|err| GuardianError::io(file, err))?;
+        let Some(cache) = &self.file_cache else {
+            self.scan_content(file, &content, patterns, features, issues);
+            return Ok(());
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(&self.cache_salt);
+        for pattern in patterns {
+            hasher.update(pattern.id.as_bytes());
+        }
+        if let Some(features) = features {
+            hasher.update(b"features");
+            for feature in features {
+                hasher.update(feature.as_bytes());
+                hasher.update(b"\0");
             }
         }
+        hasher.update(content.as_bytes());
+        let key: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        if let Some(cached) = cache.load(&key, file) {
+            issues.extend(cached);
+            return Ok(());
+        }
+        let mut found = Vec::new();
+        self.scan_content(file, &content, patterns, features, &mut found);
+        if let Err(err) = cache.store(&key, &found) {
+            debug!("Failed to cache scan of {}: {}", file.display(), err);
+        }
+        issues.extend(found);
         Ok(())
     }
 
-    fn scan_file(&self, file: &Path, issues: &mut Vec<SecurityIssue>) -> Result<()> {
-        let content = fs::read_to_string(file)?;
-        
-        for (pattern, description, severity) in &self.patterns {
-            if pattern.is_match(&content) {
+    fn scan_content(
+        &self,
+        file: &Path,
+        content: &str,
+        patterns: &[Pattern],
+        features: Option<&BTreeSet<String>>,
+        issues: &mut Vec<SecurityIssue>,
+    ) {
+        let line_starts = line_starts(content);
+        let ignored = if self.include_comments {
+            Vec::new()
+        } else {
+            lexer::ignored_spans(content)
+        };
+        let disabled = match features {
+            Some(features) => self.cfg_gates.disabled_spans(content, features),
+            None => Vec::new(),
+        };
+        let is_disabled = |offset: usize| lexer::is_ignored(&disabled, offset);
+        // Found on first use, as only a few rules look inside unsafe blocks
+        let mut unsafe_blocks = None;
+
+        for pattern in patterns {
+            let mut matches: Vec<_> = pattern.regex.find_iter(content).collect();
+            if let Some(in_unsafe) = &pattern.in_unsafe {
+                let blocks = unsafe_blocks.get_or_insert_with(|| {
+                    lexer::unsafe_blocks(content, &lexer::ignored_spans(content))
+                });
+                let starts: BTreeSet<usize> = matches.iter().map(|found| found.start()).collect();
+                matches.extend(in_unsafe.find_iter(content).filter(|found| {
+                    !starts.contains(&found.start()) && lexer::is_ignored(blocks, found.start())
+                }));
+                matches.sort_by_key(|found| found.start());
+            }
+            for found in matches {
+                if lexer::is_ignored(&ignored, found.start()) || is_disabled(found.start()) {
+                    continue;
+                }
+                if let Some(unless) = &pattern.unless {
+                    if unless.is_match(found.as_str()) {
+                        continue;
+                    }
+                }
                 issues.push(SecurityIssue {
-                    severity: severity.clone(),
-                    description: format!(
-                        "{} in {}",
-                        description,
-                        file.display()
-                    ),
-                    affected_versions: vec![],
-                    fix_version: None,
+                    severity: pattern.severity.clone(),
+                    description: pattern.description.clone(),
+                    rule_id: Some(pattern.id.clone()),
+                    confidence: pattern.confidence,
+                    location: Some(locate(file, content, &line_starts, found.start())),
+                    ..Default::default()
                 });
             }
         }
-        Ok(())
+
+        if let Some(secrets) = &self.secrets {
+            let mut found = Vec::new();
+            secrets.scan(file, content, &line_starts, &mut found);
+            found.retain(|issue| match &issue.location {
+                Some(location) => !is_disabled(offset(content, &line_starts, location)),
+                None => true,
+            });
+            issues.extend(found);
+        }
     }
 
     fn check_version(&self, package: &Package, issues: &mut Vec<SecurityIssue>) {
@@ -141,7 +465,8 @@ impl SecurityScanner {
                     package.name, package.version
                 ),
                 affected_versions: vec![package.version.to_string()],
-                fix_version: None,
+                rule_id: Some("GUARD009".to_string()),
+                ..Default::default()
             });
         }
     }
@@ -156,7 +481,8 @@ impl SecurityScanner {
                     package.dependencies.len()
                 ),
                 affected_versions: vec![package.version.to_string()],
-                fix_version: None,
+                rule_id: Some("GUARD010".to_string()),
+                ..Default::default()
             });
         }
 
@@ -170,14 +496,61 @@ impl SecurityScanner {
                         dep.name
                     ),
                     affected_versions: vec![package.version.to_string()],
-                    fix_version: None,
+                    rule_id: Some("GUARD011".to_string()),
+                    ..Default::default()
                 });
             }
         }
     }
 
+    /// Reports the `[profile]` settings of the package's own manifest that
+    /// weaken run-time checks. Cargo only honors the profiles of the
+    /// workspace root, so for dependencies they show how the crate is meant
+    /// to be built rather than how it is.
+    fn check_profiles(&self, package: &Package, issues: &mut Vec<SecurityIssue>) {
+        let Some(path) = self.package_dir(package).map(|dir| dir.join("Cargo.toml")) else {
+            return;
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => issues.extend(profile::check(package, &path, &content)),
+            Err(err) => debug!("Failed to read manifest {}: {}", path.display(), err),
+        }
+    }
+
+    /// Reports build scripts, and specifically the risky operations in them:
+    /// a build script that shells out, downloads or touches files outside
+    /// the build is raised to High.
     fn check_build_scripts(&self, package: &Package, issues: &mut Vec<SecurityIssue>) {
-        if package.targets.iter().any(|t| t.kind.contains(&"custom-build".to_string())) {
+        let Some(build_script) = package
+            .targets
+            .iter()
+            .find(|t| t.kind.contains(&"custom-build".to_string()))
+        else {
+            return;
+        };
+
+        let mut findings = Vec::new();
+        let path = build_script.src_path.as_std_path();
+        // The build script of a vendored package is at the same place in its
+        // vendored copy
+        let vendored = self.package_dir(package).and_then(|dir| {
+            let original = package.manifest_path.parent()?;
+            Some(dir.join(path.strip_prefix(original).ok()?))
+        });
+        let path = vendored.as_deref().unwrap_or(path);
+        if path.is_file() {
+            if let Err(err) = self.scan_file(path, &self.build_patterns, None, &mut findings) {
+                debug!("Failed to scan build script {}: {}", path.display(), err);
+            }
+            if let Some(check) = &self.build_fs {
+                match read_source(path) {
+                    Ok(content) => self.check_build_fs(check, path, &content, &mut findings),
+                    Err(err) => debug!("Failed to read build script {}: {}", path.display(), err),
+                }
+            }
+        }
+
+        if findings.is_empty() {
             issues.push(SecurityIssue {
                 severity: Severity::Medium,
                 description: format!(
@@ -185,8 +558,750 @@ impl SecurityScanner {
                     package.name
                 ),
                 affected_versions: vec![package.version.to_string()],
-                fix_version: None,
+                rule_id: Some("GUARD012".to_string()),
+                ..Default::default()
             });
         }
+
+        for finding in &mut findings {
+            finding.affected_versions = vec![package.version.to_string()];
+        }
+        issues.extend(findings);
+    }
+
+    /// The directory holding the sources of `package`: with a vendor
+    /// directory, the vendored copy of a registry or git package, otherwise
+    /// the directory of its manifest.
+    pub fn package_dir(&self, package: &Package) -> Option<PathBuf> {
+        let manifest_dir = package.manifest_path.parent()?.as_std_path();
+        let vendor_dir = match &self.vendor_dir {
+            Some(dir) if package.source.is_some() => dir,
+            _ => return Some(manifest_dir.to_path_buf()),
+        };
+
+        // `cargo vendor` names the directory after the crate, adding the
+        // version to all but one when a crate is vendored at several
+        let candidates = [
+            format!("{}-{}", package.name, package.version),
+            package.name.clone(),
+        ];
+        let vendored = candidates
+            .iter()
+            .map(|name| vendor_dir.join(name))
+            .find(|dir| vendored_version(dir).as_deref() == Some(&package.version.to_string()));
+        if vendored.is_none() {
+            debug!(
+                "{} {} is not vendored in {}",
+                package.name,
+                package.version,
+                vendor_dir.display()
+            );
+        }
+        vendored.or_else(|| Some(manifest_dir.to_path_buf()))
+    }
+
+    fn check_build_fs(
+        &self,
+        check: &BuildFsCheck,
+        file: &Path,
+        content: &str,
+        issues: &mut Vec<SecurityIssue>,
+    ) {
+        let line_starts = line_starts(content);
+        let ignored = if self.include_comments {
+            Vec::new()
+        } else {
+            lexer::ignored_spans(content)
+        };
+        check.scan(file, content, &line_starts, &ignored, issues);
+    }
+}
+
+/// The version in the manifest of the vendored crate in `dir`.
+fn vendored_version(dir: &Path) -> Option<String> {
+    let manifest: toml::Table = fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()?
+        .parse()
+        .ok()?;
+    let version = manifest.get("package")?.get("version")?;
+    version.as_str().map(str::to_string)
+}
+
+/// The enabled features of the package being scanned, and the module files
+/// and directories they turn off.
+struct Gated<'a> {
+    features: &'a BTreeSet<String>,
+    excluded: HashSet<PathBuf>,
+}
+
+impl Gated<'_> {
+    /// Directories are checked before they are entered, so their contents
+    /// need no check of their own.
+    fn excludes(&self, path: &Path) -> bool {
+        self.excluded.contains(path)
+    }
+}
+
+/// Orders issues by file, line and column so that parallel scans produce
+/// stable output; issues without a location keep their relative order first.
+pub fn sort_issues(issues: &mut [SecurityIssue]) {
+    issues.sort_by(|a, b| {
+        let key = |issue: &SecurityIssue| {
+            issue
+                .location
+                .as_ref()
+                .map(|location| (location.file.clone(), location.line, location.column))
+        };
+        key(a).cmp(&key(b))
+    });
+}
+
+/// Reads a source file, replacing invalid UTF-8 rather than failing, so
+/// that one stray byte does not abort the scan of a whole package.
+fn read_source(path: &Path) -> std::io::Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(err) => {
+            warn!(
+                "{} is not valid UTF-8, scanning it with the invalid bytes replaced",
+                path.display()
+            );
+            String::from_utf8_lossy(err.as_bytes()).into_owned()
+        }
+    })
+}
+
+/// Byte offsets at which the lines of `content` start. Lines end at `\n`,
+/// so a CRLF file has the same lines as its LF copy; the `\r` is the last
+/// character of its line and never shifts a column.
+pub fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Converts a location back into a byte offset; the inverse of [`locate`].
+fn offset(content: &str, line_starts: &[usize], location: &Location) -> usize {
+    let start = line_starts[location.line - 1];
+    content[start..]
+        .char_indices()
+        .nth(location.column - 1)
+        .map_or(content.len(), |(offset, _)| start + offset)
+}
+
+/// Converts a byte offset into a 1-based line and character column.
+pub fn locate(file: &Path, content: &str, line_starts: &[usize], offset: usize) -> Location {
+    let line = line_starts.partition_point(|&start| start <= offset);
+    let column = content[line_starts[line - 1]..offset].chars().count() + 1;
+
+    Location {
+        file: normalize_separators(file),
+        line,
+        column,
+    }
+}
+
+/// `path` with `/` separators on Windows, where joined paths mix `\` with
+/// the `/` of paths given on the command line; unchanged elsewhere, where
+/// `\` can be part of a file name.
+fn normalize_separators(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(path.to_string_lossy().replace('\\', "/"))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_patterns;
+    use crate::testing::Project;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn config() -> Config {
+        Config {
+            cache: false,
+            ..Config::default()
+        }
+    }
+
+    /// Scans `source`, saved as `lib.rs` in a temporary directory.
+    fn scan(config: &Config, source: &str) -> Vec<SecurityIssue> {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, source).unwrap();
+        SecurityScanner::new(config)
+            .unwrap()
+            .scan_path(&file)
+            .unwrap()
+    }
+
+    /// The rule and line of each issue.
+    fn findings(issues: &[SecurityIssue]) -> Vec<(&str, usize)> {
+        issues
+            .iter()
+            .map(|issue| {
+                let line = issue.location.as_ref().map_or(0, |location| location.line);
+                (issue.rule_id.as_deref().unwrap_or_default(), line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn custom_patterns_from_a_file_fire() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("patterns.toml");
+        fs::write(
+            &path,
+            "[[patterns]]\n\
+             id = \"ORG001\"\n\
+             regex = 'legacy_crypto::encrypt\\s*\\('\n\
+             description = \"Deprecated internal crypto\"\n\
+             severity = \"critical\"\n",
+        )
+        .unwrap();
+        let config = Config {
+            patterns: load_patterns(&path).unwrap(),
+            ..config()
+        };
+
+        let issues = scan(
+            &config,
+            "fn seal(data: &[u8]) -> Vec<u8> {\n    legacy_crypto::encrypt(data)\n}\n",
+        );
+
+        assert_eq!(findings(&issues), [("ORG001", 2)]);
+        assert_eq!(issues[0].severity, Severity::Critical);
+        assert_eq!(issues[0].confidence, Confidence::Medium);
+        assert_eq!(issues[0].description, "Deprecated internal crypto");
+    }
+
+    #[test]
+    fn disabled_built_in_patterns_do_not_fire() {
+        let source = "use std::process::Command;\nfn f() { unsafe { g() } }\n";
+        let config = Config {
+            disabled_rules: vec!["GUARD001".to_string()],
+            ..config()
+        };
+
+        assert_eq!(
+            findings(&scan(&self::config(), source)),
+            [("GUARD006", 1), ("GUARD001", 2)]
+        );
+        assert_eq!(findings(&scan(&config, source)), [("GUARD006", 1)]);
+    }
+
+    #[test]
+    fn unsafe_in_comments_and_strings_is_not_reported() {
+        let source = "\
+            /// Wraps `unsafe { ffi() }` safely.\n\
+            fn f() {\n\
+                // unsafe { no }\n\
+                let s = \"unsafe { no }\";\n\
+                /* unsafe { no } */\n\
+                unsafe { ffi() }\n\
+            }\n";
+
+        assert_eq!(findings(&scan(&config(), source)), [("GUARD001", 6)]);
+
+        let config = Config {
+            include_comments: true,
+            ..config()
+        };
+        let lines: Vec<usize> = findings(&scan(&config, source))
+            .iter()
+            .map(|(_, line)| *line)
+            .collect();
+        assert_eq!(lines, [1, 3, 4, 5, 6]);
+    }
+
+    /// Scans the package `dep` with the build script `build`.
+    fn scan_build_script(build: &str) -> Vec<SecurityIssue> {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0");
+        let dir = project.package_dir("dep");
+        project
+            .set(
+                "dep",
+                "targets",
+                json!([{
+                    "name": "build-script-build",
+                    "kind": ["custom-build"],
+                    "src_path": dir.join("build.rs"),
+                }]),
+            )
+            .file("dep", "build.rs", build);
+        SecurityScanner::new(&config())
+            .unwrap()
+            .scan_package(&project.package("dep"))
+            .unwrap()
+    }
+
+    #[test]
+    fn build_scripts_without_risky_operations_are_reported_once() {
+        let issues = scan_build_script(
+            "fn main() {\n\
+                 let out = std::env::var(\"OUT_DIR\").unwrap();\n\
+                 std::fs::write(format!(\"{}/v.rs\", out), \"\").unwrap();\n\
+             }\n",
+        );
+
+        let rules: Vec<&str> = findings(&issues)
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect();
+        assert_eq!(rules, ["GUARD012"]);
+    }
+
+    #[test]
+    fn risky_build_scripts_are_reported_by_operation() {
+        let issues = scan_build_script(
+            "use std::process::Command;\n\
+             fn main() {\n\
+                 Command::new(\"curl\").arg(\"https://example.com/x.sh\").status().unwrap();\n\
+                 std::fs::write(\"/usr/local/bin/x\", \"\").unwrap();\n\
+             }\n",
+        );
+
+        let mut found = findings(&issues);
+        found.sort();
+        assert_eq!(found, [("GUARD017", 3), ("GUARD019", 4)]);
+        assert!(issues
+            .iter()
+            .all(|issue| issue.severity == Severity::High && issue.affected_versions == ["1.0.0"]));
+    }
+
+    #[test]
+    fn code_gated_off_by_the_features_is_not_scanned() {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0");
+        project
+            .file(
+                "dep",
+                "src/lib.rs",
+                "#[cfg(feature = \"ffi\")]\n\
+                 mod ffi;\n\
+                 #[cfg(feature = \"shell\")]\n\
+                 pub fn run() { std::process::Command::new(\"sh\"); }\n\
+                 pub fn get(v: &[u8]) -> u8 { unsafe { *v.get_unchecked(0) } }\n",
+            )
+            .file(
+                "dep",
+                "src/ffi.rs",
+                "pub fn cast(x: u32) -> f32 { unsafe { std::mem::transmute(x) } }\n",
+            );
+        let scanner = SecurityScanner::new(&config()).unwrap();
+        let package = project.package("dep");
+        let rules = |features: Option<&[&str]>| -> Vec<String> {
+            let features: Option<BTreeSet<String>> =
+                features.map(|names| names.iter().map(|name| name.to_string()).collect());
+            let issues = scanner
+                .scan_package_with(&package, features.as_ref())
+                .unwrap();
+            let mut rules: Vec<String> = issues
+                .into_iter()
+                .filter_map(|issue| issue.rule_id)
+                .collect();
+            rules.sort();
+            rules
+        };
+
+        // Everything by default
+        assert_eq!(
+            rules(None),
+            ["GUARD001", "GUARD001", "GUARD002", "GUARD006", "GUARD056"]
+        );
+        assert_eq!(rules(Some(&[])), ["GUARD001", "GUARD056"]);
+        assert_eq!(
+            rules(Some(&["ffi"])),
+            ["GUARD001", "GUARD001", "GUARD002", "GUARD056"]
+        );
+    }
+
+    #[test]
+    fn vendored_sources_are_scanned_instead_of_the_registry_copy() {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0").add("other", "2.0.0");
+        project.file("dep", "src/lib.rs", "pub fn f() {}\n");
+        let vendor = TempDir::new().unwrap();
+        let vendored = vendor.path().join("dep");
+        fs::create_dir_all(vendored.join("src")).unwrap();
+        fs::write(
+            vendored.join("Cargo.toml"),
+            "[package]\nname = \"dep\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            vendored.join("src/lib.rs"),
+            "pub fn f() {}\npub fn g(p: *const u8) -> u8 { unsafe { *p } }\n",
+        )
+        .unwrap();
+        let config = Config {
+            vendor_dir: Some(vendor.path().to_path_buf()),
+            ..config()
+        };
+        let scanner = SecurityScanner::new(&config).unwrap();
+
+        assert_eq!(scanner.package_dir(&project.package("dep")), Some(vendored));
+        let issues = scanner.scan_package(&project.package("dep")).unwrap();
+        assert_eq!(findings(&issues), [("GUARD001", 2), ("GUARD056", 2)]);
+        assert!(issues[0]
+            .location
+            .as_ref()
+            .unwrap()
+            .file
+            .starts_with(vendor.path()));
+
+        // Not vendored: the registry copy
+        assert_eq!(
+            scanner.package_dir(&project.package("other")),
+            Some(project.package_dir("other"))
+        );
+        // Path dependencies are never vendored
+        assert_eq!(
+            scanner.package_dir(&project.package("app")),
+            Some(project.root())
+        );
+        assert!(SecurityScanner::new(&self::config())
+            .unwrap()
+            .scan_package(&project.package("dep"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn vendored_copies_are_matched_by_version() {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0");
+        let vendor = TempDir::new().unwrap();
+        for (dir, version) in [("dep", "2.0.0"), ("dep-1.0.0", "1.0.0")] {
+            let dir = vendor.path().join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("Cargo.toml"),
+                format!("[package]\nname = \"dep\"\nversion = \"{}\"\n", version),
+            )
+            .unwrap();
+        }
+        let config = Config {
+            vendor_dir: Some(vendor.path().to_path_buf()),
+            ..config()
+        };
+
+        assert_eq!(
+            SecurityScanner::new(&config)
+                .unwrap()
+                .package_dir(&project.package("dep")),
+            Some(vendor.path().join("dep-1.0.0"))
+        );
+    }
+
+    #[test]
+    fn exported_symbols_are_reported() {
+        let issues = scan(
+            &config(),
+            "#[no_mangle]\n\
+             pub extern fn malloc(size: usize) -> *mut u8 { todo!() }\n\
+             #[export_name = \"free\"]\n\
+             pub fn release() {}\n\
+             #[unsafe(no_mangle)]\n\
+             pub fn init() {}\n\
+             #[inline]\n\
+             pub fn mangled() {}\n",
+        );
+
+        let exported: Vec<(&str, usize)> = findings(&issues)
+            .into_iter()
+            .filter(|(rule, _)| *rule == "GUARD039")
+            .collect();
+        assert_eq!(
+            exported,
+            [("GUARD039", 1), ("GUARD039", 3), ("GUARD039", 5)]
+        );
+        assert!(issues
+            .iter()
+            .filter(|issue| issue.rule_id.as_deref() == Some("GUARD039"))
+            .all(|issue| issue.severity == Severity::Medium));
+    }
+
+    #[test]
+    fn crate_level_unsafe_allows_are_reported() {
+        let issues = scan(
+            &config(),
+            "#![allow(dead_code, unsafe_code)]\n\
+             #![deny(unsafe_code)]\n\
+             #[allow(unsafe_code)]\n\
+             fn f() {}\n",
+        );
+
+        assert_eq!(findings(&issues), [("GUARD038", 1)]);
+        assert_eq!(issues[0].severity, Severity::Low);
+        assert_eq!(issues[0].location.as_ref().unwrap().column, 1);
+    }
+
+    #[test]
+    fn dependency_profiles_disabling_overflow_checks_are_reported() {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0");
+        project.file(
+            "dep",
+            "Cargo.toml",
+            "[package]\n\
+             name = \"dep\"\n\
+             \n\
+             [profile.release]\n\
+             overflow-checks = false\n\
+             \n\
+             [profile.dev.package.\"*\"]\n\
+             overflow-checks = true\n\
+             \n\
+             [dependencies]\n\
+             overflow-checks = false # not a profile\n",
+        );
+
+        let issues = SecurityScanner::new(&config())
+            .unwrap()
+            .scan_package(&project.package("dep"))
+            .unwrap();
+
+        assert_eq!(findings(&issues), [("GUARD037", 5)]);
+        assert_eq!(
+            issues[0].description,
+            "dep disables overflow checks in [profile.release] - integer overflow wraps silently"
+        );
+    }
+
+    #[test]
+    fn functions_named_like_eval_are_not_code_evaluation() {
+        let source = "fn evaluate_score(x: u32) -> u32 { x }\n\
+                      fn eval(x: u32) -> u32 { evaluate_score(x) }\n\
+                      fn f(engine: &Engine, x: u32) -> u32 {\n\
+                          engine.eval(\"1 + 1\");\n\
+                          engine . eval (x)\n\
+                      }\n";
+
+        assert!(scan(&config(), source)
+            .iter()
+            .all(|issue| issue.severity != Severity::Critical));
+        // Only calls of a free function named `eval`
+        let issues = scan(&config(), "fn f(x: u32) -> u32 {\n    eval(x)\n}\n");
+        assert_eq!(findings(&issues), [("GUARD005", 2)]);
+    }
+
+    #[test]
+    fn crlf_files_are_located_like_their_lf_copy() {
+        let source = "// header\nuse std::process::Command;\n\nfn f() {\n    unsafe { g() }\n}\n";
+        let lf = scan(&config(), source);
+        let crlf = scan(&config(), &source.replace('\n', "\r\n"));
+
+        let positions = |issues: &[SecurityIssue]| -> Vec<(usize, usize)> {
+            issues
+                .iter()
+                .map(|issue| {
+                    let location = issue.location.as_ref().unwrap();
+                    (location.line, location.column)
+                })
+                .collect()
+        };
+        assert_eq!(positions(&lf), [(2, 5), (5, 5)]);
+        assert_eq!(positions(&crlf), positions(&lf));
+    }
+
+    #[test]
+    fn invalid_utf8_does_not_abort_the_scan() {
+        let dir = TempDir::new().unwrap();
+        let mut content = b"const BYTES: &str = \"".to_vec();
+        content.extend([0xff, 0xfe, b'"', b';', b'\n']);
+        content.extend(b"fn f() { unsafe { g() } }\n");
+        fs::write(dir.path().join("binary.rs"), content).unwrap();
+        fs::write(dir.path().join("lib.rs"), "use std::process::Command;\n").unwrap();
+
+        let issues = SecurityScanner::new(&config())
+            .unwrap()
+            .scan_path(dir.path())
+            .unwrap();
+
+        let mut found: Vec<(String, &str, usize)> = issues
+            .iter()
+            .map(|issue| {
+                let location = issue.location.as_ref().unwrap();
+                let file = location.file.file_name().unwrap().to_string_lossy();
+                (
+                    file.into_owned(),
+                    issue.rule_id.as_deref().unwrap(),
+                    location.line,
+                )
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("binary.rs".to_string(), "GUARD001", 2),
+                ("lib.rs".to_string(), "GUARD006", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_starts_end_lines_at_line_feeds() {
+        let content = "a\r\nbc\nd";
+
+        let starts = line_starts(content);
+        assert_eq!(starts, [0, 3, 6]);
+        let location = locate(Path::new("f.rs"), content, &starts, 4);
+        assert_eq!((location.line, location.column), (2, 2));
+        assert_eq!(offset(content, &starts, &location), 4);
+    }
+
+    /// The files under `dir` with a finding, relative to `dir`.
+    fn scanned_files(config: &Config, dir: &Path) -> Vec<PathBuf> {
+        let issues = SecurityScanner::new(config)
+            .unwrap()
+            .scan_path(dir)
+            .unwrap();
+        let mut files: Vec<PathBuf> = issues
+            .into_iter()
+            .map(|issue| {
+                let file = issue.location.unwrap().file;
+                file.strip_prefix(dir).unwrap().to_path_buf()
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    #[test]
+    fn target_and_excluded_directories_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        for path in ["src", "target/debug/build", ".git/hooks", "generated"] {
+            fs::create_dir_all(dir.path().join(path)).unwrap();
+            fs::write(
+                dir.path().join(path).join("lib.rs"),
+                "fn f() { unsafe { g() } }\n",
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            scanned_files(&config(), dir.path()),
+            [Path::new("generated/lib.rs"), Path::new("src/lib.rs")]
+        );
+        let config = Config {
+            include_target: true,
+            exclude_dirs: vec!["generated".to_string()],
+            ..config()
+        };
+        assert_eq!(
+            scanned_files(&config, dir.path()),
+            [
+                Path::new("src/lib.rs"),
+                Path::new("target/debug/build/lib.rs")
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loops_terminate() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/lib.rs"), "fn f() { unsafe { g() } }\n").unwrap();
+        std::os::unix::fs::symlink(&src, src.join("nested/back")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), src.join("root")).unwrap();
+
+        let issues = SecurityScanner::new(&config())
+            .unwrap()
+            .scan_path(&src)
+            .unwrap();
+
+        assert_eq!(findings(&issues), [("GUARD001", 1)]);
+    }
+
+    #[test]
+    fn each_kind_of_unsafe_has_its_own_rule() {
+        let samples = [
+            ("unsafe impl Send for Handle {}", "GUARD052", Severity::High),
+            (
+                "unsafe impl<T: Send> Sync for Shared<T> {}",
+                "GUARD053",
+                Severity::High,
+            ),
+            (
+                "pub unsafe fn from_raw(ptr: usize) -> Self { todo!() }",
+                "GUARD054",
+                Severity::Medium,
+            ),
+            (
+                "pub unsafe extern \"C\" fn callback() {}",
+                "GUARD054",
+                Severity::Medium,
+            ),
+            ("pub unsafe trait Zeroable {}", "GUARD055", Severity::Low),
+            (
+                "fn f(p: *const u8) -> u8 { let x = *p.add(1); x }",
+                "GUARD056",
+                Severity::High,
+            ),
+            (
+                "fn f(a: usize) -> u8 { let x = *(a as *const u8); x }",
+                "GUARD056",
+                Severity::High,
+            ),
+            (
+                "fn f(p: *const u8) -> u8 { std::ptr::read(p) }",
+                "GUARD056",
+                Severity::High,
+            ),
+            (
+                "fn f(p: *mut u32) { p.write_volatile(1) }",
+                "GUARD056",
+                Severity::High,
+            ),
+        ];
+
+        for (source, rule, severity) in samples {
+            let issues = scan(&config(), source);
+            let found: Vec<(&str, &Severity)> = issues
+                .iter()
+                .map(|issue| (issue.rule_id.as_deref().unwrap(), &issue.severity))
+                .collect();
+            assert_eq!(found, [(rule, &severity)], "{}", source);
+        }
+    }
+
+    #[test]
+    fn safe_code_resembling_unsafe_patterns_is_not_reported() {
+        let source = "impl Send for Handle {}\n\
+                      fn area(w: usize, h: usize) -> usize { w * h.add(1) }\n\
+                      fn g(v: &[u8]) -> usize { v.len() * (v.len() as usize) }\n\
+                      trait Unsafe {}\n";
+
+        assert_eq!(findings(&scan(&config(), source)), []);
+    }
+
+    #[test]
+    fn dereferences_of_variables_are_reported_in_unsafe_blocks_only() {
+        let source = "fn f(p: *const u8, a: u8) -> u8 { unsafe { *p * a } }\n\
+                      fn g(p: &u8, a: u8) -> u8 { *p * a }\n\
+                      fn h(p: *mut u8) { unsafe { let q: *mut u8 = p; *q = 1 } }\n\
+                      fn k(v: &[u8]) -> u8 { unsafe { *v.get_unchecked(0) } }\n";
+
+        assert_eq!(
+            findings(&scan(&config(), source)),
+            [
+                ("GUARD001", 1),
+                ("GUARD056", 1),
+                ("GUARD001", 3),
+                ("GUARD056", 3),
+                ("GUARD001", 4),
+                ("GUARD056", 4),
+            ]
+        );
     }
 }
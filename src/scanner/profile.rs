@@ -0,0 +1,112 @@
+use cargo_metadata::Package;
+use std::path::Path;
+
+use crate::models::{SecurityIssue, Severity};
+
+use super::{line_starts, locate};
+
+/// Reports every `overflow-checks = false` in the `[profile]` tables of
+/// `content`, the manifest of `package` at `path`, including per-package
+/// and build script overrides (GUARD037).
+pub(super) fn check(package: &Package, path: &Path, content: &str) -> Vec<SecurityIssue> {
+    let line_starts = line_starts(content);
+    let mut issues = Vec::new();
+    let mut table = "";
+    for (line, &start) in content.lines().zip(&line_starts) {
+        // Comments cannot hold a setting, and nothing in a profile quotes a #
+        let code = line.split('#').next().unwrap_or_default().trim();
+        if let Some(header) = code.strip_prefix('[') {
+            table = header.trim_matches(|c| c == '[' || c == ']').trim();
+            continue;
+        }
+        let compact: String = code.split_whitespace().collect();
+        let Some(column) = compact.find("overflow-checks=false") else {
+            continue;
+        };
+        // A dotted key outside any table, e.g. `profile.release.overflow-checks`
+        let key = &compact[..column];
+        let profile = match (table, key.strip_prefix("profile.")) {
+            (table, _) if table == "profile" || table.starts_with("profile.") => table,
+            ("", Some(_)) => key.trim_end_matches('.'),
+            _ => continue,
+        };
+
+        let offset = start + line.find("overflow-checks").unwrap_or_default();
+        issues.push(SecurityIssue {
+            severity: Severity::Medium,
+            description: format!(
+                "{} disables overflow checks in [{}] - integer overflow wraps silently",
+                package.name, profile
+            ),
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD037".to_string()),
+            location: Some(locate(path, content, &line_starts, offset)),
+            ..Default::default()
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+
+    /// The profile and line of each finding in `manifest`.
+    fn profiles(manifest: &str) -> Vec<(String, usize)> {
+        let mut project = Project::new();
+        project.add("dep", "1.0.0");
+        check(&project.package("dep"), Path::new("Cargo.toml"), manifest)
+            .into_iter()
+            .map(|issue| {
+                let profile = issue
+                    .description
+                    .split(['[', ']'])
+                    .nth(1)
+                    .unwrap_or_default();
+                (profile.to_string(), issue.location.unwrap().line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_profile_tables_and_dotted_keys() {
+        let found = profiles(
+            "profile.release.overflow-checks = false\n\
+             \n\
+             [profile.bench]\n\
+             overflow-checks=false\n\
+             \n\
+             [profile.release.build-override]\n\
+             overflow-checks = false\n\
+             \n\
+             [profile.release.package.foo]\n\
+             opt-level = 3\n\
+             overflow-checks = false  # fast\n",
+        );
+
+        assert_eq!(
+            found,
+            [
+                ("profile.release".to_string(), 1),
+                ("profile.bench".to_string(), 4),
+                ("profile.release.build-override".to_string(), 7),
+                ("profile.release.package.foo".to_string(), 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_enabled_checks_comments_and_other_tables() {
+        let found = profiles(
+            "[profile.release]\n\
+             overflow-checks = true\n\
+             # overflow-checks = false\n\
+             \n\
+             [package.metadata.build]\n\
+             overflow-checks = false\n",
+        );
+
+        assert!(found.is_empty());
+    }
+}
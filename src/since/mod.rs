@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+use tracing::{info, warn};
+
+use crate::error::{GuardianError, Result};
+
+/// Packages whose sources were not scanned with `--since`, because
+/// `Cargo.lock` pins them at the version it did at that git ref.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnchangedPackages {
+    /// The git ref compared against
+    pub since: String,
+    /// `name@version` of each skipped package
+    pub packages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// The `(name, version)` pairs `Cargo.lock` in `workspace_root` pinned at
+/// `git_ref`, or `None` when it did not exist there, so that everything
+/// counts as changed. Fails when `git_ref` is not a commit of the
+/// repository.
+pub fn locked_at(
+    workspace_root: &Path,
+    git_ref: &str,
+) -> Result<Option<HashSet<(String, String)>>> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(workspace_root)
+            .args(args)
+            .output()
+            .map_err(|err| GuardianError::io("git", err))
+    };
+
+    let commit = format!("{}^{{commit}}", git_ref);
+    if !git(&["rev-parse", "--verify", "--quiet", &commit])?
+        .status
+        .success()
+    {
+        return Err(GuardianError::Config {
+            message: format!(
+                "--since: {} is not a commit of the git repository at {}",
+                git_ref,
+                workspace_root.display()
+            ),
+            source: None,
+        });
+    }
+
+    // `./` resolves the path from the workspace root, wherever it sits in
+    // the repository
+    let output = git(&["show", &format!("{}:./Cargo.lock", git_ref)])?;
+    if !output.status.success() {
+        warn!("No Cargo.lock at {}, scanning every package", git_ref);
+        return Ok(None);
+    }
+
+    let content = String::from_utf8_lossy(&output.stdout);
+    let lockfile: Lockfile = match toml::from_str(&content) {
+        Ok(lockfile) => lockfile,
+        Err(err) => {
+            warn!(
+                "Unreadable Cargo.lock at {}, scanning every package: {}",
+                git_ref, err
+            );
+            return Ok(None);
+        }
+    };
+    info!(
+        "Comparing against the {} packages locked at {}",
+        lockfile.package.len(),
+        git_ref
+    );
+    Ok(Some(
+        lockfile
+            .package
+            .into_iter()
+            .map(|package| (package.name, package.version))
+            .collect(),
+    ))
+}
@@ -0,0 +1,173 @@
+use cargo_metadata::Package;
+use glob::Pattern;
+
+use crate::error::{GuardianError, Result};
+use crate::models::{SecurityIssue, Severity};
+
+/// Flags dependencies that bypass the registry: git repositories, unless
+/// allowlisted, and local paths that a publishable package depends on.
+pub struct SourceChecker {
+    allow_git: Vec<Pattern>,
+}
+
+impl SourceChecker {
+    pub fn new(allow_git: &[String]) -> Result<Self> {
+        let allow_git = allow_git
+            .iter()
+            .map(|url| {
+                Pattern::new(url).map_err(|err| {
+                    GuardianError::config(format!("Invalid allow-git pattern {}", url), err)
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { allow_git })
+    }
+
+    /// Checks a non-member `package`, pulled in by `dependents`.
+    pub fn check(&self, package: &Package, dependents: &[&Package]) -> Option<SecurityIssue> {
+        let (severity, description, rule_id) = match &package.source {
+            Some(source) if source.repr.starts_with("git+") => {
+                let (url, rev) = parse_git_source(&source.repr);
+                if self.allow_git.iter().any(|pattern| pattern.matches(url)) {
+                    return None;
+                }
+                (
+                    Severity::Medium,
+                    format!(
+                        "{} is fetched from git repository {} at revision {}",
+                        package.name,
+                        url,
+                        rev.unwrap_or("unknown")
+                    ),
+                    "GUARD025",
+                )
+            }
+            Some(_) => return None,
+            None => {
+                let publishable: Vec<&str> = dependents
+                    .iter()
+                    .filter(|dependent| is_publishable(dependent))
+                    .map(|dependent| dependent.name.as_str())
+                    .collect();
+                if publishable.is_empty() {
+                    return None;
+                }
+                let path = package
+                    .manifest_path
+                    .parent()
+                    .unwrap_or(&package.manifest_path);
+                (
+                    Severity::High,
+                    format!(
+                        "{} is a local path dependency ({}) of publishable {}",
+                        package.name,
+                        path,
+                        publishable.join(", ")
+                    ),
+                    "GUARD026",
+                )
+            }
+        };
+
+        Some(SecurityIssue {
+            severity,
+            description,
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some(rule_id.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// Splits `git+https://host/repo?branch=main#<rev>` into the repository URL
+/// (without query) and the locked revision.
+fn parse_git_source(repr: &str) -> (&str, Option<&str>) {
+    let source = repr.trim_start_matches("git+");
+    let (url, rev) = match source.split_once('#') {
+        Some((url, rev)) => (url, Some(rev)),
+        None => (source, None),
+    };
+    (url.split('?').next().unwrap_or(url), rev)
+}
+
+/// Publishing is unrestricted when `publish` is unset and forbidden when it
+/// is an empty list.
+fn is_publishable(package: &Package) -> bool {
+    package
+        .publish
+        .as_ref()
+        .is_none_or(|registries| !registries.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Project;
+    use serde_json::json;
+
+    const GIT: &str = "git+https://github.com/org/fork?branch=main#0123abc";
+
+    fn project() -> Project {
+        let mut project = Project::new();
+        project
+            .add("serde", "1.0.0")
+            .add_from("fork", "0.2.0", Some(GIT))
+            .add_from("local", "0.1.0", None);
+        project
+    }
+
+    fn rule(issue: Option<SecurityIssue>) -> Option<String> {
+        issue.and_then(|issue| issue.rule_id)
+    }
+
+    #[test]
+    fn registry_dependencies_are_clean() {
+        let project = project();
+        let checker = SourceChecker::new(&[]).unwrap();
+
+        assert!(checker
+            .check(&project.package("serde"), &[&project.package("app")])
+            .is_none());
+    }
+
+    #[test]
+    fn flags_git_dependencies_with_their_url_and_revision() {
+        let project = project();
+        let checker = SourceChecker::new(&[]).unwrap();
+
+        let issue = checker
+            .check(&project.package("fork"), &[&project.package("app")])
+            .unwrap();
+
+        assert_eq!(issue.rule_id.as_deref(), Some("GUARD025"));
+        assert_eq!(issue.severity, Severity::Medium);
+        assert_eq!(
+            issue.description,
+            "fork is fetched from git repository https://github.com/org/fork at revision 0123abc"
+        );
+    }
+
+    #[test]
+    fn allowlisted_git_repositories_are_clean() {
+        let project = project();
+        let checker = SourceChecker::new(&["https://github.com/org/*".to_string()]).unwrap();
+
+        assert!(checker
+            .check(&project.package("fork"), &[&project.package("app")])
+            .is_none());
+    }
+
+    #[test]
+    fn flags_path_dependencies_of_publishable_packages() {
+        let mut project = project();
+        let checker = SourceChecker::new(&[]).unwrap();
+
+        let issue = checker.check(&project.package("local"), &[&project.package("app")]);
+        assert_eq!(rule(issue).as_deref(), Some("GUARD026"));
+
+        project.set("app", "publish", json!([]));
+        let issue = checker.check(&project.package("local"), &[&project.package("app")]);
+        assert_eq!(rule(issue), None);
+    }
+}
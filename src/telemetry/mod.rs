@@ -0,0 +1,64 @@
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use std::env;
+use std::sync::OnceLock;
+use tracing::level_filters::LevelFilter;
+use tracing::{warn, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// The target of the spans around the phases of the analysis. They are
+/// only exported, never logged.
+pub const TARGET: &str = "telemetry";
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// A layer exporting the phase spans over OTLP/HTTP, when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`)
+/// is set. The exporter takes its endpoint, headers and timeout from the
+/// standard `OTEL_EXPORTER_OTLP_*` variables, and the resource from
+/// `OTEL_SERVICE_NAME` and `OTEL_RESOURCE_ATTRIBUTES`.
+pub fn layer<S>() -> Result<Option<Box<dyn Layer<S> + Send + Sync>>, ExporterBuildError>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    let configured = [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .iter()
+    .any(|var| env::var_os(var).is_some_and(|value| !value.is_empty()));
+    if !configured {
+        return Ok(None);
+    }
+
+    let exporter = SpanExporter::builder().with_http().build()?;
+    let mut resource = Resource::builder();
+    if env::var_os("OTEL_SERVICE_NAME").is_none() {
+        resource = resource.with_service_name(env!("CARGO_PKG_NAME"));
+    }
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build();
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+    let _ = PROVIDER.set(provider);
+
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(Targets::new().with_target(TARGET, LevelFilter::INFO));
+    Ok(Some(layer.boxed()))
+}
+
+/// Sends the spans still buffered. Must be called before the process
+/// exits, or they are lost.
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(err) = provider.shutdown() {
+            warn!("Failed to export telemetry: {}", err);
+        }
+    }
+}
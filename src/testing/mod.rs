@@ -0,0 +1,274 @@
+//! Fixtures for unit tests: a project with resolved dependencies, saved as
+//! `cargo metadata` output so that analyses run without cargo or the
+//! network.
+
+use cargo_metadata::{DependencyKind, Metadata, Package};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::analyzer::{Analyzer, DependencyAnalysis};
+use crate::config::Config;
+
+const CRATES_IO: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+/// The root package `app 0.1.0` and its resolved dependencies, each with a
+/// source directory in a temporary directory.
+pub struct Project {
+    dir: TempDir,
+    packages: Vec<Value>,
+    /// The dependencies of each package id, by id: their name and kinds
+    edges: BTreeMap<String, BTreeMap<String, (String, Vec<DependencyKind>)>>,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        let mut project = Self {
+            dir: TempDir::new().expect("temporary directory"),
+            packages: Vec::new(),
+            edges: BTreeMap::new(),
+        };
+        let root = project.dir.path().join("app");
+        let source = format!("path+file://{}", root.display());
+        project.add_package("app", "0.1.0", None, &root, &source);
+        project.file("app", "Cargo.toml", "[package]\nname = \"app\"\n");
+        project
+    }
+
+    /// The workspace root, holding the root package.
+    pub fn root(&self) -> PathBuf {
+        self.dir.path().join("app")
+    }
+
+    /// Adds a crates.io package.
+    pub fn add(&mut self, name: &str, version: &str) -> &mut Self {
+        self.add_from(name, version, Some(CRATES_IO))
+    }
+
+    /// Adds a package from `source`: a registry or git source, or a path
+    /// dependency when `None`.
+    pub fn add_from(&mut self, name: &str, version: &str, source: Option<&str>) -> &mut Self {
+        let dir = self.dir.path().join(format!("{}-{}", name, version));
+        let id_source =
+            source.map_or_else(|| format!("path+file://{}", dir.display()), str::to_string);
+        self.add_package(name, version, source, &dir, &id_source);
+        self
+    }
+
+    fn add_package(
+        &mut self,
+        name: &str,
+        version: &str,
+        source: Option<&str>,
+        dir: &Path,
+        id_source: &str,
+    ) {
+        fs::create_dir_all(dir.join("src")).expect("package directory");
+        self.packages.push(json!({
+            "name": name,
+            "version": version,
+            "id": format!("{} {} ({})", name, version, id_source),
+            "source": source,
+            "description": null,
+            "dependencies": [],
+            "license": "MIT",
+            "license_file": null,
+            "targets": [{
+                "name": name,
+                "kind": ["lib"],
+                "src_path": dir.join("src/lib.rs"),
+            }],
+            "features": {},
+            "manifest_path": dir.join("Cargo.toml"),
+            "readme": null,
+            "repository": null,
+            "homepage": null,
+            "documentation": null,
+            "edition": "2021",
+            "links": null,
+            "publish": null,
+            "default_run": null,
+            "rust_version": null,
+        }));
+    }
+
+    /// Sets a field of the `cargo metadata` entry of the package `name`.
+    pub fn set(&mut self, name: &str, field: &str, value: Value) -> &mut Self {
+        self.package_mut(name)[field] = value;
+        self
+    }
+
+    /// Makes `from` depend on `to`, both given by name.
+    pub fn depend(&mut self, from: &str, to: &str, kind: DependencyKind) -> &mut Self {
+        let (to_id, version, source) = {
+            let package = self.package_mut(to);
+            (
+                package["id"].clone(),
+                package["version"].clone(),
+                package["source"].clone(),
+            )
+        };
+        let from_package = self.package_mut(from);
+        let from_id = from_package["id"].as_str().unwrap_or_default().to_string();
+        from_package["dependencies"]
+            .as_array_mut()
+            .expect("dependency list")
+            .push(json!({
+                "name": to,
+                "source": source,
+                "req": format!("^{}", version.as_str().unwrap_or_default()),
+                "kind": kind,
+                "optional": false,
+                "uses_default_features": true,
+                "features": [],
+                "target": null,
+                "rename": null,
+                "registry": null,
+                "path": null,
+            }));
+        let to_id = to_id.as_str().unwrap_or_default().to_string();
+        let (_, kinds) = self
+            .edges
+            .entry(from_id)
+            .or_default()
+            .entry(to_id)
+            .or_insert_with(|| (to.to_string(), Vec::new()));
+        kinds.push(kind);
+        self
+    }
+
+    /// Changes the version requirement with which `from` depends on `to`.
+    pub fn require(&mut self, from: &str, to: &str, req: &str) -> &mut Self {
+        let dependencies = self.package_mut(from)["dependencies"]
+            .as_array_mut()
+            .expect("dependency list");
+        let dependency = dependencies
+            .iter_mut()
+            .find(|dependency| dependency["name"] == to)
+            .unwrap_or_else(|| panic!("{} does not depend on {}", from, to));
+        dependency["req"] = json!(req);
+        self
+    }
+
+    fn package_mut(&mut self, name: &str) -> &mut Value {
+        self.packages
+            .iter_mut()
+            .find(|package| package["name"] == name)
+            .unwrap_or_else(|| panic!("no package {}", name))
+    }
+
+    /// The source directory of the package named `name`.
+    pub fn package_dir(&self, name: &str) -> PathBuf {
+        let package = self
+            .packages
+            .iter()
+            .find(|package| package["name"] == name)
+            .unwrap_or_else(|| panic!("no package {}", name));
+        Path::new(package["manifest_path"].as_str().unwrap_or_default())
+            .parent()
+            .expect("package directory")
+            .to_path_buf()
+    }
+
+    /// Writes `content` to `path` within the package named `name`.
+    pub fn file(&self, name: &str, path: &str, content: &str) -> &Self {
+        let path = self.package_dir(name).join(path);
+        fs::create_dir_all(path.parent().expect("parent directory")).expect("directory");
+        fs::write(path, content).expect("fixture file");
+        self
+    }
+
+    /// Adds a RustSec advisory against `package`, patched in `patched`, to
+    /// the advisory database at [`advisory_db`](Self::advisory_db).
+    pub fn advisory(&self, package: &str, id: &str, patched: &[&str]) -> &Self {
+        let dir = self.advisory_db().join("crates").join(package);
+        fs::create_dir_all(&dir).expect("advisory directory");
+        let advisory = format!(
+            "```toml\n[advisory]\nid = \"{}\"\npackage = \"{}\"\ndate = \"2024-01-01\"\n\n\
+             [versions]\npatched = {:?}\n```\n\n# Vulnerability in {}\n",
+            id, package, patched, package
+        );
+        fs::write(dir.join(format!("{}.md", id)), advisory).expect("advisory");
+        self
+    }
+
+    /// The resolved package named `name`, as the analyzer sees it.
+    pub fn package(&self, name: &str) -> Package {
+        self.metadata()
+            .packages
+            .into_iter()
+            .find(|package| package.name == name)
+            .unwrap_or_else(|| panic!("no package {}", name))
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        serde_json::from_value(self.metadata_json()).expect("cargo metadata")
+    }
+
+    pub fn advisory_db(&self) -> PathBuf {
+        self.dir.path().join("advisory-db")
+    }
+
+    fn metadata_json(&self) -> Value {
+        let nodes: Vec<Value> = self
+            .packages
+            .iter()
+            .map(|package| {
+                let id = package["id"].as_str().unwrap_or_default();
+                let deps = self.edges.get(id).into_iter().flatten();
+                json!({
+                    "id": id,
+                    "dependencies": deps.clone().map(|(to, _)| to).collect::<Vec<_>>(),
+                    "deps": deps
+                        .map(|(to, (name, kinds))| json!({
+                            "name": name,
+                            "pkg": to,
+                            "dep_kinds": kinds
+                                .iter()
+                                .map(|kind| json!({ "kind": kind, "target": null }))
+                                .collect::<Vec<_>>(),
+                        }))
+                        .collect::<Vec<_>>(),
+                    "features": [],
+                })
+            })
+            .collect();
+        let root = &self.packages[0]["id"];
+        json!({
+            "packages": self.packages,
+            "workspace_members": [root],
+            "resolve": { "nodes": nodes, "root": root },
+            "workspace_root": self.root(),
+            "target_directory": self.root().join("target"),
+            "version": 1,
+        })
+    }
+
+    /// A configuration analyzing this project offline, with the fixture
+    /// advisory database and without the cache or registry checks.
+    pub fn config(&self) -> Config {
+        let path = self.dir.path().join("metadata.json");
+        fs::write(&path, self.metadata_json().to_string()).expect("metadata file");
+        fs::create_dir_all(self.advisory_db().join("crates")).expect("advisory database");
+        Config {
+            metadata_json: Some(path),
+            advisory_db: Some(self.advisory_db()),
+            offline: true,
+            cache: false,
+            yanked: false,
+            quiet: true,
+            ..Config::default()
+        }
+    }
+
+    pub async fn analyze(&self, config: Config) -> DependencyAnalysis {
+        let manifest = self.root().join("Cargo.toml");
+        Analyzer::new(manifest.display().to_string(), config)
+            .expect("analyzer")
+            .analyze()
+            .await
+            .expect("analysis")
+    }
+}
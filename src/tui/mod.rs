@@ -0,0 +1,320 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::io;
+
+use crate::analyzer::DependencyAnalysis;
+use crate::error::{GuardianError, Result};
+use crate::models::{Confidence, SecurityIssue, Severity};
+use crate::rules;
+
+const HELP: &str =
+    "↑/↓ select  ←/→ or Tab switch pane  1-5 minimum severity (critical..info)  0 all  q quit";
+
+/// Browses the findings of `analysis` in a terminal UI until the user
+/// quits: packages with their issue counts on the left, the selected
+/// package's findings on the right and the selected finding below them.
+pub fn run(analysis: &DependencyAnalysis) -> Result<()> {
+    browse(analysis).map_err(GuardianError::Terminal)
+}
+
+fn browse(analysis: &DependencyAnalysis) -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    if let Err(err) = execute!(stdout, EnterAlternateScreen) {
+        disable_raw_mode()?;
+        return Err(err);
+    }
+
+    let result = Terminal::new(CrosstermBackend::new(stdout))
+        .and_then(|mut terminal| Browser::new(analysis).run(&mut terminal));
+
+    // Restore the terminal whatever happened
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    result
+}
+
+#[derive(PartialEq)]
+enum Pane {
+    Packages,
+    Findings,
+}
+
+struct Browser<'a> {
+    analysis: &'a DependencyAnalysis,
+    /// Findings below this severity are hidden
+    min_severity: Severity,
+    /// Packages with findings at or above `min_severity`
+    packages: Vec<(&'a str, Vec<&'a SecurityIssue>)>,
+    package_state: ListState,
+    finding_state: ListState,
+    focus: Pane,
+}
+
+impl<'a> Browser<'a> {
+    fn new(analysis: &'a DependencyAnalysis) -> Self {
+        let mut browser = Self {
+            analysis,
+            min_severity: Severity::Info,
+            packages: Vec::new(),
+            package_state: ListState::default(),
+            finding_state: ListState::default(),
+            focus: Pane::Packages,
+        };
+        browser.filter(Severity::Info);
+        browser
+    }
+
+    fn run(mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.step(1),
+                KeyCode::Left | KeyCode::Char('h') => self.focus = Pane::Packages,
+                KeyCode::Right | KeyCode::Char('l') => self.focus = Pane::Findings,
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Pane::Packages => Pane::Findings,
+                        Pane::Findings => Pane::Packages,
+                    }
+                }
+                KeyCode::Char('1') => self.filter(Severity::Critical),
+                KeyCode::Char('2') => self.filter(Severity::High),
+                KeyCode::Char('3') => self.filter(Severity::Medium),
+                KeyCode::Char('4') => self.filter(Severity::Low),
+                KeyCode::Char('5') | KeyCode::Char('0') => self.filter(Severity::Info),
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows only findings at or above `min_severity`, keeping the selected
+    /// package when it still has some.
+    fn filter(&mut self, min_severity: Severity) {
+        let selected = self.selected_package().map(|(name, _)| *name);
+        self.packages = self
+            .analysis
+            .security_issues
+            .iter()
+            .map(|(package, issues)| {
+                let shown = issues
+                    .iter()
+                    .filter(|issue| issue.severity.meets(&min_severity))
+                    .collect();
+                (package.as_str(), shown)
+            })
+            .filter(|(_, issues): &(_, Vec<_>)| !issues.is_empty())
+            .collect();
+        self.min_severity = min_severity;
+
+        let index = selected.and_then(|name| {
+            self.packages
+                .iter()
+                .position(|(package, _)| *package == name)
+        });
+        let index = index.or((!self.packages.is_empty()).then_some(0));
+        self.package_state.select(index);
+        self.finding_state.select(index.map(|_| 0));
+    }
+
+    fn selected_package(&self) -> Option<&(&'a str, Vec<&'a SecurityIssue>)> {
+        self.packages.get(self.package_state.selected()?)
+    }
+
+    fn selected_finding(&self) -> Option<&'a SecurityIssue> {
+        let (_, issues) = self.selected_package()?;
+        issues.get(self.finding_state.selected()?).copied()
+    }
+
+    /// Moves the selection of the focused pane by `delta`, within bounds.
+    fn step(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Packages => (&mut self.package_state, self.packages.len()),
+            Pane::Findings => {
+                let len = self
+                    .selected_package()
+                    .map_or(0, |(_, issues)| issues.len());
+                (&mut self.finding_state, len)
+            }
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0);
+        state.select(Some(current.saturating_add_signed(delta).min(len - 1)));
+        if self.focus == Pane::Packages {
+            self.finding_state.select(Some(0));
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, help] = split(
+            Direction::Vertical,
+            frame.size(),
+            [Constraint::Min(0), Constraint::Length(1)],
+        );
+        let [left, right] = split(
+            Direction::Horizontal,
+            body,
+            [Constraint::Percentage(30), Constraint::Percentage(70)],
+        );
+        let [findings, details] = split(
+            Direction::Vertical,
+            right,
+            [Constraint::Percentage(55), Constraint::Percentage(45)],
+        );
+
+        let packages: Vec<ListItem> = self
+            .packages
+            .iter()
+            .map(|(package, issues)| {
+                let worst = issues.iter().map(|issue| &issue.severity).max();
+                Line::from(vec![
+                    Span::raw(format!("{} ", package)),
+                    Span::styled(
+                        format!("({})", issues.len()),
+                        worst.map_or(Style::default(), severity_style),
+                    ),
+                ])
+                .into()
+            })
+            .collect();
+        let title = format!(
+            " Packages ({}, {} and above) ",
+            self.packages.len(),
+            self.min_severity
+        );
+        let list = List::new(packages)
+            .block(pane(title, self.focus == Pane::Packages))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.package_state);
+
+        let issues = self
+            .selected_package()
+            .map(|(_, issues)| issues.as_slice())
+            .unwrap_or_default();
+        let items: Vec<ListItem> = issues
+            .iter()
+            .map(|issue| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("[{}] ", issue.severity),
+                        severity_style(&issue.severity),
+                    ),
+                    Span::raw(issue.description.as_str()),
+                ])
+                .into()
+            })
+            .collect();
+        let list = List::new(items)
+            .block(pane(" Findings ".to_string(), self.focus == Pane::Findings))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, findings, &mut self.finding_state);
+
+        let text = self.selected_finding().map(describe).unwrap_or_default();
+        let paragraph = Paragraph::new(text)
+            .block(pane(" Details ".to_string(), false))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, details);
+
+        frame.render_widget(Paragraph::new(HELP), help);
+    }
+}
+
+/// The rule, location and remediation of a finding, for the details pane.
+fn describe(issue: &SecurityIssue) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(vec![
+        Span::styled(issue.severity.to_string(), severity_style(&issue.severity)),
+        Span::raw(format!(" {}", issue.description)),
+    ])];
+    if let Some(id) = &issue.rule_id {
+        match rules::find(id) {
+            Some(rule) => lines.push(Line::from(format!(
+                "Rule {}: {} - {}",
+                rule.id, rule.title, rule.description
+            ))),
+            None => lines.push(Line::from(format!("Rule {}", id))),
+        }
+    }
+    if let Some(id) = &issue.advisory_id {
+        lines.push(Line::from(format!("Advisory {}", id)));
+    }
+    if issue.confidence < Confidence::High {
+        lines.push(Line::from(format!("Confidence {}", issue.confidence)));
+    }
+    if let Some(severity) = &issue.escalated_from {
+        lines.push(Line::from(format!("Escalated from {}", severity)));
+    }
+    if let Some(informational) = &issue.informational {
+        lines.push(Line::from(format!(
+            "Informational advisory: {}",
+            informational
+        )));
+    }
+    let locations = issue.location.iter().chain(&issue.locations);
+    for location in locations {
+        lines.push(Line::from(format!("--> {}", location)));
+    }
+    if issue.dependency_path.len() > 1 {
+        lines.push(Line::from(format!(
+            "via {}",
+            issue.dependency_path.join(" → ")
+        )));
+    }
+    if let Some(fix) = issue.fix_description() {
+        lines.push(Line::from(fix));
+    }
+    lines
+}
+
+fn split<const N: usize>(
+    direction: Direction,
+    area: ratatui::layout::Rect,
+    constraints: [Constraint; N],
+) -> [ratatui::layout::Rect; N] {
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+    std::array::from_fn(|index| chunks[index])
+}
+
+fn pane(title: String, focused: bool) -> Block<'static> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(style)
+}
+
+/// Severity colors, as in the colored text report.
+fn severity_style(severity: &Severity) -> Style {
+    match severity {
+        Severity::Critical => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        Severity::High => Style::default().fg(Color::Red),
+        Severity::Medium => Style::default().fg(Color::Yellow),
+        Severity::Low => Style::default().fg(Color::Blue),
+        Severity::Info => Style::default().add_modifier(Modifier::DIM),
+    }
+}
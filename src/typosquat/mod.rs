@@ -0,0 +1,144 @@
+use cargo_metadata::Package;
+use std::collections::HashSet;
+
+use crate::models::{SecurityIssue, Severity};
+
+/// Popular crate names, bundled so that the check works offline.
+const POPULAR: &str = include_str!("popular.txt");
+
+/// Legitimate crates whose names happen to be close to a popular one.
+const KNOWN_GOOD: &[&str] = &[
+    "serde_yml",
+    "serde-xml-rs",
+    "rustls-ffi",
+    "tokio-io",
+    "tokio-core",
+    "tokio-uring",
+    "futures-timer",
+    "rand_pcg",
+    "rand_xorshift",
+    "hyper-old-types",
+    "clap-verbosity-flag",
+    "sha-1",
+    "hashlink",
+    "coloured",
+];
+
+/// Prefixes and suffixes that squatters add to a popular name.
+const AFFIXES: &[&str] = &["-rs", "-rust", "-lib", "-crate"];
+const PREFIXES: &[&str] = &["rs-", "rust-", "lib"];
+
+/// Flags crates.io dependencies whose names are one or two edits away from
+/// a popular crate, or a popular name with a squatter's affix.
+pub struct TyposquatChecker {
+    /// Normalized and original names
+    popular: Vec<(String, &'static str)>,
+    allowed: HashSet<String>,
+}
+
+impl TyposquatChecker {
+    /// `allow` lists further crate names that are never reported.
+    pub fn new(allow: &[String]) -> Self {
+        let popular: Vec<(String, &str)> = POPULAR
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|name| (normalize(name), name))
+            .collect();
+        let allowed = popular
+            .iter()
+            .map(|(normalized, _)| normalized.clone())
+            .chain(KNOWN_GOOD.iter().copied().map(normalize))
+            .chain(allow.iter().map(|name| normalize(name)))
+            .collect();
+
+        Self { popular, allowed }
+    }
+
+    pub fn check(&self, package: &Package) -> Option<SecurityIssue> {
+        if !package.source.as_ref().is_some_and(|s| s.is_crates_io()) {
+            return None;
+        }
+        let resembled = self.resembles(&package.name)?;
+
+        Some(SecurityIssue {
+            severity: Severity::High,
+            description: format!(
+                "{} resembles the popular crate {} - possible typosquat, verify it is the intended dependency",
+                package.name, resembled
+            ),
+            affected_versions: vec![package.version.to_string()],
+            rule_id: Some("GUARD032".to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// The popular crate `name` looks like a misspelling of, if any; the most
+    /// popular one when several are equally close.
+    fn resembles(&self, name: &str) -> Option<&'static str> {
+        let name = normalize(name);
+        if self.allowed.contains(&name) {
+            return None;
+        }
+
+        let affixed = |popular: &str| {
+            AFFIXES
+                .iter()
+                .any(|affix| name == format!("{}{}", popular, affix))
+                || PREFIXES
+                    .iter()
+                    .any(|prefix| name == format!("{}{}", prefix, popular))
+        };
+        // Short names are a few edits from many others
+        let max_distance = match name.len() {
+            0..=4 => 0,
+            5..=7 => 1,
+            _ => 2,
+        };
+
+        let mut closest: Option<(usize, &'static str)> = None;
+        for (popular, original) in &self.popular {
+            if popular.len() >= 4 && affixed(popular) {
+                return Some(original);
+            }
+            if popular.len() < 5 || popular.len().abs_diff(name.len()) > max_distance {
+                continue;
+            }
+            let distance = edit_distance(&name, popular);
+            if distance <= max_distance && closest.is_none_or(|(best, _)| distance < best) {
+                closest = Some((distance, original));
+            }
+        }
+        closest.map(|(_, popular)| popular)
+    }
+}
+
+/// crates.io treats `-` and `_` as the same, and names as case-insensitive.
+fn normalize(name: &str) -> String {
+    name.to_ascii_lowercase().replace('_', "-")
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of adjacent characters each count as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    rows[0] = (0..=b.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
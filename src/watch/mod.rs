@@ -0,0 +1,89 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tracing::debug;
+
+use crate::error::{GuardianError, Result};
+use crate::ignore::IGNORE_FILE;
+
+/// Quiet period after the last change before a rescan starts, so that a
+/// save touching several files triggers one run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directories whose contents never affect an analysis.
+const SKIPPED_DIRS: &[&str] = &["target", ".git", "node_modules"];
+
+/// Watches a project directory for changes to manifests, lockfiles,
+/// `.guardianignore` and Rust sources.
+pub struct Watcher {
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<PathBuf>,
+    root: PathBuf,
+}
+
+impl Watcher {
+    pub fn new(root: &Path) -> Result<Self> {
+        let watch_error = |err: notify::Error| GuardianError::Watch {
+            path: root.to_path_buf(),
+            source: err.into(),
+        };
+        let (sender, events) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if !event.kind.is_access() => {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => debug!("Watch error: {}", err),
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            root: root.to_path_buf(),
+        })
+    }
+
+    /// Waits for a relevant change and for the following burst of changes
+    /// to settle.
+    pub async fn changed(&mut self) -> Result<()> {
+        loop {
+            let path = self
+                .events
+                .recv()
+                .await
+                .ok_or_else(|| GuardianError::Watch {
+                    path: self.root.clone(),
+                    source: "file watcher stopped".into(),
+                })?;
+            if self.is_relevant(&path) {
+                debug!("{} changed", path.display());
+                break;
+            }
+        }
+        while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {}
+        Ok(())
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let skipped = relative.components().any(|component| {
+            matches!(component, Component::Normal(name) if SKIPPED_DIRS.iter().any(|dir| name == *dir))
+        });
+        if skipped {
+            return false;
+        }
+
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        matches!(name, "Cargo.toml" | "Cargo.lock" | IGNORE_FILE) || name.ends_with(".rs")
+    }
+}